@@ -0,0 +1,196 @@
+// Timings for the collision code in src/physics.rs, run with `cargo bench`.
+// The game is a binary, so the module is compiled straight into this file.
+#[path = "../src/physics.rs"]
+mod physics;
+
+use raylib::ffi::Rectangle;
+use raylib::prelude::Vector2;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const WIDTH: f32 = 1280.0;
+const HEIGHT: f32 = 720.0;
+const BALL: f32 = 16.0;
+const SAMPLES: usize = 11;
+
+// Runs `f` in batches big enough to take about 50 ms and prints the median
+// time per call.
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut batch = 1;
+    loop {
+        let start = Instant::now();
+        for _ in 0..batch {
+            black_box(f());
+        }
+        if start.elapsed() > Duration::from_millis(50) {
+            break;
+        }
+        batch *= 2;
+    }
+    let mut samples: Vec<Duration> = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..batch {
+                black_box(f());
+            }
+            start.elapsed().div_f64(batch as f64)
+        })
+        .collect();
+    samples.sort();
+    println!("{:<32} {:>12.2?}", name, samples[SAMPLES / 2]);
+}
+
+// `count` bricks in an even grid over the top half of the screen.
+fn grid(count: usize) -> Vec<Rectangle> {
+    let columns = ((count as f32 * 2.0).sqrt().ceil() as usize).min(count);
+    let rows = count.div_ceil(columns);
+    let (width, height) = (WIDTH / columns as f32, HEIGHT / 2.0 / rows as f32);
+    (0..count)
+        .map(|i| Rectangle {
+            x: (i % columns) as f32 * width,
+            y: (i / columns) as f32 * height,
+            width: width - 1.0,
+            height: height - 1.0,
+        })
+        .collect()
+}
+
+fn ball_at(position: Vector2) -> Rectangle {
+    Rectangle {
+        x: position.x,
+        y: position.y,
+        width: BALL,
+        height: BALL,
+    }
+}
+
+fn collisions() {
+    for count in [50, 500, 5000] {
+        let bricks = grid(count);
+        // A ball below the bricks has to be checked against every one of them.
+        let miss = ball_at(Vector2::new(WIDTH / 2.0, HEIGHT - 100.0));
+        bench(&format!("miss, {} bricks", count), || {
+            physics::first_hit(black_box(miss), bricks.iter().copied()).is_some()
+        });
+        let last = bricks[count - 1];
+        let ball = ball_at(Vector2::new(last.x, last.y));
+        bench(&format!("hit, {} bricks", count), || {
+            let hit = physics::first_hit(black_box(ball), bricks.iter().copied()).unwrap();
+            let mut direction = Vector2::new(1.0, -1.0);
+            let centre = Vector2::new(last.x + last.width / 2.0, last.y + last.height / 2.0);
+            let normal =
+                physics::reflect(Vector2::new(last.x, last.y), centre, &hit, &mut direction);
+            normal + hit.contact() + physics::separate(ball, last, normal)
+        });
+    }
+    // The paddle is tilted, so it is tested in its own frame.
+    let paddle = physics::OrientedRect {
+        centre: Vector2::new(WIDTH / 2.0, HEIGHT - 72.0),
+        half_size: Vector2::new(64.0, 8.0),
+        angle: 0.2,
+    };
+    let ball = Vector2::new(WIDTH / 2.0, HEIGHT - 90.0);
+    bench("tilted paddle bounce", || {
+        let contact = physics::circle_contact(black_box(ball), BALL, &paddle).unwrap();
+        let mut direction = Vector2::new(1.0, 1.0);
+        physics::bounce(&mut direction, contact.normal);
+        physics::clamp_upwards(&mut direction, 1.0);
+        direction + contact.point
+    });
+    let bumper = Vector2::new(WIDTH / 2.0, 200.0);
+    let ball = Vector2::new(WIDTH / 2.0 + 20.0, 230.0);
+    bench("bumper bounce", || {
+        let contact = physics::circles_contact(black_box(ball), BALL, bumper, 28.0).unwrap();
+        let mut direction = Vector2::new(-1.0, -1.0);
+        physics::bounce(&mut direction, contact.normal);
+        direction + contact.point
+    });
+    // A deflector across the top left corner.
+    let wall = physics::Segment {
+        a: Vector2::new(0.0, 120.0),
+        b: Vector2::new(120.0, 0.0),
+    };
+    let ball = Vector2::new(60.0, 70.0);
+    bench("angled wall bounce", || {
+        let contact = physics::segment_contact(black_box(ball), BALL, &wall).unwrap();
+        let mut direction = Vector2::new(-1.0, -1.0);
+        physics::bounce(&mut direction, contact.normal);
+        direction + contact.point
+    });
+    // A ball in reach of a whole row of magnets.
+    let magnets: Vec<Vector2> = (0..10)
+        .map(|i| Vector2::new(64.0 + i as f32 * 128.0, 200.0))
+        .collect();
+    let ball = Vector2::new(WIDTH / 2.0, 300.0);
+    bench("magnet pull, 10 magnets", || {
+        let mut forces = physics::Forces::default();
+        forces.add(Vector2::new(120.0, 0.0));
+        for magnet in &magnets {
+            forces.attract(black_box(ball), *magnet, 160.0, 700.0);
+        }
+        let mut direction = Vector2::new(1.0, -1.0);
+        forces.steer(&mut direction, 500.0, 1.0 / 60.0);
+        direction
+    });
+}
+
+// A level of the size the game ships with, played by a paddle that never
+// misses, stepped at 60 fps for up to ten minutes of game time. Returns the
+// number of bricks left.
+fn simulate_level() -> usize {
+    const COLUMNS: usize = 10;
+    const GAP: f32 = 5.0;
+    let width = (WIDTH - GAP) / COLUMNS as f32 - GAP;
+    let mut bricks: Vec<Rectangle> = (0..COLUMNS * 6)
+        .map(|i| Rectangle {
+            x: GAP + (i % COLUMNS) as f32 * (width + GAP),
+            y: 100.0 + (i / COLUMNS) as f32 * (32.0 + GAP),
+            width,
+            height: 32.0,
+        })
+        .collect();
+    let paddle = physics::OrientedRect {
+        centre: Vector2::new(WIDTH / 2.0, HEIGHT - 72.0),
+        half_size: Vector2::new(WIDTH / 2.0, 8.0),
+        angle: 0.1,
+    };
+
+    let dt = 1.0 / 60.0;
+    let mut position = Vector2::new(WIDTH / 3.0, HEIGHT - 120.0);
+    let mut direction = Vector2::new(1.0, -1.0);
+    let mut speed = 500.0;
+    for _ in 0..60 * 60 * 10 {
+        if bricks.is_empty() {
+            break;
+        }
+        if position.y <= 0.0 {
+            direction.y = 1.0;
+        }
+        if position.x <= BALL {
+            direction.x = 1.0;
+        }
+        if position.x >= WIDTH - BALL {
+            direction.x = -1.0;
+        }
+        let ball = ball_at(position);
+        if let Some(contact) = physics::circle_contact(position, BALL, &paddle) {
+            if physics::bounce(&mut direction, contact.normal) {
+                physics::clamp_upwards(&mut direction, 1.0);
+                speed += 2.0;
+            }
+        }
+        if let Some(hit) = physics::first_hit(ball, bricks.iter().copied()) {
+            let brick = bricks.swap_remove(hit.index);
+            let centre = Vector2::new(brick.x + brick.width / 2.0, brick.y + brick.height / 2.0);
+            physics::reflect(position, centre, &hit, &mut direction);
+            speed += 4.0;
+        }
+        position += direction * (speed / 2.0f32.sqrt() * dt);
+    }
+    bricks.len()
+}
+
+fn main() {
+    collisions();
+    bench("simulated level", simulate_level);
+}
@@ -0,0 +1,33 @@
+#version 330
+
+// Single pass bloom: bright parts of the frame bleed a soft glow around them.
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+const vec2 SIZE = vec2(1280.0, 720.0);
+const float THRESHOLD = 0.5;
+const float STRENGTH = 1.5;
+const int RADIUS = 4;
+
+void main()
+{
+    vec4 base = texture(texture0, fragTexCoord);
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int x = -RADIUS; x <= RADIUS; x++) {
+        for (int y = -RADIUS; y <= RADIUS; y++) {
+            vec2 offset = vec2(x, y) * 2.0 / SIZE;
+            vec3 texel = texture(texture0, fragTexCoord + offset).rgb;
+            float weight = 1.0 / (1.0 + float(x * x + y * y));
+            glow += max(texel - THRESHOLD, 0.0) * weight;
+            total += weight;
+        }
+    }
+    finalColor = vec4(base.rgb + glow / total * STRENGTH, base.a) * colDiffuse * fragColor;
+}
@@ -0,0 +1,22 @@
+#version 330
+
+// Chromatic aberration: red and blue drift apart towards the edges of the frame.
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+const float AMOUNT = 0.006;
+
+void main()
+{
+    vec2 offset = (fragTexCoord - 0.5) * AMOUNT;
+    vec4 base = texture(texture0, fragTexCoord);
+    float r = texture(texture0, fragTexCoord + offset).r;
+    float b = texture(texture0, fragTexCoord - offset).b;
+    finalColor = vec4(r, base.g, b, base.a) * colDiffuse * fragColor;
+}
@@ -0,0 +1,32 @@
+#version 330
+
+// Scanlines, a slight barrel distortion and a vignette over the final frame.
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+// One dark line every other row of the 720 line logical frame.
+const float LINES = 360.0;
+const float CURVATURE = 6.0;
+
+void main()
+{
+    // Bend the picture outwards from the centre like a curved tube.
+    vec2 uv = fragTexCoord * 2.0 - 1.0;
+    vec2 offset = uv.yx / CURVATURE;
+    uv = (uv + uv * offset * offset) * 0.5 + 0.5;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        finalColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec4 color = texture(texture0, uv) * colDiffuse * fragColor;
+    float scanline = 0.8 + 0.2 * sin(uv.y * LINES * 6.28318);
+    float vignette = pow(16.0 * uv.x * uv.y * (1.0 - uv.x) * (1.0 - uv.y), 0.25);
+    finalColor = vec4(color.rgb * scanline * vignette, color.a);
+}
@@ -0,0 +1,196 @@
+use crate::config::{Table, Value, Writer};
+use crate::lang::tr;
+use crate::paths;
+use crate::stats::RunStats;
+use crate::text::Text;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DEFINITIONS: &str = include_str!("../achievements/default.toml");
+const UNLOCKED_FILE: &str = "achievements.toml";
+const TOAST_TIME: f32 = 4.0;
+const TOAST_FADE: f32 = 0.5;
+const TOAST_WIDTH: f32 = 420.0;
+const TOAST_HEIGHT: f32 = 64.0;
+
+// What an achievement counts, taken from the stats of the current run.
+#[derive(Clone, Copy)]
+enum Stat {
+    Score,
+    Combo,
+    BallSpeed,
+    Bricks,
+    Levels,
+    FlawlessLevels,
+}
+
+impl Stat {
+    const ALL: [Stat; 6] = [
+        Stat::Score,
+        Stat::Combo,
+        Stat::BallSpeed,
+        Stat::Bricks,
+        Stat::Levels,
+        Stat::FlawlessLevels,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Stat::Score => "score",
+            Stat::Combo => "combo",
+            Stat::BallSpeed => "ball_speed",
+            Stat::Bricks => "bricks",
+            Stat::Levels => "levels",
+            Stat::FlawlessLevels => "flawless_levels",
+        }
+    }
+
+    fn value(&self, stats: &RunStats) -> f64 {
+        match self {
+            Stat::Score => stats.score as f64,
+            Stat::Combo => stats.longest_combo as f64,
+            Stat::BallSpeed => stats.max_ball_speed as f64,
+            Stat::Bricks => stats.bricks_destroyed as f64,
+            Stat::Levels => stats.levels_cleared as f64,
+            Stat::FlawlessLevels => stats.flawless_clears as f64,
+        }
+    }
+}
+
+struct Achievement {
+    id: String,
+    name: String,
+    description: String,
+    stat: Stat,
+    goal: f64,
+}
+
+fn parse(text: &str) -> Result<Vec<Achievement>, String> {
+    let table = Table::parse(text);
+    table
+        .sections()
+        .iter()
+        .map(|id| {
+            let get = |key: &str| {
+                table
+                    .get_str(&format!("{}.{}", id, key))
+                    .ok_or(format!("{} has no {}", id, key))
+            };
+            let stat = get("stat")?;
+            Ok(Achievement {
+                id: id.clone(),
+                name: get("name")?.to_string(),
+                description: get("description")?.to_string(),
+                stat: Stat::ALL
+                    .into_iter()
+                    .find(|s| s.key() == stat)
+                    .ok_or(format!("{} counts an unknown stat '{}'", id, stat))?,
+                goal: table
+                    .get_float(&format!("{}.goal", id))
+                    .ok_or(format!("{} has no goal", id))?,
+            })
+        })
+        .collect()
+}
+
+// The achievements and which of them have been unlocked, kept in
+// `achievements.toml`. Unlocking one shows a toast in the corner for a few
+// seconds, whatever scene is up.
+pub struct Achievements {
+    path: PathBuf,
+    all: Vec<Achievement>,
+    unlocked: Vec<bool>,
+    // Indices into `all` and how long each toast has been showing.
+    toasts: Vec<(usize, f32)>,
+}
+
+impl Achievements {
+    pub fn load() -> Self {
+        let all = parse(DEFINITIONS).expect("built-in achievements are valid");
+        let path = paths::data_dir().join(UNLOCKED_FILE);
+        let table = fs::read_to_string(&path)
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let unlocked = all
+            .iter()
+            .map(|a| {
+                table
+                    .get_bool(&format!("unlocked.{}", a.id))
+                    .unwrap_or(false)
+            })
+            .collect();
+        Self {
+            path,
+            all,
+            unlocked,
+            toasts: Vec::new(),
+        }
+    }
+
+    // Unlocks whatever the run has earned so far.
+    pub fn check(&mut self, stats: &RunStats) {
+        let mut changed = false;
+        for (i, achievement) in self.all.iter().enumerate() {
+            if !self.unlocked[i] && achievement.stat.value(stats) >= achievement.goal {
+                self.unlocked[i] = true;
+                self.toasts.push((i, 0.0));
+                changed = true;
+            }
+        }
+        if changed {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let mut writer = Writer::default();
+        writer.section("unlocked");
+        for (achievement, unlocked) in self.all.iter().zip(&self.unlocked) {
+            if *unlocked {
+                writer.value(&achievement.id, Value::Bool(true));
+            }
+        }
+        if let Err(e) = fs::write(&self.path, writer.finish()) {
+            log::error!("could not write {}: {}", self.path.display(), e);
+        }
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        for (_, age) in &mut self.toasts {
+            *age += duration.as_secs_f32();
+        }
+        self.toasts.retain(|(_, age)| *age < TOAST_TIME);
+    }
+
+    // Toasts stack upwards from the bottom right corner.
+    pub fn render(&self, d: &mut impl RaylibDraw, font: &ffi::Font) {
+        for (slot, (i, age)) in self.toasts.iter().enumerate() {
+            let achievement = &self.all[*i];
+            let alpha = ((TOAST_TIME - age) / TOAST_FADE).clamp(0.0, 1.0);
+            let rect = Rectangle {
+                x: WINDOW_WIDTH - TOAST_WIDTH - 16.0,
+                y: WINDOW_HEIGHT - (TOAST_HEIGHT + 12.0) * (slot + 1) as f32 - 4.0,
+                width: TOAST_WIDTH,
+                height: TOAST_HEIGHT,
+            };
+            d.draw_rectangle_rec(rect, Color::BLACK.alpha(0.8 * alpha));
+            d.draw_rectangle_lines_ex(rect, 2.0, Color::GOLD.alpha(alpha));
+            let (x, y) = (rect.x as i32 + 14, rect.y as i32 + 8);
+            Text::new(
+                &tr!("achievements.unlocked", achievement.name),
+                20,
+                Color::GOLD.alpha(alpha),
+            )
+            .draw(d, font, x, y);
+            Text::new(&achievement.description, 18, Color::LIGHTGRAY.alpha(alpha)).draw(
+                d,
+                font,
+                x,
+                y + 28,
+            );
+        }
+    }
+}
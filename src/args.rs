@@ -0,0 +1,111 @@
+use crate::mutators::{Mutators, Seed};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const USAGE: &str = "usage: pong [options]
+
+  --level <n>      start straight into level n
+  --seed <seed>    seed the random effects so runs look the same; a seed
+                   string like 1234-BT also turns on the mutators it lists
+  --fullscreen     start in fullscreen
+  --fps <n>        run at n frames per second instead of 60
+  --mute           play no sound
+  --config <path>  read and save settings here instead of pong.toml
+  --replay <file>  play back a ghost file, a recorded time attack clear
+  --debug          enable the F4 debug overlay and the console
+  --verbose        log debug messages as well as warnings; RUST_LOG picks
+                   levels per part of the game, e.g. RUST_LOG=pong::net=trace
+  --import <path>  convert an LBreakout2 levelset into playable levels and exit
+  --host <port>    wait for another player to race on this UDP port
+  --join <address> race the player hosting at address, e.g. 192.168.1.5:7777
+  --overlay-server <port>
+                   serve the game state to stream overlays on ws://localhost:port
+  --help           show this message";
+
+// Command line flags. They override the config file for this run only.
+#[derive(Default)]
+pub struct Args {
+    pub level: Option<usize>,
+    pub seed: Option<u64>,
+    pub mutators: Mutators,
+    pub fullscreen: bool,
+    pub fps: Option<f32>,
+    pub mute: bool,
+    pub config: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub debug: bool,
+    pub verbose: bool,
+    pub import: Option<PathBuf>,
+    pub host: Option<u16>,
+    pub join: Option<String>,
+    pub overlay_server: Option<u16>,
+}
+
+impl Args {
+    // Prints the usage and exits on `--help` or a bad flag.
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(args)) => args,
+            Ok(None) => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // `None` means help was asked for.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut ret = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--level" => {
+                    let level: usize = value(&mut args, &arg)?;
+                    if level == 0 {
+                        return Err("levels start at 1".into());
+                    }
+                    ret.level = Some(level - 1);
+                }
+                "--seed" => {
+                    let seed: Seed = value(&mut args, &arg)?;
+                    ret.seed = Some(seed.value);
+                    ret.mutators = seed.mutators;
+                }
+                "--fullscreen" => ret.fullscreen = true,
+                "--fps" => {
+                    let fps: f32 = value(&mut args, &arg)?;
+                    if !fps.is_finite() || fps <= 0.0 {
+                        return Err("--fps must be above 0".into());
+                    }
+                    ret.fps = Some(fps);
+                }
+                "--mute" => ret.mute = true,
+                "--config" => ret.config = Some(value(&mut args, &arg)?),
+                "--replay" => ret.replay = Some(value(&mut args, &arg)?),
+                "--debug" => ret.debug = true,
+                "--verbose" | "-v" => ret.verbose = true,
+                "--import" => ret.import = Some(value(&mut args, &arg)?),
+                "--host" => ret.host = Some(value(&mut args, &arg)?),
+                "--join" => ret.join = Some(value(&mut args, &arg)?),
+                "--overlay-server" => ret.overlay_server = Some(value(&mut args, &arg)?),
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+        if ret.host.is_some() && ret.join.is_some() {
+            return Err("--host and --join can't be used together".into());
+        }
+        Ok(Some(ret))
+    }
+}
+
+fn value<T: FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T, String> {
+    let raw = args
+        .next()
+        .ok_or_else(|| format!("{} needs a value", flag))?;
+    raw.parse()
+        .map_err(|_| format!("invalid value '{}' for {}", raw, flag))
+}
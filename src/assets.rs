@@ -0,0 +1,184 @@
+use crate::error::Error;
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::path::Path;
+
+pub const ASSETS_DIR: &str = "assets";
+const FONTS_DIR: &str = "fonts";
+// Fonts are rasterised once at this size and scaled, so it should match the
+// biggest text drawn.
+const FONT_SIZE: i32 = 100;
+
+// Optional art loaded from `assets/` at startup. Anything missing is left as
+// `None` and drawn with the built-in shapes instead.
+pub struct Assets {
+    pub paddle: Option<Texture2D>,
+    pub ball: Option<Texture2D>,
+    pub brick: Option<Texture2D>,
+    pub background: Option<Texture2D>,
+    // `assets/font.ttf` replaces raylib's built-in font everywhere; themes can
+    // pick one of `assets/fonts/` by file name instead.
+    pub font: Option<Font>,
+    pub fonts: HashMap<String, Font>,
+    default_font: WeakFont,
+}
+
+impl Assets {
+    // Nothing but the built-in font, for the tests, which have no window to
+    // load textures into.
+    #[cfg(test)]
+    pub fn none() -> Self {
+        Self {
+            paddle: None,
+            ball: None,
+            brick: None,
+            background: None,
+            font: None,
+            fonts: HashMap::new(),
+            default_font: unsafe { WeakFont::from_raw(ffi::GetFontDefault()) },
+        }
+    }
+
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        Self {
+            paddle: optional(load_texture(rl, thread, "paddle.png")),
+            ball: optional(load_texture(rl, thread, "ball.png")),
+            brick: optional(load_texture(rl, thread, "brick.png")),
+            background: optional(load_texture(rl, thread, "background.png")),
+            font: optional(load_font(
+                rl,
+                thread,
+                &Path::new(ASSETS_DIR).join("font.ttf"),
+            )),
+            fonts: load_fonts(rl, thread),
+            default_font: rl.get_font_default(),
+        }
+    }
+
+    // Loads `path` again if it is one of the files above. A file that was
+    // deleted or can't be loaded falls back to the built-in look.
+    pub fn reload(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path) {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return;
+        };
+        let dir = path.parent();
+        if dir == Some(Path::new(ASSETS_DIR)) {
+            match name {
+                "paddle.png" => self.paddle = optional(load_texture(rl, thread, name)),
+                "ball.png" => self.ball = optional(load_texture(rl, thread, name)),
+                "brick.png" => self.brick = optional(load_texture(rl, thread, name)),
+                "background.png" => self.background = optional(load_texture(rl, thread, name)),
+                "font.ttf" => self.font = optional(load_font(rl, thread, path)),
+                _ => (),
+            }
+        } else if dir == Some(&Path::new(ASSETS_DIR).join(FONTS_DIR)) {
+            match optional(load_font(rl, thread, path)) {
+                Some(font) => self.fonts.insert(name.to_string(), font),
+                None => self.fonts.remove(name),
+            };
+        }
+    }
+
+    // The theme's font if it was found, otherwise the general one.
+    pub fn font(&self, name: Option<&str>) -> &ffi::Font {
+        name.and_then(|n| self.fonts.get(n))
+            .or(self.font.as_ref())
+            .map(|f| &**f)
+            .unwrap_or(&self.default_font)
+    }
+}
+
+// The art is all optional, so a file that can't be loaded is only reported
+// and the game carries on without it.
+fn optional<T>(result: Result<Option<T>, Error>) -> Option<T> {
+    result.unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        None
+    })
+}
+
+// Fonts get the Latin-1 letters besides plain ASCII, so the accents of the
+// translations show up.
+fn load_font(
+    _rl: &mut RaylibHandle,
+    _thread: &RaylibThread,
+    path: &Path,
+) -> Result<Option<Font>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let error = |message: &str| Error::Asset {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    };
+    let name = CString::new(path.to_string_lossy().as_bytes())
+        .map_err(|_| error("the path has a NUL character in it"))?;
+    let mut codepoints: Vec<i32> = (32..=255).collect();
+    let font = unsafe {
+        ffi::LoadFontEx(
+            name.as_ptr(),
+            FONT_SIZE,
+            codepoints.as_mut_ptr(),
+            codepoints.len() as i32,
+        )
+    };
+    if font.glyphs.is_null() || font.texture.id == 0 {
+        return Err(error("not a font raylib can read"));
+    }
+    unsafe { ffi::SetTextureFilter(font.texture, TextureFilter::TEXTURE_FILTER_BILINEAR as i32) };
+    log::info!("loaded {}", path.display());
+    Ok(Some(unsafe { Font::from_raw(font) }))
+}
+
+fn load_fonts(rl: &mut RaylibHandle, thread: &RaylibThread) -> HashMap<String, Font> {
+    let Ok(entries) = fs::read_dir(Path::new(ASSETS_DIR).join(FONTS_DIR)) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "ttf" || ext == "otf")
+        })
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some((name, optional(load_font(rl, thread, &path))?))
+        })
+        .collect()
+}
+
+fn load_texture(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    name: &str,
+) -> Result<Option<Texture2D>, Error> {
+    let path = Path::new(ASSETS_DIR).join(name);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = path.to_string_lossy().into_owned();
+    let texture = rl
+        .load_texture(thread, &file)
+        .map_err(|message| Error::Asset { path, message })?;
+    log::info!("loaded {}", file);
+    Ok(Some(texture))
+}
+
+// Stretches the whole texture over `rect`.
+pub fn draw_stretched(
+    d: &mut impl RaylibDraw,
+    texture: &Texture2D,
+    rect: impl Into<ffi::Rectangle>,
+    tint: Color,
+) {
+    d.draw_texture_pro(
+        texture,
+        Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32),
+        rect,
+        Vector2::zero(),
+        0.0,
+        tint,
+    );
+}
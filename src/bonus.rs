@@ -0,0 +1,129 @@
+use crate::physics::{self, OrientedRect};
+use crate::rng::Rng;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+
+// A bonus stage follows every BONUS_EVERY levels cleared and lasts
+// BONUS_TIME seconds.
+pub const BONUS_EVERY: usize = 3;
+pub const BONUS_TIME: f32 = 30.0;
+// Seconds between gems, on average.
+const SPAWN_INTERVAL: f32 = 0.5;
+const GEM_RADIUS: f32 = 14.0;
+// Gems fall somewhere between these speeds, in pixels a second, and faster
+// as the stage goes on.
+const MIN_FALL_SPEED: f32 = 160.0;
+const MAX_FALL_SPEED: f32 = 300.0;
+const SPEEDUP: f32 = 0.5;
+// Degrees a second the gems turn as they fall.
+const SPIN: f32 = 90.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Emerald,
+    Sapphire,
+    Ruby,
+}
+
+impl Kind {
+    fn points(&self) -> u32 {
+        match self {
+            Kind::Emerald => 50,
+            Kind::Sapphire => 100,
+            Kind::Ruby => 250,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Kind::Emerald => Color::new(60, 220, 120, 255),
+            Kind::Sapphire => Color::new(70, 130, 255, 255),
+            Kind::Ruby => Color::new(255, 60, 90, 255),
+        }
+    }
+
+    // Rubies are rare and sapphires uncommon.
+    fn pick(roll: f32) -> Self {
+        if roll < 0.1 {
+            Kind::Ruby
+        } else if roll < 0.35 {
+            Kind::Sapphire
+        } else {
+            Kind::Emerald
+        }
+    }
+}
+
+struct Gem {
+    kind: Kind,
+    position: Vector2,
+    speed: f32,
+    angle: f32,
+}
+
+// Gems falling from the top of the screen, to be caught with the paddle
+// between levels. There is no ball, so nothing is lost by missing them.
+pub struct BonusStage {
+    gems: Vec<Gem>,
+    rng: Rng,
+    next_gem: f32,
+    elapsed: f32,
+    pub caught: u32,
+}
+
+impl BonusStage {
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            gems: Vec::new(),
+            rng,
+            next_gem: 0.0,
+            elapsed: 0.0,
+            caught: 0,
+        }
+    }
+
+    // Drops new gems and moves the falling ones. Returns where each gem the
+    // paddle caught was and what it is worth.
+    pub fn update(&mut self, seconds: f32, paddle: &OrientedRect) -> Vec<(Vector2, u32)> {
+        self.elapsed += seconds;
+        self.next_gem -= seconds;
+        while self.next_gem <= 0.0 {
+            self.next_gem += SPAWN_INTERVAL * (0.5 + self.rng.next_f32());
+            let x = GEM_RADIUS + self.rng.next_f32() * (WINDOW_WIDTH - GEM_RADIUS * 2.0);
+            let speed = MIN_FALL_SPEED + self.rng.next_f32() * (MAX_FALL_SPEED - MIN_FALL_SPEED);
+            self.gems.push(Gem {
+                kind: Kind::pick(self.rng.next_f32()),
+                position: Vector2::new(x, -GEM_RADIUS),
+                speed: speed * (1.0 + SPEEDUP * self.elapsed / BONUS_TIME),
+                angle: self.rng.next_f32() * 90.0,
+            });
+        }
+
+        let mut caught = Vec::new();
+        self.gems.retain_mut(|gem| {
+            gem.position.y += gem.speed * seconds;
+            gem.angle += SPIN * seconds;
+            if physics::circle_contact(gem.position, GEM_RADIUS, paddle).is_some() {
+                caught.push((gem.position, gem.kind.points()));
+                return false;
+            }
+            gem.position.y <= WINDOW_HEIGHT + GEM_RADIUS
+        });
+        self.caught += caught.len() as u32;
+        caught
+    }
+
+    pub fn render(&self, d: &mut impl RaylibDraw) {
+        for gem in &self.gems {
+            let color = gem.kind.color();
+            d.draw_poly(gem.position, 4, GEM_RADIUS, gem.angle, color);
+            d.draw_poly_lines(
+                gem.position,
+                4,
+                GEM_RADIUS,
+                gem.angle,
+                color.brightness(0.5),
+            );
+        }
+    }
+}
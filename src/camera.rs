@@ -0,0 +1,83 @@
+use crate::rng::Rng;
+use crate::shake::Shake;
+use crate::tween::Tween;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::time::Duration;
+
+// The dramatic zoom: how long it lasts in real time, how far in it goes, and
+// the share of it spent zooming in and back out.
+const ZOOM_TIME: f32 = 1.5;
+const MAX_ZOOM: f32 = 1.8;
+const ZOOM_IN: f32 = 0.15;
+const ZOOM_OUT: f32 = 0.35;
+// Game speed at the height of the zoom.
+const SLOW_MOTION: f32 = 0.2;
+
+// The camera the playfield is drawn through: screen shake plus a short zoom
+// towards a point, which also slows the game down while it lasts.
+pub struct WorldCamera {
+    pub shake: Shake,
+    focus: Vector2,
+    zoom: Option<Tween>,
+}
+
+impl WorldCamera {
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            shake: Shake::new(rng),
+            focus: Vector2::zero(),
+            zoom: None,
+        }
+    }
+
+    pub fn zoom_to(&mut self, focus: Vector2) {
+        self.focus = focus;
+        self.zoom = Some(Tween::new(ZOOM_TIME));
+    }
+
+    // The zoom runs on real time so that it is not slowed down by itself.
+    pub fn update(&mut self, real: &Duration, scaled: &Duration, shake_intensity: f32) {
+        self.shake.update(scaled, shake_intensity);
+        if let Some(zoom) = &mut self.zoom {
+            zoom.update(real);
+            if zoom.finished() {
+                self.zoom = None;
+            }
+        }
+    }
+
+    // 0.0 when not zooming, easing up to 1.0 and back down again.
+    fn amount(&self) -> f32 {
+        let Some(zoom) = &self.zoom else {
+            return 0.0;
+        };
+        let t = zoom.linear();
+        let ramp = if t < ZOOM_IN {
+            t / ZOOM_IN
+        } else if t > 1.0 - ZOOM_OUT {
+            (1.0 - t) / ZOOM_OUT
+        } else {
+            1.0
+        };
+        ramp * ramp * (3.0 - 2.0 * ramp)
+    }
+
+    // How fast the game should run, 1.0 being normal speed.
+    pub fn time_scale(&self) -> f32 {
+        1.0 - (1.0 - SLOW_MOTION) * self.amount()
+    }
+
+    // The focus point moves towards the middle of the screen as it is zoomed
+    // into, so it never ends up off screen.
+    pub fn camera(&self) -> Camera2D {
+        let amount = self.amount();
+        let centre = Vector2::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0);
+        Camera2D {
+            offset: self.focus + (centre - self.focus) * amount + self.shake.offset(),
+            target: self.focus,
+            rotation: 0.0,
+            zoom: 1.0 + (MAX_ZOOM - 1.0) * amount,
+        }
+    }
+}
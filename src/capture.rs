@@ -0,0 +1,172 @@
+use crate::gif;
+use crate::lang::tr;
+use crate::paths;
+use raylib::prelude::*;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const REPLAY_DIR: &str = "replays";
+const SCREENSHOT_DIR: &str = "screenshots";
+const CAPTURE_FPS: u32 = 20;
+const BUFFER_SECONDS: u32 = 5;
+// Each captured frame keeps every SCALE-th pixel of the logical frame.
+const SCALE: usize = 4;
+
+// A fixed 6 x 7 x 6 colour cube, padded with black to 256 entries. Green gets
+// the extra level since the eye is most sensitive to it.
+const LEVELS: [usize; 3] = [6, 7, 6];
+
+fn palette() -> [[u8; 3]; 256] {
+    let mut palette = [[0; 3]; 256];
+    let step = |level: usize, levels: usize| (level * 255 / (levels - 1)) as u8;
+    for r in 0..LEVELS[0] {
+        for g in 0..LEVELS[1] {
+            for b in 0..LEVELS[2] {
+                palette[quantize_index(r, g, b)] =
+                    [step(r, LEVELS[0]), step(g, LEVELS[1]), step(b, LEVELS[2])];
+            }
+        }
+    }
+    palette
+}
+
+fn quantize_index(r: usize, g: usize, b: usize) -> usize {
+    (r * LEVELS[1] + g) * LEVELS[2] + b
+}
+
+fn quantize(color: &Color) -> u8 {
+    let level = |c: u8, levels: usize| (c as usize * (levels - 1) + 127) / 255;
+    quantize_index(
+        level(color.r, LEVELS[0]),
+        level(color.g, LEVELS[1]),
+        level(color.b, LEVELS[2]),
+    ) as u8
+}
+
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_secs())
+}
+
+// Saves the frame at the logical resolution as a PNG in the screenshots
+// directory and returns where it went.
+pub fn screenshot(frame: &RenderTexture2D) -> Result<PathBuf, String> {
+    let mut image = frame.texture().load_image()?;
+    image.flip_vertical();
+    let path = paths::data_subdir(SCREENSHOT_DIR).join(format!("screenshot-{}.png", timestamp()));
+    export_png(&image, &path)?;
+    Ok(path)
+}
+
+// Writes `image` to `path`. The wrapper's `export_image` doesn't say whether
+// it worked.
+pub fn export_png(image: &Image, path: &Path) -> Result<(), String> {
+    let name = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+    if unsafe { ffi::ExportImage(**image, name.as_ptr()) } {
+        Ok(())
+    } else {
+        Err(format!("could not write {}", path.display()))
+    }
+}
+
+// Keeps the last few seconds of gameplay at a low resolution so they can be
+// saved as an animated GIF at any time.
+pub struct Recorder {
+    frames: VecDeque<Vec<u8>>,
+    since_capture: Duration,
+    size: (usize, usize),
+    saving: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            since_capture: Duration::ZERO,
+            size: (0, 0),
+            saving: None,
+        }
+    }
+
+    // Grabs the frame that was just drawn when the next capture is due.
+    // Returns a message for the player once a save has finished.
+    pub fn update(&mut self, duration: &Duration, frame: &RenderTexture2D) -> Option<String> {
+        let message =
+            self.saving
+                .as_ref()
+                .and_then(|r| r.try_recv().ok())
+                .map(|result| match result {
+                    Ok(path) => tr!("notice.replay_saved", path.display()),
+                    Err(e) => {
+                        log::error!("could not save the replay: {}", e);
+                        tr!("notice.replay_failed").to_string()
+                    }
+                });
+        if message.is_some() {
+            self.saving = None;
+        }
+
+        self.since_capture += *duration;
+        let interval = Duration::from_secs(1) / CAPTURE_FPS;
+        if self.since_capture < interval {
+            return message;
+        }
+        self.since_capture = Duration::ZERO;
+        let Ok(image) = frame.texture().load_image() else {
+            return message;
+        };
+        let (width, height) = (image.width as usize, image.height as usize);
+        let pixels = image.get_image_data();
+        self.size = (width / SCALE, height / SCALE);
+        // Render textures are stored bottom row first.
+        let indices = (0..self.size.1)
+            .flat_map(|y| (0..self.size.0).map(move |x| (x, y)))
+            .map(|(x, y)| quantize(&pixels[(height - 1 - y * SCALE) * width + x * SCALE]))
+            .collect();
+        if self.frames.len() == (CAPTURE_FPS * BUFFER_SECONDS) as usize {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(indices);
+        message
+    }
+
+    // Encodes and writes the buffer on a background thread so the game
+    // doesn't stall; `update` reports the result once it's done. Returns
+    // whether a save was started.
+    pub fn save(&mut self) -> bool {
+        if self.saving.is_some() || self.frames.is_empty() {
+            return false;
+        }
+        let frames: Vec<Vec<u8>> = self.frames.iter().cloned().collect();
+        let (width, height) = (self.size.0 as u16, self.size.1 as u16);
+        let path = paths::data_subdir(REPLAY_DIR).join(format!("replay-{}.gif", timestamp()));
+        let (sender, receiver) = mpsc::channel();
+        self.saving = Some(receiver);
+        let encode = move || {
+            let data = gif::encode(
+                width,
+                height,
+                &palette(),
+                &frames,
+                (100 / CAPTURE_FPS) as u16,
+            );
+            let result = fs::write(&path, data)
+                .map(|_| path)
+                .map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        };
+        // The browser build has no threads.
+        if cfg!(target_os = "emscripten") {
+            encode();
+        } else {
+            thread::spawn(encode);
+        }
+        true
+    }
+}
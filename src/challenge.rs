@@ -0,0 +1,133 @@
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::mode::GameMode;
+use crate::WINDOW_WIDTH;
+use raylib::ffi;
+
+// Modifiers that make a run harder without changing its rules: the playfield
+// drawn mirrored left to right, and left and right swapped on the controls.
+// Together they cancel out on screen, but still count as their own category.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Challenge {
+    pub mirror: bool,
+    pub inverted: bool,
+}
+
+impl Challenge {
+    pub const ALL: [Challenge; 4] = [
+        Challenge {
+            mirror: false,
+            inverted: false,
+        },
+        Challenge {
+            mirror: true,
+            inverted: false,
+        },
+        Challenge {
+            mirror: false,
+            inverted: true,
+        },
+        Challenge {
+            mirror: true,
+            inverted: true,
+        },
+    ];
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match (self.mirror, self.inverted) {
+            (false, false) => tr!("challenge.none"),
+            (true, false) => tr!("challenge.mirrored"),
+            (false, true) => tr!("challenge.inverted"),
+            (true, true) => tr!("challenge.both"),
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match (self.mirror, self.inverted) {
+            (false, false) => "",
+            (true, false) => "mirror",
+            (false, true) => "inverted",
+            (true, true) => "mirror_inverted",
+        }
+    }
+
+    // The input as the game should see it.
+    pub fn map(&self, input: &Input) -> Input {
+        let mut input = input.clone();
+        if self.inverted {
+            input.swap(Action::Left, Action::Right);
+            input.swap(Action::TiltLeft, Action::TiltRight);
+            input.swap(Action::HandLeft, Action::HandRight);
+            input.mouse.x = WINDOW_WIDTH - input.mouse.x;
+        }
+        input
+    }
+}
+
+// Flips whatever is drawn while it is alive left to right across the
+// playfield.
+pub struct Mirror;
+
+impl Mirror {
+    pub fn begin() -> Self {
+        // Flipping turns the triangles' winding around, so culling has to be
+        // off for them to show. It is GL state, so what was queued under the
+        // old state is drawn first.
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableBackfaceCulling();
+            ffi::rlPushMatrix();
+            ffi::rlTranslatef(WINDOW_WIDTH, 0.0, 0.0);
+            ffi::rlScalef(-1.0, 1.0, 1.0);
+        }
+        Self
+    }
+}
+
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlPopMatrix();
+            ffi::rlEnableBackfaceCulling();
+        }
+    }
+}
+
+// What high scores and the leaderboard are kept per: a mode with a challenge.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Category {
+    pub mode: GameMode,
+    pub challenge: Challenge,
+}
+
+impl Category {
+    pub fn all() -> impl Iterator<Item = Category> {
+        GameMode::RANKED.into_iter().flat_map(|mode| {
+            Challenge::ALL
+                .into_iter()
+                .map(move |challenge| Category { mode, challenge })
+        })
+    }
+
+    // Plain modes keep the keys they had, so old scores still count.
+    pub fn key(&self) -> String {
+        if self.challenge.is_none() {
+            self.mode.key().to_string()
+        } else {
+            format!("{}_{}", self.mode.key(), self.challenge.key())
+        }
+    }
+
+    pub fn name(&self) -> String {
+        if self.challenge.is_none() {
+            self.mode.name().to_string()
+        } else {
+            format!("{} \u{2014} {}", self.mode.name(), self.challenge.name())
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::error::Error;
+use crate::hud::HudLayout;
+use crate::leaderboard::LeaderboardSettings;
+use crate::paths;
+use crate::settings::Settings;
+use crate::tuning::Tuning;
+use crate::twitch::TwitchSettings;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "pong.toml";
+
+// Values of the small TOML subset the config file uses: flat `[section]`
+// tables holding booleans, numbers and strings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl Value {
+    fn parse(raw: &str) -> Option<Value> {
+        let raw = raw.trim();
+        if let Some(s) = raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+            return Some(Value::Str(s.replace("\\\"", "\"").replace("\\\\", "\\")));
+        }
+        match raw {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => raw
+                .parse()
+                .map(Value::Int)
+                .or_else(|_| raw.parse().map(Value::Float))
+                .ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Str(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        }
+    }
+}
+
+// Keys are stored as `section.key`.
+#[derive(Default)]
+pub struct Table {
+    values: HashMap<String, Value>,
+    // Section names in the order they first appear.
+    sections: Vec<String>,
+}
+
+impl Table {
+    pub fn parse(text: &str) -> Self {
+        let mut ret = Self::default();
+        let mut section = String::new();
+        for line in text.lines() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                if !ret.sections.contains(&section) {
+                    ret.sections.push(section.clone());
+                }
+            } else if let Some((key, value)) = line.split_once('=') {
+                if let Some(value) = Value::parse(value) {
+                    ret.values
+                        .insert(format!("{}.{}", section, key.trim()), value);
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn sections(&self) -> &[String] {
+        &self.sections
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.values.get(key) {
+            Some(Value::Bool(b)) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        match self.values.get(key) {
+            Some(Value::Int(i)) => Some(*i),
+            _ => None,
+        }
+    }
+
+    // Integers are accepted too, so `speed = 40` reads the same as `speed = 40.0`.
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        match self.values.get(key) {
+            Some(Value::Float(x)) => Some(*x),
+            Some(Value::Int(i)) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.values.get(key) {
+            Some(Value::Str(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+// Builds a config file section by section, one `key = value` per line.
+#[derive(Default)]
+pub struct Writer {
+    out: String,
+}
+
+impl Writer {
+    pub fn section(&mut self, name: &str) -> &mut Self {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out.push_str(&format!("[{}]\n", name));
+        self
+    }
+
+    pub fn comment(&mut self, text: &str) -> &mut Self {
+        self.out.push_str(&format!("# {}\n", text));
+        self
+    }
+
+    pub fn value(&mut self, key: &str, value: Value) -> &mut Self {
+        self.out.push_str(&format!("{} = {}\n", key, value));
+        self
+    }
+
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.out)
+    }
+}
+
+pub struct Config {
+    path: PathBuf,
+    pub settings: Settings,
+    pub hud: HudLayout,
+    pub tuning: Tuning,
+    pub leaderboard: LeaderboardSettings,
+    pub twitch: TwitchSettings,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Error> {
+        Self::load_from(&paths::file(paths::config_dir(), CONFIG_FILE))
+    }
+
+    // A missing file is a first run, but one that can't be read is an error
+    // rather than a reason to replace it with the defaults.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        let mut first_run = false;
+        let table = match fs::read_to_string(path) {
+            Ok(text) => Table::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                first_run = true;
+                Table::default()
+            }
+            Err(error) => {
+                return Err(Error::Config {
+                    path: path.to_path_buf(),
+                    error,
+                })
+            }
+        };
+        let ret = Self::from_table(path, &table);
+        // Writing the defaults out documents every option in one place.
+        if first_run {
+            ret.save();
+        }
+        Ok(ret)
+    }
+
+    // The config in `text`, as read from `path`.
+    pub fn parse(path: &Path, text: &str) -> Self {
+        Self::from_table(path, &Table::parse(text))
+    }
+
+    fn from_table(path: &Path, table: &Table) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            settings: Settings::from_table(table),
+            hud: HudLayout::from_table(table),
+            tuning: Tuning::from_table(table),
+            leaderboard: LeaderboardSettings::from_table(table),
+            twitch: TwitchSettings::from_table(table),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // What `save` writes.
+    pub fn text(&self) -> String {
+        let mut writer = Writer::default();
+        self.settings.write(&mut writer);
+        self.hud.write(&mut writer);
+        self.tuning.write(&mut writer);
+        self.leaderboard.write(&mut writer);
+        self.twitch.write(&mut writer);
+        writer.finish()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = fs::write(&self.path, self.text()) {
+            log::error!("could not write {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// The commands mods and the debug console run. Built without the
+// `devtools` feature the console itself never opens.
+use crate::scene::Context;
+use std::str::FromStr;
+
+pub type CommandFn<T> = fn(&mut T, &Context, &[&str]) -> Result<String, String>;
+type Run<T> = Box<dyn Fn(&mut T, &Context, &[&str]) -> Result<String, String>>;
+
+struct Command<T> {
+    name: &'static str,
+    usage: &'static str,
+    run: Run<T>,
+}
+
+// Console commands that act on a `T`, usually the scene that owns the console.
+// Commands are added with `register`, and a subsystem's own list, acting on
+// its part of the `T`, with `include`; `help` is always available.
+pub struct Commands<T> {
+    list: Vec<Command<T>>,
+}
+
+impl<T: 'static> Commands<T> {
+    pub fn new() -> Self {
+        Self { list: Vec::new() }
+    }
+
+    pub fn register(mut self, name: &'static str, usage: &'static str, run: CommandFn<T>) -> Self {
+        self.list.push(Command {
+            name,
+            usage,
+            run: Box::new(run),
+        });
+        self
+    }
+
+    // Adds every command in `commands`, run on the part of the `T` that `part`
+    // picks out.
+    pub fn include<U: 'static>(
+        mut self,
+        part: fn(&mut T) -> &mut U,
+        commands: Commands<U>,
+    ) -> Self {
+        for command in commands.list {
+            let run = command.run;
+            self.list.push(Command {
+                name: command.name,
+                usage: command.usage,
+                run: Box::new(move |target, ctx, args| run(part(target), ctx, args)),
+            });
+        }
+        self
+    }
+
+    pub fn execute(&self, target: &mut T, ctx: &Context, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            return Ok(String::new());
+        };
+        if *name == "help" {
+            let usages: Vec<&str> = self.list.iter().map(|c| c.usage).collect();
+            return Ok(usages.join("\n"));
+        }
+        match self.list.iter().find(|c| c.name == *name) {
+            Some(command) => (command.run)(target, ctx, args),
+            None => Err(format!("unknown command '{}', try help", name)),
+        }
+    }
+}
+
+// Parses the argument at `index`, naming it in the error.
+pub fn arg<N: FromStr>(args: &[&str], index: usize, name: &str) -> Result<N, String> {
+    let value = args.get(index).ok_or(format!("missing {}", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("bad {} '{}'", name, value))
+}
+
+#[cfg(feature = "devtools")]
+mod imp {
+    use crate::input::Input;
+    use crate::screen::Canvas;
+    use crate::WINDOW_WIDTH;
+    use raylib::prelude::*;
+
+    const HEIGHT: i32 = 300;
+    const FONT_SIZE: i32 = 20;
+    const LINE_HEIGHT: i32 = FONT_SIZE + 4;
+    const MAX_LOG: usize = 64;
+
+    // A drop-down text prompt with a scrollback of earlier commands and results.
+    pub struct Console {
+        pub open: bool,
+        line: String,
+        log: Vec<(String, Color)>,
+    }
+
+    impl Console {
+        pub fn new() -> Self {
+            Self {
+                open: false,
+                line: String::new(),
+                log: Vec::new(),
+            }
+        }
+
+        // Applies this frame's typing and returns the line once ENTER is pressed.
+        pub fn edit(&mut self, input: &Input) -> Option<String> {
+            self.line.push_str(&input.typed);
+            if input.erase {
+                self.line.pop();
+            }
+            if !input.submit {
+                return None;
+            }
+            let line = std::mem::take(&mut self.line);
+            self.print(&format!("> {}", line), Color::GRAY);
+            Some(line)
+        }
+
+        pub fn report(&mut self, result: Result<String, String>) {
+            match result {
+                Ok(text) => self.print(&text, Color::WHITE),
+                Err(text) => self.print(&text, Color::RED),
+            }
+        }
+
+        fn print(&mut self, text: &str, color: Color) {
+            for line in text.lines() {
+                self.log.push((line.to_string(), color));
+            }
+            let excess = self.log.len().saturating_sub(MAX_LOG);
+            self.log.drain(..excess);
+        }
+
+        pub fn render(&self, d: &mut Canvas) {
+            if !self.open {
+                return;
+            }
+            d.draw_rectangle(0, 0, WINDOW_WIDTH as i32, HEIGHT, Color::BLACK.alpha(0.85));
+            d.draw_line(0, HEIGHT, WINDOW_WIDTH as i32, HEIGHT, Color::GRAY);
+
+            let prompt_y = HEIGHT - LINE_HEIGHT - 6;
+            d.draw_text(
+                &format!("> {}_", self.line),
+                10,
+                prompt_y,
+                FONT_SIZE,
+                Color::YELLOW,
+            );
+            let mut y = prompt_y;
+            for (text, color) in self.log.iter().rev() {
+                y -= LINE_HEIGHT;
+                if y < 0 {
+                    break;
+                }
+                d.draw_text(text, 10, y, FONT_SIZE, *color);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "devtools"))]
+mod imp {
+    use crate::input::Input;
+    use crate::screen::Canvas;
+
+    pub struct Console {
+        pub open: bool,
+    }
+
+    impl Console {
+        pub fn new() -> Self {
+            Self { open: false }
+        }
+
+        pub fn edit(&mut self, _input: &Input) -> Option<String> {
+            None
+        }
+
+        pub fn report(&mut self, _result: Result<String, String>) {}
+
+        pub fn render(&self, _d: &mut Canvas) {}
+    }
+}
+
+pub use imp::Console;
@@ -0,0 +1,180 @@
+use crate::assets::ASSETS_DIR;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::music::Track;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::draw_centered;
+use crate::ui;
+use crate::WINDOW_HEIGHT;
+use raylib::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// Pixels a second the credits roll up by, and how much faster holding down
+// makes them.
+const SCROLL_SPEED: f32 = 40.0;
+const FAST_FORWARD: f32 = 6.0;
+const LINE_GAP: f32 = 12.0;
+const HEADING_SIZE: i32 = 36;
+const LINE_SIZE: i32 = 24;
+// Anyone putting their own art in the assets folder can credit it here, one
+// line per line on screen.
+const ASSET_CREDITS: &str = "credits.txt";
+
+// The optional parts of the game this build was compiled with.
+const FEATURES: [(&str, bool); 8] = [
+    ("audio", cfg!(feature = "audio")),
+    ("net", cfg!(feature = "net")),
+    ("mods", cfg!(feature = "mods")),
+    ("devtools", cfg!(feature = "devtools")),
+    ("profiling", cfg!(feature = "profiling")),
+    ("leaderboard", cfg!(feature = "leaderboard")),
+    ("discord", cfg!(feature = "discord")),
+    ("plugins", cfg!(feature = "plugins")),
+];
+
+struct Line {
+    text: String,
+    size: i32,
+    color: Color,
+}
+
+impl Line {
+    fn heading(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            size: HEADING_SIZE,
+            color: Color::YELLOW,
+        }
+    }
+
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            size: LINE_SIZE,
+            color: Color::WHITE,
+        }
+    }
+
+    fn gap() -> Self {
+        Self::text("")
+    }
+}
+
+// Who made the game and what it is made with, rolling slowly up the screen.
+// Down speeds it up, up winds it back, and Confirm, Back or a click skips
+// back to the menu.
+pub struct Credits {
+    lines: Vec<Line>,
+    // How far the credits have rolled, in pixels.
+    offset: f32,
+}
+
+impl Credits {
+    pub fn new() -> Self {
+        let mut lines = vec![
+            Line::heading(tr!("credits.game")),
+            Line::text(format!("Pong {}", env!("CARGO_PKG_VERSION"))),
+            Line::text(tr!(
+                "credits.by",
+                env!("CARGO_PKG_AUTHORS").replace(':', ", ")
+            )),
+            Line::text(env!("CARGO_PKG_REPOSITORY")),
+            Line::gap(),
+            Line::heading(tr!("credits.built_with")),
+            Line::text("raylib 5.0 - Ramon Santamaria and contributors"),
+            Line::text(tr!("credits.license", "zlib/libpng")),
+            Line::text("raylib-rs - the raylib-rs contributors"),
+            Line::text(tr!("credits.license", "zlib")),
+            Line::gap(),
+            Line::heading(tr!("credits.content")),
+            Line::text(tr!("credits.builtin")),
+        ];
+        let assets = asset_credits();
+        if !assets.is_empty() {
+            lines.push(Line::gap());
+            lines.push(Line::heading(tr!("credits.assets")));
+            lines.extend(assets.into_iter().map(Line::text));
+        }
+        let features: Vec<_> = FEATURES
+            .iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| *name)
+            .collect();
+        lines.extend([
+            Line::gap(),
+            Line::heading(tr!("credits.build")),
+            Line::text(tr!(
+                "credits.target",
+                std::env::consts::ARCH,
+                std::env::consts::OS
+            )),
+            Line::text(if cfg!(debug_assertions) {
+                tr!("credits.debug")
+            } else {
+                tr!("credits.release")
+            }),
+            Line::text(tr!(
+                "credits.features",
+                if features.is_empty() {
+                    tr!("credits.no_features").to_string()
+                } else {
+                    features.join(", ")
+                }
+            )),
+            Line::gap(),
+            Line::text(tr!("credits.thanks")),
+        ]);
+        Self { lines, offset: 0.0 }
+    }
+
+    fn height(&self) -> f32 {
+        self.lines.iter().map(|l| l.size as f32 + LINE_GAP).sum()
+    }
+}
+
+// The lines of `assets/credits.txt`, if there is one.
+fn asset_credits() -> Vec<String> {
+    fs::read_to_string(Path::new(ASSETS_DIR).join(ASSET_CREDITS))
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+impl Scene for Credits {
+    fn update(&mut self, _ctx: &mut Context, input: &Input, duration: &Duration) -> Transition {
+        if input.pressed(Action::Confirm) || input.pressed(Action::Back) || input.mouse_clicked {
+            return Transition::Pop;
+        }
+        let speed = if input.down(Action::Down) {
+            SCROLL_SPEED * FAST_FORWARD
+        } else if input.down(Action::Up) {
+            -SCROLL_SPEED * FAST_FORWARD
+        } else {
+            SCROLL_SPEED
+        };
+        // Once the last line has rolled off the top they start over.
+        let end = self.height() + WINDOW_HEIGHT;
+        self.offset = (self.offset + speed * duration.as_secs_f32()).rem_euclid(end);
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        let mut y = WINDOW_HEIGHT - self.offset;
+        for line in &self.lines {
+            let height = line.size as f32 + LINE_GAP;
+            if y > -height && y < WINDOW_HEIGHT {
+                draw_centered(d, font, &line.text, y as i32, line.size, line.color);
+            }
+            y += height;
+        }
+        ui::hint(d, font, tr!("credits.hint"), WINDOW_HEIGHT as i32 - 30);
+    }
+
+    fn music(&self, _ctx: &Context) -> Option<Track> {
+        Some(Track::Menu)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::lang::tr;
+use raylib::prelude::*;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+const TITLE_SIZE: i32 = 30;
+const TEXT_SIZE: i32 = 20;
+const MARGIN: i32 = 40;
+
+// What can go wrong while the game starts up or loads its files.
+#[derive(Debug)]
+pub enum Error {
+    // No window with an OpenGL context could be opened.
+    Window,
+    // One of the offscreen textures every frame is drawn through could not
+    // be created; the string says which.
+    RenderTexture(&'static str),
+    // The config file is there but could not be read. Starting with the
+    // defaults would write over it on the first change.
+    Config { path: PathBuf, error: io::Error },
+    // A file in `assets/` is there but could not be loaded.
+    Asset { path: PathBuf, message: String },
+    // `--host` or `--join` could not open the connection for a versus race.
+    Versus(io::Error),
+    // The file given to `--replay` is not a ghost that can be played.
+    Replay { path: PathBuf, message: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Window => write!(
+                f,
+                "could not open a window. The game needs a display and graphics drivers with OpenGL 3.3."
+            ),
+            Error::RenderTexture(what) => write!(
+                f,
+                "could not create the {} texture. The graphics card may be out of memory or too old for the game.",
+                what
+            ),
+            Error::Config { path, error } => write!(
+                f,
+                "could not read {}: {}. Fix the file or move it out of the way to start with the defaults.",
+                path.display(),
+                error
+            ),
+            Error::Asset { path, message } => {
+                write!(f, "could not load {}: {}", path.display(), message)
+            }
+            Error::Versus(error) => write!(f, "could not start a versus game: {}", error),
+            Error::Replay { path, message } => {
+                write!(f, "could not play back {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Config { error, .. } | Error::Versus(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Tells the player why the game cannot go on and quits. The message goes to
+// the terminal and, on the desktop, to a plain screen in the window that
+// stays up until a key or button is pressed or the window is closed.
+pub fn fatal(rl: &mut RaylibHandle, thread: &RaylibThread, error: &Error) -> ! {
+    log::error!("{}", error);
+    if !cfg!(target_os = "emscripten") {
+        show(rl, thread, error);
+    }
+    std::process::exit(1);
+}
+
+fn show(rl: &mut RaylibHandle, thread: &RaylibThread, error: &Error) {
+    let message = capitalize(&error.to_string());
+    while !rl.window_should_close()
+        && rl.get_key_pressed().is_none()
+        && !rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+    {
+        let width = rl.get_screen_width();
+        let lines = wrap(rl, &message, width - MARGIN * 2);
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::BLACK);
+        d.draw_text(tr!("error.title"), MARGIN, MARGIN, TITLE_SIZE, Color::RED);
+        let mut y = MARGIN + TITLE_SIZE * 2;
+        for line in &lines {
+            d.draw_text(line, MARGIN, y, TEXT_SIZE, Color::WHITE);
+            y += TEXT_SIZE + TEXT_SIZE / 2;
+        }
+        d.draw_text(
+            tr!("error.hint"),
+            MARGIN,
+            y + TEXT_SIZE,
+            TEXT_SIZE,
+            Color::GRAY,
+        );
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+// Breaks `text` into lines no wider than `width` pixels with raylib's
+// built-in font, which is all there is before the assets are loaded.
+fn wrap(rl: &RaylibHandle, text: &str, width: i32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if rl.measure_text(&candidate, TEXT_SIZE) > width && !line.is_empty() {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
@@ -0,0 +1,68 @@
+use crate::powerups::PowerUp;
+use crate::twitch::Mutator;
+use pong::plugin::BrickType;
+use raylib::prelude::Vector2;
+
+// Things that happened during a physics step, or in Twitch chat. The game
+// only reports them; effects such as particles react to them afterwards.
+#[derive(Clone, Copy, Debug)]
+pub enum GameEvent {
+    WallHit {
+        position: Vector2,
+    },
+    PaddleHit {
+        position: Vector2,
+        speed: f32,
+    },
+    BrickHit {
+        position: Vector2,
+        hp: usize,
+        kind: Option<BrickType>,
+    },
+    BrickDestroyed {
+        position: Vector2,
+        hp: usize,
+        kind: Option<BrickType>,
+        points: u32,
+    },
+    LifeLost,
+    BumperHit {
+        position: Vector2,
+        points: u32,
+    },
+    Teleported {
+        from: Vector2,
+        to: Vector2,
+    },
+    PowerUpCaught {
+        position: Vector2,
+        kind: PowerUp,
+    },
+    ChatVote {
+        mutator: Mutator,
+    },
+    GemCaught {
+        position: Vector2,
+        points: u32,
+    },
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn emit(&mut self, event: GameEvent) {
+        log::debug!("{:?}", event);
+        self.queue.push(event);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, GameEvent> {
+        self.queue.iter()
+    }
+
+    pub fn drain(&mut self) -> std::vec::Drain<'_, GameEvent> {
+        self.queue.drain(..)
+    }
+}
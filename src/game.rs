@@ -0,0 +1,3051 @@
+use crate::assets::{draw_stretched, Assets};
+use crate::bonus::{BonusStage, BONUS_EVERY, BONUS_TIME};
+use crate::camera::WorldCamera;
+use crate::capture;
+use crate::challenge::{Category, Challenge, Mirror};
+use crate::config::{Table, Value, Writer};
+use crate::console::{arg, Commands, Console};
+use crate::events::{EventBus, GameEvent};
+use crate::ghost::{Ghost, GhostFrame, GhostRecorder};
+use crate::highscores::{HighScore, NameEntry};
+use crate::hints::{Control, Hints};
+use crate::hud::Hud;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::leaderboard::{Submission, Trace};
+use crate::level::{self, Background, BrickSpec, BumperSpec, Level, Wind, MAX_BRICK_HP};
+use crate::menu::{PauseMenu, QuitPrompt};
+use crate::mode::GameMode;
+use crate::music::Track;
+use crate::mutators::Mutators;
+use crate::net::{Phase, Status};
+use crate::overlay::OverlayState;
+use crate::particles::{Burst, Particles};
+use crate::paths;
+use crate::physics;
+use crate::pool::Pool;
+use crate::popups::Popups;
+use crate::powerups::PowerUps;
+use crate::practice::{self, Practice, Spawn};
+use crate::presence::Activity;
+use crate::profile;
+use crate::rng::Rng;
+use crate::run_code::RunCode;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::script::{Hook, Script};
+use crate::settings::{Difficulty, InputMode};
+use crate::sonify::BallTone;
+use crate::sounds::{Cue, Sfx};
+use crate::splits::Splits;
+use crate::starfield::Starfield;
+use crate::stats::RunStats;
+use crate::summary::RunSummary;
+use crate::text::{draw_banner, draw_centered};
+use crate::theme::Theme;
+use crate::trail::Trail;
+use crate::tuning::Tuning;
+use crate::tween::Tween;
+use crate::twitch::Mutator;
+use crate::window::{group_thousands, BASE_TITLE};
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use ffi::Rectangle;
+use pong::plugin::BrickType;
+use raylib::color::Color;
+use raylib::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub const PROJ_RADIUS: f32 = 16.0;
+const RACKET_WIDTH: f32 = 8.0 * PROJ_RADIUS;
+const RACKET_HEIGHT: f32 = 16.0;
+const RACKET_POS_Y: f32 = WINDOW_HEIGHT - RACKET_HEIGHT * 5.0;
+const BRICK_POINTS: u32 = 100;
+const LEVEL_CLEARED_PAUSE: Duration = Duration::from_secs(2);
+const BRICK_DYING_TIME: f32 = 0.35;
+const MAX_DYING_BRICKS: usize = 64;
+// A damaged brick jiggles for BRICK_HIT_TIME, flashing white for the first
+// BRICK_FLASH_TIME of it.
+const BRICK_HIT_TIME: f32 = 0.2;
+const BRICK_FLASH_TIME: f32 = 0.08;
+const BRICK_JIGGLE: f32 = 4.0;
+// Bricks that take more than one hit show a row of pips, one per hit point
+// they started with, and crack a little more with every hit.
+const HP_PIP_RADIUS: f32 = 2.5;
+const HP_PIP_SPACING: f32 = 8.0;
+const CRACK_STEPS: usize = 3;
+// Magnet bricks pull the ball from up to MAGNET_REACH pixels away, with a
+// force of up to MAGNET_STRENGTH pixels per second squared. Their field
+// pulses outwards every MAGNET_PULSE_TIME seconds.
+const MAGNET_REACH: f32 = 160.0;
+const MAGNET_STRENGTH: f32 = 700.0;
+const MAGNET_PULSE_TIME: f32 = 1.2;
+const MAGNET_COLOR: Color = Color::new(230, 80, 255, 255);
+// Each pair of portals has its own colour, and their swirl turns at
+// PORTAL_SPIN degrees per second.
+const PORTAL_COLORS: [Color; 4] = [
+    Color::new(255, 150, 40, 255),
+    Color::new(60, 170, 255, 255),
+    Color::new(90, 230, 120, 255),
+    Color::new(255, 90, 120, 255),
+];
+const PORTAL_SPIN: f32 = 240.0;
+// Wind is shown as faint streaks blowing through its area, one for every
+// WIND_STREAK_AREA square pixels, moving at WIND_STREAK_SPEED seconds' worth
+// of the wind's force per second.
+const WIND_STREAK_AREA: f32 = 3000.0;
+const WIND_STREAK_SPEED: f32 = 0.6;
+const WIND_STREAK_LENGTH: f32 = 24.0;
+// A bumper kicks the ball away BUMPER_KICK pixels per second faster than it
+// came, an extra that wears off by BOOST_DECAY per second, and scores
+// BUMPER_POINTS. It flashes for BUMPER_FLASH_TIME seconds when hit.
+const BUMPER_KICK: f32 = 350.0;
+const BOOST_DECAY: f32 = 2.5;
+const BUMPER_POINTS: u32 = 10;
+const BUMPER_FLASH_TIME: f32 = 0.2;
+const BUMPER_COLOR: Color = Color::new(80, 160, 255, 255);
+const WALL_THICKNESS: f32 = 4.0;
+const WEAR_FACTOR: f32 = 0.9;
+const MIN_WEAR: f32 = 0.4;
+const OUTLINE: f32 = 3.0;
+// Q and E tilt the racket up to MAX_TILT radians either way, turning at
+// TILT_SPEED radians a second, and it levels out again when let go. However
+// it is tilted, the ball leaves it at most MAX_BOUNCE_ANGLE off straight up.
+const MAX_TILT: f32 = 0.26;
+const TILT_SPEED: f32 = 1.5;
+const MAX_BOUNCE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+// With the free paddle mutator the racket can also rise this far above its
+// usual height, moving up and down at this fraction of its sideways speed.
+const PADDLE_BAND: f32 = 120.0;
+const VERTICAL_SPEED: f32 = 0.6;
+// Seconds counted down before the ball is served, and how long "GO!" stays up.
+const COUNTDOWN: f32 = 3.0;
+const GO_TIME: f32 = 0.6;
+// How long a bonus stage is announced for when it starts.
+const BONUS_BANNER_TIME: f32 = 2.0;
+// A new level's bricks drop in from above the screen; the ball fades in on each serve.
+const BRICKS_SLIDE_TIME: f32 = 0.8;
+const BALL_FADE_TIME: f32 = 0.4;
+// How strongly the time attack ghost shows through.
+const GHOST_ALPHA: f32 = 0.3;
+// The layout of the files `snapshot` writes.
+const SNAPSHOT_VERSION: i64 = 1;
+// Below the lives in the top left corner.
+const SPLITS_POSITION: (i32, i32) = (16, 60);
+// Seconds of travel the debug velocity arrow represents.
+const DEBUG_VELOCITY_SCALE: f32 = 0.25;
+const DEBUG_NORMAL_LENGTH: f32 = 60.0;
+// Rows the console and mods may spawn bricks into, which keeps them above
+// the racket.
+const SPAWN_ROWS: usize = 12;
+// Mods can't hand out lives beyond this.
+const MAX_SCRIPT_LIVES: usize = 9;
+
+// Score popups grow with the combo, up to COMBO_POPUP_MAX hits.
+const POPUP_SIZE: i32 = 22;
+const COMBO_POPUP_MAX: u32 = 10;
+
+// The HUD speed gauge is full at this multiple of the base ball speed.
+const SPEED_GAUGE_MAX: f32 = 2.0;
+
+// Bounces sound higher as the ball speeds up and the combo grows: PITCH_PER_SPEED
+// for every multiple of the serve speed, PITCH_PER_COMBO per brick in a row.
+const PITCH_PER_SPEED: f32 = 0.5;
+const PITCH_PER_COMBO: f32 = 0.03;
+const MAX_PITCH: f32 = 2.0;
+
+// Racket hits faster than this shake the screen a little.
+const FAST_HIT_SPEED: f32 = 800.0;
+// How much darker a heavy ball is drawn, as a raylib brightness factor.
+const HEAVY_BALL_SHADE: f32 = -0.55;
+
+// What the mutators chat votes for do, and how long the shrunk paddle lasts.
+const MUTATOR_SPEED_UP: f32 = 1.25;
+const MUTATOR_SHRINK: f32 = 0.6;
+const MUTATOR_TIME: f32 = 15.0;
+
+const SHARDS: Burst = Burst {
+    count: 24,
+    speed: 350.0,
+    lifetime: 0.9,
+    size: 7.0,
+    color: Color::WHITE,
+};
+const CHIPS: Burst = Burst {
+    count: 6,
+    speed: 200.0,
+    lifetime: 0.4,
+    size: 5.0,
+    color: Color::WHITE,
+};
+const SPARKS: Burst = Burst {
+    count: 10,
+    speed: 300.0,
+    lifetime: 0.3,
+    size: 3.0,
+    color: Color::WHITE,
+};
+
+struct Brick {
+    x: f32,
+    y: f32,
+    live: usize,
+    // The hit points it started with.
+    hp: usize,
+    // Seconds left of the hit animation.
+    hit: f32,
+    kind: Option<BrickType>,
+    magnet: bool,
+    // What the level says breaking it scores, instead of the usual points for
+    // its type.
+    worth: Option<u32>,
+}
+
+impl Brick {
+    // In the grid with its first row at `top`.
+    fn at(tuning: &Tuning, size: (f32, f32), top: f32, cell: (usize, usize), hp: usize) -> Self {
+        let (width, height) = size;
+        let (column, row) = cell;
+        let gap = tuning.brick_gap;
+        Self {
+            x: gap + (column as f32) * (width + gap),
+            y: top + (row as f32) * (height + gap),
+            live: hp,
+            hp,
+            hit: 0.0,
+            kind: None,
+            magnet: false,
+            worth: None,
+        }
+    }
+
+    fn centre(&self, (width, height): (f32, f32)) -> Vector2 {
+        Vector2::new(self.x + width / 2.0, self.y + height / 2.0)
+    }
+
+    fn points(&self) -> u32 {
+        self.worth
+            .unwrap_or_else(|| self.kind.map_or(BRICK_POINTS, |k| k.points))
+    }
+}
+
+struct Bumper {
+    centre: Vector2,
+    radius: f32,
+    // Seconds left of the flash after a hit.
+    flash: f32,
+}
+
+// A destroyed brick playing its crumble animation; `hp` is the colour it had
+// before the final hit.
+#[derive(Default)]
+struct DyingBrick {
+    x: f32,
+    y: f32,
+    hp: usize,
+    kind: Option<BrickType>,
+    age: f32,
+}
+
+struct Projectile {
+    x: f32,
+    y: f32,
+    speed: f32,
+    direction: Vector2,
+    already_in_collision: bool,
+    // Set from the mutators and power-ups; everything that touches the ball
+    // goes by this.
+    radius: f32,
+    // Hit points taken off each brick it strikes, and how much of the bounce
+    // off a brick it breaks is kept, from 0 (straight through) to 1. Set from
+    // the power-ups.
+    damage: usize,
+    deflection: f32,
+    // Extra speed from bumpers, on top of `speed`, that wears off.
+    boost: f32,
+    // The pair and end of the portal the ball last came out of. That end
+    // can't take the ball back until the ball has left it, or the two ends
+    // would pass it back and forth for ever.
+    portal: Option<(usize, usize)>,
+}
+
+impl Projectile {
+    fn new(tuning: &Tuning, difficulty: Difficulty, mutators: &Mutators, radius: f32) -> Self {
+        Self {
+            x: WINDOW_WIDTH / 2.0,
+            y: RACKET_POS_Y - radius - 1.0,
+            radius,
+            speed: mutators.ball_speed(tuning.ball_speed * difficulty.ball_speed_factor()),
+            direction: Vector2 { x: 1.0, y: -1.0 },
+            already_in_collision: false,
+            damage: 1,
+            deflection: 1.0,
+            boost: 0.0,
+            portal: None,
+        }
+    }
+}
+struct Racket {
+    x: f32,
+    // The top edge; RACKET_POS_Y unless the free paddle mutator lifted it.
+    y: f32,
+    direction: f32,
+    direction_y: f32,
+    target: Option<f32>,
+    target_y: Option<f32>,
+    width: f32,
+    // Seconds until a chat mutator's width change wears off.
+    resized: f32,
+    // Clockwise, in radians; `tilting` is which way it is being turned.
+    tilt: f32,
+    tilting: f32,
+}
+
+impl Racket {
+    fn new(width: f32) -> Self {
+        Self {
+            x: WINDOW_WIDTH / 2.0 - width / 2.0,
+            y: RACKET_POS_Y,
+            direction: 0.0,
+            direction_y: 0.0,
+            target: None,
+            target_y: None,
+            width,
+            resized: 0.0,
+            tilt: 0.0,
+            tilting: 0.0,
+        }
+    }
+
+    fn centre(&self) -> Vector2 {
+        Vector2::new(self.x + self.width / 2.0, self.y + RACKET_HEIGHT / 2.0)
+    }
+
+    fn shape(&self) -> physics::OrientedRect {
+        physics::OrientedRect {
+            centre: self.centre(),
+            half_size: Vector2::new(self.width / 2.0, RACKET_HEIGHT / 2.0),
+            angle: self.tilt,
+        }
+    }
+
+    // The top paddle of dual paddle mode, this one mirrored across the
+    // middle of the screen.
+    fn mirrored_shape(&self) -> physics::OrientedRect {
+        let shape = self.shape();
+        physics::OrientedRect {
+            centre: Vector2::new(shape.centre.x, WINDOW_HEIGHT - shape.centre.y),
+            angle: -shape.angle,
+            ..shape
+        }
+    }
+
+    // Keeps the racket centred where it was.
+    fn resize(&mut self, width: f32) {
+        self.x += (self.width - width) / 2.0;
+        self.width = width;
+        self.resized = MUTATOR_TIME;
+    }
+}
+// Turns whatever is drawn while it is alive by `angle` radians about
+// `centre`. The matrix applies as vertices are queued, so textures and
+// gradients turn along without flushing the batch.
+struct Turned;
+
+impl Turned {
+    fn about(centre: Vector2, angle: f32) -> Self {
+        unsafe {
+            ffi::rlPushMatrix();
+            ffi::rlTranslatef(centre.x, centre.y, 0.0);
+            ffi::rlRotatef(angle.to_degrees(), 0.0, 0.0, 1.0);
+            ffi::rlTranslatef(-centre.x, -centre.y, 0.0);
+        }
+        Self
+    }
+}
+
+impl Drop for Turned {
+    fn drop(&mut self) {
+        unsafe { ffi::rlPopMatrix() };
+    }
+}
+
+// How the arena is turned about the middle of the screen, for the rotating
+// arena mutator. Everything a level places stays where the level put it, in
+// the arena's frame, while the ball, the paddle and the edges of the screen
+// are in the screen's; these carry points and directions between the two.
+#[derive(Clone, Copy)]
+struct Arena {
+    angle: f32,
+}
+
+impl Arena {
+    const CENTRE: Vector2 = Vector2 {
+        x: WINDOW_WIDTH / 2.0,
+        y: WINDOW_HEIGHT / 2.0,
+    };
+
+    fn to_screen(self, point: Vector2) -> Vector2 {
+        Self::CENTRE + (point - Self::CENTRE).rotated(self.angle)
+    }
+
+    fn to_arena(self, point: Vector2) -> Vector2 {
+        Self::CENTRE + (point - Self::CENTRE).rotated(-self.angle)
+    }
+
+    fn turn(self, direction: Vector2) -> Vector2 {
+        direction.rotated(self.angle)
+    }
+
+    fn unturn(self, direction: Vector2) -> Vector2 {
+        direction.rotated(-self.angle)
+    }
+}
+
+pub struct Game {
+    mode: GameMode,
+    challenge: Challenge,
+    mutators: Mutators,
+    // Of the random effects; part of the run code shown at the end.
+    seed: u64,
+    difficulty: Difficulty,
+    tuning: Tuning,
+    // Of the current level's grid.
+    columns: usize,
+    // Racing another player over the network to clear one level first.
+    versus: bool,
+    // Set when the other player cleared it first.
+    beaten: bool,
+    brick_size: (f32, f32),
+    // Where the first row of bricks is.
+    bricks_top: f32,
+    level: usize,
+    // The revision of the level that was loaded, which a debug build bumps
+    // when the level's file changes.
+    level_revision: u32,
+    bricks: Vec<Brick>,
+    dying_bricks: Pool<DyingBrick>,
+    ball: Projectile,
+    racket: Racket,
+    lives: usize,
+    level_score: u32,
+    // Whether the paddle is worn down by lost balls in this run, and by how
+    // many so far on this level.
+    paddle_wear: bool,
+    wear: u32,
+    state: State,
+    level_elapsed: Duration,
+    // Real time since the game started; Zen shows it instead of the lives.
+    session: Duration,
+    stats: RunStats,
+    finished: bool,
+    rank: Option<usize>,
+    // How the run was played, for the leaderboard's hash.
+    trace: Trace,
+    // A leaderboard entry waiting for the name the high scores ask for.
+    submission: Option<Submission>,
+    events: EventBus,
+    particles: Particles,
+    popups: Popups,
+    camera: WorldCamera,
+    trail: Trail,
+    starfield: Starfield,
+    // Where the ball last bounced and the surface normal there.
+    last_contact: Option<(Vector2, Vector2)>,
+    console: Console,
+    // This run's copies of the mods, so their variables start over.
+    scripts: Vec<Script>,
+    // Set by `load_level` so the mods' level start hook runs on the next update.
+    level_started: bool,
+    // Time attack races the best clear of the level, and records this one.
+    ghost: Option<Ghost>,
+    ghost_recorder: Option<GhostRecorder>,
+    splits: Splits,
+    practice: Option<Practice>,
+    powerups: PowerUps,
+    // The centres of the two ends of each pair of portals.
+    portals: Vec<[Vector2; 2]>,
+    winds: Vec<Wind>,
+    bumpers: Vec<Bumper>,
+    // Walls inside the arena, besides its edges, and the areas that lose
+    // the ball.
+    walls: Vec<physics::Segment>,
+    dead_zones: Vec<raylib::prelude::Rectangle>,
+    go_time: f32,
+    bricks_slide: Tween,
+    ball_fade: Tween,
+    hints: Option<Hints>,
+    // The gems of the bonus stage being played, if one is.
+    bonus: Option<BonusStage>,
+    // The slowest game speed this run has been played at, in percent.
+    slowest: u8,
+    // How much bigger the ball and paddle are for readability, from the
+    // settings and on top of everything else that sizes them.
+    oversize: f32,
+}
+
+impl Game {
+    // Logs the state the game goes into, but not the countdowns ticking
+    // within one.
+    fn set_state(&mut self, state: State) {
+        if std::mem::discriminant(&state) != std::mem::discriminant(&self.state) {
+            log::debug!("{:?} -> {:?}", self.state, state);
+        }
+        self.state = state;
+    }
+
+    pub fn new(ctx: &Context, level: usize) -> Self {
+        let difficulty = ctx.config.settings.difficulty;
+        let tuning = ctx.config.tuning;
+        let seed = ctx.seed.unwrap_or_else(Rng::pick_seed);
+        let oversize = ctx.config.settings.visual.oversize();
+        let mut ret = Self {
+            mode: ctx.mode,
+            challenge: ctx.challenge,
+            mutators: ctx.mutators,
+            seed,
+            difficulty,
+            tuning,
+            columns: 0,
+            versus: false,
+            beaten: false,
+            brick_size: (0.0, 0.0),
+            bricks_top: 0.0,
+            level,
+            level_revision: 0,
+            ball: Projectile::new(
+                &tuning,
+                difficulty,
+                &ctx.mutators,
+                ctx.mutators.ball_radius(PROJ_RADIUS) * oversize,
+            ),
+            bricks: Vec::new(),
+            dying_bricks: Pool::new(MAX_DYING_BRICKS),
+            level_elapsed: Duration::ZERO,
+            session: Duration::ZERO,
+            lives: ctx.mode.spare_lives(tuning.lives(difficulty)),
+            level_score: 0,
+            paddle_wear: ctx.config.settings.paddle_wear && difficulty.allows_paddle_wear(),
+            wear: 0,
+            racket: Racket::new(ctx.mutators.paddle_width(RACKET_WIDTH) * oversize),
+            state: State::Countdown(COUNTDOWN),
+            stats: RunStats::default(),
+            finished: false,
+            rank: None,
+            trace: Trace::new(),
+            submission: None,
+            events: EventBus::default(),
+            particles: Particles::new(Rng::new(seed)),
+            popups: Popups::new(),
+            camera: WorldCamera::new(Rng::new(seed)),
+            trail: Trail::new(),
+            starfield: Starfield::new(0, 0.0),
+            last_contact: None,
+            console: Console::new(),
+            scripts: ctx.scripts.clone(),
+            level_started: false,
+            ghost: None,
+            ghost_recorder: None,
+            splits: Splits::new(
+                Category {
+                    mode: ctx.mode,
+                    challenge: ctx.challenge,
+                },
+                level,
+                ctx.levels.len(),
+            ),
+            practice: (ctx.mode == GameMode::Practice).then(Practice::new),
+            powerups: PowerUps::new(Rng::new(seed)),
+            portals: Vec::new(),
+            winds: Vec::new(),
+            bumpers: Vec::new(),
+            walls: Vec::new(),
+            dead_zones: Vec::new(),
+            go_time: 0.0,
+            bricks_slide: Tween::new(BRICKS_SLIDE_TIME),
+            ball_fade: Tween::new(BALL_FADE_TIME),
+            hints: None,
+            bonus: None,
+            slowest: ctx.config.settings.game_speed,
+            oversize,
+        };
+        ret.load_level(&ctx.levels[level]);
+        ret
+    }
+
+    pub fn versus(ctx: &Context, level: usize, seed: u64) -> Self {
+        let mut game = Self::new(ctx, level);
+        game.versus = true;
+        // Both players race on the same rules, and the host's seed.
+        game.reseed(seed);
+        game.scripts.clear();
+        game.challenge = Challenge::default();
+        game.mutators = Mutators::default();
+        game.paddle_wear = false;
+        game.ghost = None;
+        game.ghost_recorder = None;
+        game.load_level(&ctx.levels[level]);
+        game
+    }
+
+    // Everything random follows from the seed.
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.particles = Particles::new(Rng::new(seed));
+        self.camera = WorldCamera::new(Rng::new(seed));
+        self.powerups = PowerUps::new(Rng::new(seed));
+    }
+
+    fn load_level(&mut self, level: &Level) {
+        self.level_revision = level.revision;
+        self.columns = level.columns;
+        self.brick_size = self.tuning.brick_size(level.columns, level.rows());
+        self.bricks_top = if self.mode.dual_paddle() {
+            let rows = level.rows() as f32;
+            let height = rows * (self.brick_size.1 + self.tuning.brick_gap) - self.tuning.brick_gap;
+            (WINDOW_HEIGHT - height) / 2.0
+        } else {
+            self.tuning.bricks_top
+        };
+        self.bricks = level
+            .bricks
+            .iter()
+            .map(|b| Brick {
+                kind: b.kind,
+                magnet: b.magnet,
+                worth: b.points,
+                ..Brick::at(
+                    &self.tuning,
+                    self.brick_size,
+                    self.bricks_top,
+                    (b.column, b.row),
+                    b.hp,
+                )
+            })
+            .collect();
+        self.portals = level
+            .portals
+            .iter()
+            .map(|pair| {
+                pair.map(|(column, row)| {
+                    let cell = (column, row);
+                    Brick::at(&self.tuning, self.brick_size, self.bricks_top, cell, 1)
+                        .centre(self.brick_size)
+                })
+            })
+            .collect();
+        self.winds = level.winds.clone();
+        self.walls = arena_walls(level);
+        self.dead_zones = level.dead_zones.clone();
+        self.bumpers = level
+            .bumpers
+            .iter()
+            .map(|b| Bumper {
+                centre: b.centre,
+                radius: b.radius,
+                flash: 0.0,
+            })
+            .collect();
+        self.dying_bricks.clear();
+        self.popups.clear();
+        self.powerups.clear();
+        self.starfield = Starfield::new(level.background.stars, level.background.star_speed);
+        self.ball = self.new_ball();
+        self.wear = 0;
+        self.racket = Racket::new(self.racket_width());
+        self.trail.clear();
+        self.last_contact = None;
+        self.level_score = 0;
+        self.level_elapsed = Duration::ZERO;
+        self.set_state(ST::Countdown(COUNTDOWN));
+        self.bricks_slide = Tween::new(BRICKS_SLIDE_TIME);
+        self.ball_fade = Tween::new(BALL_FADE_TIME);
+        self.level_started = true;
+        // Mutated runs would race a ghost that played by other rules.
+        if self.mode == GameMode::TimeAttack && !self.versus && self.mutators.is_none() {
+            self.ghost = Ghost::load(self.level, &level.name);
+            self.ghost_recorder = Some(GhostRecorder::new());
+        }
+    }
+
+    fn overlay_state(&self, levels: &[Level]) -> OverlayState {
+        OverlayState {
+            mode: if self.versus {
+                "versus"
+            } else {
+                self.mode.key()
+            },
+            phase: match self.state {
+                ST::Countdown(_) => "countdown",
+                ST::Running => "playing",
+                ST::LevelCleared(_) => "cleared",
+                ST::Bonus(_) => "bonus",
+                ST::Winning => "won",
+                ST::GameOver => "game_over",
+            },
+            level: self.level,
+            level_name: levels[self.level].name.clone(),
+            score: self.stats.score,
+            lives: self.lives,
+            combo: self.stats.combo(),
+            bricks: self.bricks.len(),
+        }
+    }
+
+    // Replaces whatever brick is in that cell.
+    fn spawn_brick(
+        &mut self,
+        column: usize,
+        row: usize,
+        hp: usize,
+        kind: Option<BrickType>,
+    ) -> Result<String, String> {
+        if column >= self.columns || row >= SPAWN_ROWS {
+            return Err(format!(
+                "the grid is {} columns by {} rows",
+                self.columns, SPAWN_ROWS
+            ));
+        }
+        if !(1..=MAX_BRICK_HP).contains(&hp) {
+            return Err(format!("hp goes from 1 to {}", MAX_BRICK_HP));
+        }
+        let brick = Brick {
+            kind,
+            ..Brick::at(
+                &self.tuning,
+                self.brick_size,
+                self.bricks_top,
+                (column, row),
+                hp,
+            )
+        };
+        self.bricks.retain(|b| b.x != brick.x || b.y != brick.y);
+        self.bricks.push(brick);
+        Ok(format!("brick at {} {}", column, row))
+    }
+
+    // Runs `hook` in every mod. Besides `locals`, scripts can read the state
+    // of the run.
+    fn run_scripts(&mut self, ctx: &Context, hook: Hook, locals: &[(&str, f64)]) {
+        let mut values = vec![
+            ("score", self.stats.score as f64),
+            ("lives", self.lives as f64),
+            ("level", (self.level + 1) as f64),
+            ("combo", self.stats.combo() as f64),
+            ("bricks", self.bricks.len() as f64),
+            ("time", self.stats.time_played.as_secs_f64()),
+            ("speed", self.ball.speed as f64),
+        ];
+        values.extend_from_slice(locals);
+        let api = script_api();
+        let mut scripts = std::mem::take(&mut self.scripts);
+        for script in scripts.iter_mut() {
+            script.run(hook, &values, &mut |line| api.execute(self, ctx, line));
+        }
+        self.scripts = scripts;
+    }
+
+    fn update_scripts(&mut self, ctx: &Context, duration: &Duration) {
+        if self.scripts.is_empty() {
+            return;
+        }
+        if std::mem::take(&mut self.level_started) {
+            self.run_scripts(ctx, Hook::LevelStart, &[]);
+        }
+        let destroyed: Vec<(Vector2, usize)> = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::BrickDestroyed { position, hp, .. } => Some((*position, *hp)),
+                _ => None,
+            })
+            .collect();
+        for (position, hp) in destroyed {
+            self.run_scripts(
+                ctx,
+                Hook::BrickDestroyed,
+                &[
+                    ("hp", hp as f64),
+                    ("x", position.x as f64),
+                    ("y", position.y as f64),
+                ],
+            );
+        }
+        if let ST::Running = self.state {
+            self.run_scripts(ctx, Hook::Tick, &[("dt", duration.as_secs_f64())]);
+        }
+    }
+
+    // The bricks that are left, as a level on the current level's grid.
+    fn layout(&self, ctx: &Context) -> Level {
+        let (width, height) = self.brick_size;
+        let gap = self.tuning.brick_gap;
+        let level = &ctx.levels[self.level];
+        Level {
+            name: level.name.clone(),
+            boss: level.boss,
+            corners: level.corners,
+            columns: self.columns,
+            background: Background {
+                stars: level.background.stars,
+                star_speed: level.background.star_speed,
+            },
+            bricks: self
+                .bricks
+                .iter()
+                .map(|b| BrickSpec {
+                    column: ((b.x - gap) / (width + gap)).round() as usize,
+                    row: ((b.y - self.bricks_top) / (height + gap)).round() as usize,
+                    hp: b.live,
+                    kind: b.kind,
+                    magnet: b.magnet,
+                    points: b.worth,
+                })
+                .collect(),
+            portals: level.portals.clone(),
+            winds: level.winds.clone(),
+            bumpers: self
+                .bumpers
+                .iter()
+                .map(|b| BumperSpec {
+                    centre: b.centre,
+                    radius: b.radius,
+                })
+                .collect(),
+            walls: level.walls.clone(),
+            obstacles: level.obstacles.clone(),
+            dead_zones: level.dead_zones.clone(),
+            file: None,
+            revision: 0,
+        }
+    }
+
+    // Everything needed to carry on playing from this moment, in the config
+    // file format. Effects that are only for show (particles, popups, the
+    // camera) are left out and start fresh on restore. The file starts with
+    // the version of its layout; see `upgrade_snapshot`.
+    pub fn snapshot(&self) -> String {
+        let f = |x: f32| Value::Float(x as f64);
+        let (state, state_time) = match self.state {
+            ST::Running => ("running", 0.0),
+            ST::Countdown(left) => ("countdown", left),
+            ST::LevelCleared(left) => ("level_cleared", left.as_secs_f32()),
+            ST::Bonus(left) => ("bonus", left),
+            ST::Winning => ("winning", 0.0),
+            ST::GameOver => ("game_over", 0.0),
+        };
+        let mut writer = Writer::default();
+        writer
+            .section("snapshot")
+            .value("version", Value::Int(SNAPSHOT_VERSION));
+        writer
+            .section("game")
+            .value("mode", Value::Str(self.mode.key().into()))
+            .value("difficulty", Value::Str(self.difficulty.key().into()))
+            .value("mutators", Value::Str(self.mutators.to_string()))
+            .value("challenge", Value::Str(self.challenge.key().into()))
+            .value("seed", Value::Int(self.seed as i64))
+            .value("level", Value::Int(self.level as i64))
+            .value("lives", Value::Int(self.lives as i64))
+            .value("wear", Value::Int(self.wear as i64))
+            .value("level_score", Value::Int(self.level_score as i64))
+            .value(
+                "level_elapsed_ms",
+                Value::Int(self.level_elapsed.as_millis() as i64),
+            )
+            .value("state", Value::Str(state.into()))
+            .value("state_time", f(state_time))
+            .value("finished", Value::Bool(self.finished));
+        self.stats.write(&mut writer, "stats");
+        writer
+            .section("ball")
+            .value("x", f(self.ball.x))
+            .value("y", f(self.ball.y))
+            .value("speed", f(self.ball.speed))
+            .value("direction_x", f(self.ball.direction.x))
+            .value("direction_y", f(self.ball.direction.y))
+            .value("in_collision", Value::Bool(self.ball.already_in_collision))
+            .value("radius", f(self.ball.radius));
+        writer
+            .section("racket")
+            .value("x", f(self.racket.x))
+            .value("y", f(self.racket.y))
+            .value("direction", f(self.racket.direction))
+            .value("tilt", f(self.racket.tilt));
+        if let Some(target) = self.racket.target {
+            writer.value("target", f(target));
+        }
+        for (i, brick) in self.bricks.iter().enumerate() {
+            writer
+                .section(&format!("brick{}", i + 1))
+                .value("x", f(brick.x))
+                .value("y", f(brick.y))
+                .value("live", Value::Int(brick.live as i64))
+                .value("hp", Value::Int(brick.hp as i64))
+                .value("hit", f(brick.hit));
+            if brick.magnet {
+                writer.value("magnet", Value::Bool(true));
+            }
+            if let Some(worth) = brick.worth {
+                writer.value("worth", Value::Int(worth as i64));
+            }
+            // Plugin brick types go by their symbol, as in level files.
+            if let Some(kind) = brick.kind {
+                writer.value("kind", Value::Str(kind.symbol.to_string()));
+            }
+        }
+        for (i, bumper) in self.bumpers.iter().enumerate() {
+            writer
+                .section(&format!("bumper{}", i + 1))
+                .value("x", f(bumper.centre.x))
+                .value("y", f(bumper.centre.y))
+                .value("radius", f(bumper.radius))
+                .value("flash", f(bumper.flash));
+        }
+        self.powerups.write(&mut writer);
+        writer.finish()
+    }
+
+    pub fn restore(ctx: &Context, text: &str) -> Result<Game, String> {
+        let table = Table::parse(text);
+        upgrade_snapshot(&table)?;
+        let float = |key: &str| {
+            table
+                .get_float(key)
+                .map(|x| x as f32)
+                .ok_or_else(|| format!("snapshot has no {}", key))
+        };
+        let int = |key: &str| {
+            table
+                .get_int(key)
+                .ok_or_else(|| format!("snapshot has no {}", key))
+        };
+        // Counts and indices have to fit their type, so a hand-edited or
+        // corrupt file can't wrap around into a huge one.
+        let index = |key: &str| {
+            usize::try_from(int(key)?).map_err(|_| format!("snapshot has a bad {}", key))
+        };
+        let number =
+            |key: &str| u32::try_from(int(key)?).map_err(|_| format!("snapshot has a bad {}", key));
+        let level = index("game.level")?;
+        if level >= ctx.levels.len() {
+            return Err(format!(
+                "snapshot is on level {}, which doesn't exist",
+                level + 1
+            ));
+        }
+        let mut game = Game::new(ctx, level);
+        if let Some(mode) = table
+            .get_str("game.mode")
+            .and_then(|k| GameMode::ALL.into_iter().find(|m| m.key() == k))
+        {
+            game.mode = mode;
+        }
+        if let Some(difficulty) = table
+            .get_str("game.difficulty")
+            .and_then(|k| Difficulty::ALL.into_iter().find(|d| d.key() == k))
+        {
+            game.difficulty = difficulty;
+        }
+        if let Some(mutators) = table.get_str("game.mutators").and_then(|m| m.parse().ok()) {
+            game.mutators = mutators;
+        }
+        if let Some(challenge) = table
+            .get_str("game.challenge")
+            .and_then(|k| Challenge::ALL.into_iter().find(|c| c.key() == k))
+        {
+            game.challenge = challenge;
+        }
+        // The layout depends on the mode, e.g. the bricks of a two-paddle
+        // game sit in the middle, so it is built again for the saved one.
+        game.practice = (game.mode == GameMode::Practice).then(Practice::new);
+        game.load_level(&ctx.levels[level]);
+        game.splits = Splits::new(
+            Category {
+                mode: game.mode,
+                challenge: game.challenge,
+            },
+            level,
+            ctx.levels.len(),
+        );
+        // Everything random follows from the seed, and the drops carry on
+        // from where they were. The seed is written as the bits of an i64.
+        if let Ok(seed) = int("game.seed") {
+            game.reseed(seed as u64);
+        }
+        game.powerups = PowerUps::from_table(&table, Rng::new(game.seed))?;
+        game.lives = index("game.lives")?;
+        game.wear = if table.get_int("game.wear").is_some() {
+            number("game.wear")?
+        } else {
+            0
+        };
+        game.level_score = number("game.level_score")?;
+        game.level_elapsed = Duration::from_millis(
+            u64::try_from(int("game.level_elapsed_ms")?)
+                .map_err(|_| "snapshot has a bad game.level_elapsed_ms".to_string())?,
+        );
+        let state_time = float("game.state_time")?;
+        game.state = match table.get_str("game.state") {
+            Some("running") => ST::Running,
+            Some("countdown") => ST::Countdown(state_time),
+            Some("level_cleared") => ST::LevelCleared(Duration::from_secs_f32(state_time.max(0.0))),
+            // The gems that were falling aren't kept, so the stage starts
+            // over with the time it had left.
+            Some("bonus") => {
+                game.start_bonus();
+                ST::Bonus(state_time.min(BONUS_TIME))
+            }
+            Some("winning") => ST::Winning,
+            Some("game_over") => ST::GameOver,
+            _ => return Err("snapshot has no valid game.state".into()),
+        };
+        game.finished = table.get_bool("game.finished").unwrap_or(false);
+        game.stats = RunStats::from_table(&table, "stats");
+        game.ball = Projectile {
+            x: float("ball.x")?,
+            y: float("ball.y")?,
+            speed: float("ball.speed")?,
+            direction: Vector2::new(float("ball.direction_x")?, float("ball.direction_y")?),
+            already_in_collision: table.get_bool("ball.in_collision").unwrap_or(false),
+            radius: float("ball.radius").unwrap_or(game.ball.radius),
+            ..game.ball
+        };
+        game.racket = Racket {
+            x: float("racket.x")?,
+            y: float("racket.y").unwrap_or(RACKET_POS_Y),
+            direction: float("racket.direction")?,
+            target: float("racket.target").ok(),
+            tilt: float("racket.tilt").unwrap_or(0.0),
+            ..Racket::new(game.racket_width())
+        };
+        game.bricks = (1..)
+            .map_while(|i| {
+                let section = format!("brick{}", i);
+                let live_key = format!("{}.live", section);
+                // The bricks end at the first one without hit points.
+                table.get_int(&live_key)?;
+                let live = index(&live_key).map(|live| live.max(1));
+                let kind = table.get_str(&format!("{}.kind", section)).map(|symbol| {
+                    ctx.registry
+                        .brick_types()
+                        .iter()
+                        .find(|t| t.symbol.to_string() == symbol)
+                        .copied()
+                        .ok_or_else(|| {
+                            format!("snapshot has a '{}' brick, which no plugin adds", symbol)
+                        })
+                });
+                let x = float(&format!("{}.x", section)).ok()?;
+                let y = float(&format!("{}.y", section)).ok()?;
+                let brick = || -> Result<Brick, String> {
+                    let live = live?;
+                    let hp_key = format!("{}.hp", section);
+                    let worth_key = format!("{}.worth", section);
+                    Ok(Brick {
+                        x,
+                        y,
+                        live,
+                        hp: match table.get_int(&hp_key) {
+                            Some(_) => index(&hp_key)?.max(live),
+                            None => live,
+                        },
+                        hit: float(&format!("{}.hit", section)).unwrap_or(0.0),
+                        kind: kind.transpose()?,
+                        magnet: table
+                            .get_bool(&format!("{}.magnet", section))
+                            .unwrap_or(false),
+                        worth: match table.get_int(&worth_key) {
+                            Some(_) => Some(number(&worth_key)?),
+                            None => None,
+                        },
+                    })
+                };
+                Some(brick())
+            })
+            .collect::<Result<_, _>>()?;
+        // Snapshots from before bumpers were kept have the level's.
+        if table.get_float("bumper1.x").is_some() {
+            game.bumpers = (1..)
+                .map_while(|i| {
+                    let section = format!("bumper{}", i);
+                    Some(Bumper {
+                        centre: Vector2::new(
+                            float(&format!("{}.x", section)).ok()?,
+                            float(&format!("{}.y", section)).ok()?,
+                        ),
+                        radius: float(&format!("{}.radius", section))
+                            .unwrap_or(level::BUMPER_RADIUS),
+                        flash: float(&format!("{}.flash", section)).unwrap_or(0.0),
+                    })
+                })
+                .collect();
+        }
+        // Restored mid-play, so nothing should slide or fade in.
+        game.bricks_slide = Tween::new(0.0);
+        game.ball_fade = Tween::new(0.0);
+        Ok(game)
+    }
+
+    // Whether clearing the current level leads into a bonus stage.
+    fn bonus_due(&self) -> bool {
+        self.mode.bonus_stages() && !self.versus && (self.level + 1).is_multiple_of(BONUS_EVERY)
+    }
+
+    fn start_bonus(&mut self) {
+        self.set_state(ST::Bonus(BONUS_TIME));
+        self.bonus = Some(BonusStage::new(Rng::new(self.seed ^ self.level as u64)));
+        self.powerups.clear();
+        self.trail.clear();
+    }
+
+    // Practice goes round the same level again, and Zen starts over from the
+    // first once it runs out.
+    fn next_level(&mut self, levels: &[Level]) {
+        self.bonus = None;
+        if self.practice.is_none() {
+            self.level = (self.level + 1) % levels.len();
+        }
+        self.load_level(&levels[self.level]);
+    }
+
+    // What there is to do at a serve: move, serve, tilt and pause.
+    fn control_hints(&self, ctx: &Context) -> Vec<(Control, &'static str)> {
+        let keyboard = ctx.config.settings.input_mode == InputMode::Keyboard;
+        if ctx.config.settings.one_handed {
+            return if keyboard {
+                vec![
+                    (
+                        Control::Bound(&[Action::HandLeft, Action::HandRight]),
+                        "hints.move",
+                    ),
+                    (Control::Bound(&[Action::HandServe]), "hints.serve"),
+                    (Control::Bound(&[Action::HandPause]), "hints.pause"),
+                ]
+            } else {
+                vec![
+                    (Control::Mouse, "hints.move"),
+                    (Control::Click, "hints.serve"),
+                    (Control::RightClick, "hints.pause"),
+                ]
+            };
+        }
+        let movement = match (keyboard, self.mutators.free_paddle) {
+            (true, true) => {
+                Control::Bound(&[Action::Left, Action::Right, Action::Up, Action::Down])
+            }
+            (true, false) => Control::Bound(&[Action::Left, Action::Right]),
+            (false, _) => Control::Mouse,
+        };
+        let serve = if keyboard {
+            Control::Bound(&[Action::Confirm])
+        } else {
+            Control::Click
+        };
+        vec![
+            (movement, "hints.move"),
+            (serve, "hints.serve"),
+            (
+                Control::Bound(&[Action::TiltLeft, Action::TiltRight]),
+                "hints.tilt",
+            ),
+            (Control::Bound(&[Action::Pause]), "hints.pause"),
+        ]
+    }
+
+    fn handle_input(&mut self, ctx: &Context, input: &Input) -> Transition {
+        self.racket.direction = 0.0;
+        self.racket.direction_y = 0.0;
+        self.racket.target = None;
+        self.racket.target_y = None;
+        let free = self.mutators.paddle_band(PADDLE_BAND) > 0.0;
+        // With one hand the paddle only moves sideways, and up and down
+        // serve and pause instead.
+        let one_handed = ctx.config.settings.one_handed;
+        let serve = match ctx.config.settings.input_mode {
+            InputMode::Keyboard if one_handed => {
+                let left = input.down(Action::Left) || input.down(Action::HandLeft);
+                let right = input.down(Action::Right) || input.down(Action::HandRight);
+                self.racket.direction = match (left, right) {
+                    (true, false) => -1.0,
+                    (false, true) => 1.0,
+                    _ => 0.0,
+                };
+                input.pressed(Action::HandServe) || input.pressed(Action::Confirm)
+            }
+            InputMode::Keyboard => {
+                self.racket.direction = match (input.down(Action::Left), input.down(Action::Right))
+                {
+                    (true, false) => -1.0,
+                    (false, true) => 1.0,
+                    _ => 0.0,
+                };
+                if free {
+                    self.racket.direction_y =
+                        match (input.down(Action::Up), input.down(Action::Down)) {
+                            (true, false) => -1.0,
+                            (false, true) => 1.0,
+                            _ => 0.0,
+                        };
+                }
+                input.pressed(Action::Confirm)
+            }
+            InputMode::Mouse => {
+                self.racket.target = Some(input.mouse.x - self.racket.width / 2.0);
+                if free {
+                    self.racket.target_y = Some(input.mouse.y - RACKET_HEIGHT / 2.0);
+                }
+                input.mouse_clicked
+            }
+        };
+        self.racket.tilting = match (input.down(Action::TiltLeft), input.down(Action::TiltRight)) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        // The countdown can be cut short.
+        if serve && matches!(self.state, ST::Countdown(_)) {
+            self.serve();
+        }
+
+        if let ST::Running | ST::Countdown(_) | ST::Bonus(_) = self.state {
+            if input.pressed(Action::Back) && ctx.config.settings.confirm_quit {
+                return Transition::Push(Box::new(QuitPrompt::new(false)));
+            }
+            let hand_pause =
+                one_handed && (input.pressed(Action::HandPause) || input.mouse_right_clicked);
+            if input.pressed(Action::Pause) || input.pressed(Action::Back) || hand_pause {
+                let restart = if self.mode.continues() { self.level } else { 0 };
+                return Transition::Push(Box::new(PauseMenu::new(restart)));
+            }
+        }
+
+        if let ST::Winning | ST::GameOver = self.state {
+            if input.pressed(Action::Confirm)
+                || input.pressed(Action::Back)
+                || (one_handed && serve)
+            {
+                let won = matches!(self.state, ST::Winning);
+                let level = if won || !self.mode.continues() {
+                    0
+                } else {
+                    self.level
+                };
+                let summary = Box::new(
+                    RunSummary::new(self.stats.clone(), won, level, self.rank)
+                        .rematch(self.versus)
+                        .code(self.run_code()),
+                );
+                // A run that made the table asks for a name before it goes in.
+                if self.rank.is_none() {
+                    return Transition::fade(Transition::Replace(summary));
+                }
+                let entry = HighScore {
+                    name: String::new(),
+                    score: self.stats.score,
+                    level: self.level,
+                    time: self.stats.time_played,
+                    speed: self.slowest,
+                };
+                return Transition::fade(Transition::Replace(Box::new(
+                    NameEntry::new(ctx, self.category(), entry, summary)
+                        .submission(self.submission.take()),
+                )));
+            }
+        }
+        Transition::None
+    }
+
+    fn calculate_physics(&mut self, ctx: &Context, duration: &Duration) {
+        let _scope = profile::scope("physics");
+        let (brick_width, brick_height) = self.brick_size;
+        let arena = self.arena();
+        // Capsules fall and effects wear off while the ball is in play.
+        if let ST::Running | ST::Countdown(_) = self.state {
+            let winds = &self.winds;
+            let caught =
+                self.powerups
+                    .update(duration.as_secs_f32(), &self.racket.shape(), |position| {
+                        wind_at(winds, arena, position)
+                    });
+            for kind in caught {
+                self.stats.powerups_collected += 1;
+                self.events.emit(GameEvent::PowerUpCaught {
+                    position: self.racket.centre(),
+                    kind,
+                });
+            }
+            self.ball.radius = self.powerups.ball_radius(self.ball_radius());
+            self.ball.damage = self.powerups.ball_damage(1);
+            self.ball.deflection = self.powerups.ball_deflection(1.0);
+        }
+        let radius = self.ball.radius;
+        let ball = Vector2::new(self.ball.x, self.ball.y);
+        let in_dead_zone = matches!(self.state, ST::Running)
+            && self
+                .dead_zones
+                .iter()
+                .any(|zone| zone.check_collision_point_rec(arena.to_arena(ball)));
+        let off_top = self.mode.dual_paddle() && self.ball.y <= -radius;
+        if self.ball.y >= WINDOW_HEIGHT + radius || off_top || in_dead_zone {
+            self.stats.balls_lost += 1;
+            let spawn = self.practice.as_ref().and_then(|p| p.spawn);
+            if let Some(spawn) = spawn {
+                self.respawn(spawn);
+            } else if self.lives == 0 && !self.mode.endless() {
+                self.set_state(ST::GameOver);
+            } else {
+                self.set_state(ST::Countdown(COUNTDOWN));
+                self.ball_fade = Tween::new(BALL_FADE_TIME);
+                if !self.mode.endless() {
+                    self.lives -= 1;
+                }
+                self.stats.break_combo();
+                self.events.emit(GameEvent::LifeLost);
+                self.trail.clear();
+                self.powerups.clear();
+                self.ball = self.new_ball();
+                if self.paddle_wear {
+                    self.wear += 1;
+                }
+                self.racket = Racket::new(self.racket_width());
+            }
+        }
+
+        // The racket can be lined up during the countdown, carrying the ball
+        // with it, and catches gems in a bonus stage.
+        if let ST::Running | ST::Countdown(_) | ST::Bonus(_) = self.state {
+            match self.racket.target {
+                Some(target) => self.racket.x = target,
+                None => {
+                    self.racket.x +=
+                        self.racket.direction * self.tuning.paddle_speed * duration.as_secs_f32()
+                }
+            }
+            self.racket.x = self.racket.x.clamp(0.0, WINDOW_WIDTH - self.racket.width);
+            match self.racket.target_y {
+                Some(target) => self.racket.y = target,
+                None => {
+                    self.racket.y += self.racket.direction_y
+                        * self.tuning.paddle_speed
+                        * VERTICAL_SPEED
+                        * duration.as_secs_f32()
+                }
+            }
+            let band = self.mutators.paddle_band(PADDLE_BAND);
+            self.racket.y = self.racket.y.clamp(RACKET_POS_Y - band, RACKET_POS_Y);
+            let step = TILT_SPEED * duration.as_secs_f32();
+            let goal = self.racket.tilting * MAX_TILT;
+            self.racket.tilt += (goal - self.racket.tilt).clamp(-step, step);
+        }
+
+        if let ST::Bonus(left) = self.state {
+            let seconds = duration.as_secs_f32();
+            if let Some(bonus) = &mut self.bonus {
+                for (position, points) in bonus.update(seconds, &self.racket.shape()) {
+                    let points = self.mutators.points(points * self.mode.score_multiplier());
+                    self.stats.gem_caught(points);
+                    self.events.emit(GameEvent::GemCaught { position, points });
+                }
+            }
+            let left = left - seconds;
+            if left > 0.0 {
+                self.set_state(ST::Bonus(left));
+            } else {
+                self.next_level(&ctx.levels);
+            }
+        }
+
+        if let ST::Countdown(remaining) = self.state {
+            self.ball.x = self.racket.x + self.racket.width / 2.0;
+            self.ball.y = self.racket.y - radius - 1.0;
+            let remaining = remaining - duration.as_secs_f32();
+            if remaining > 0.0 {
+                self.set_state(ST::Countdown(remaining));
+            } else {
+                self.serve();
+            }
+        }
+
+        if let ST::Running = self.state {
+            let gain = self
+                .mutators
+                .speed_gain(if ctx.config.settings.constant_ball_speed {
+                    0.0
+                } else {
+                    1.0
+                });
+
+            // The ball moves first and then bounces off whatever it ended up
+            // touching. Magnet bricks bend its path towards them, and wind
+            // pushes it about.
+            let mut forces = physics::Forces::default();
+            let position = Vector2::new(self.ball.x, self.ball.y);
+            forces.add(wind_at(&self.winds, arena, position));
+            for brick in self.bricks.iter().filter(|b| b.magnet) {
+                forces.attract(
+                    position,
+                    arena.to_screen(brick.centre(self.brick_size)),
+                    MAGNET_REACH,
+                    MAGNET_STRENGTH,
+                );
+            }
+            let speed = self.ball.speed + self.ball.boost;
+            forces.steer(&mut self.ball.direction, speed, duration.as_secs_f32());
+            self.ball.boost *= (-BOOST_DECAY * duration.as_secs_f32()).exp();
+
+            self.ball.x += self.ball.direction.x * speed / 2.0f32.sqrt() * duration.as_secs_f32();
+            self.ball.y += self.ball.direction.y * speed / 2.0f32.sqrt() * duration.as_secs_f32();
+
+            if self.ball.y <= 0.0 && !self.mode.dual_paddle() {
+                self.ball.speed += gain * self.tuning.bounce_speedup;
+                self.bounce_off_wall(None, Some(1.0));
+            }
+
+            if self.ball.x <= radius {
+                self.ball.speed += gain * self.tuning.bounce_speedup;
+                self.bounce_off_wall(Some(1.0), None);
+            }
+
+            if self.ball.x >= WINDOW_WIDTH - radius {
+                self.ball.speed += gain * self.tuning.bounce_speedup;
+                self.bounce_off_wall(Some(-1.0), None);
+            }
+
+            self.pass_through_portals();
+            self.hit_bumpers();
+            self.hit_walls(gain);
+
+            // Bricks are met in the arena's frame, by the box around the ball.
+            // The frame is the one the arena has turned to by the end of the
+            // step, so a ball pushed out of a brick is still out once the
+            // bricks have turned with it.
+            let arena = self.arena_at(self.level_elapsed + *duration);
+            let in_arena = arena.to_arena(Vector2::new(self.ball.x, self.ball.y));
+            let ball = Rectangle {
+                x: in_arena.x - radius,
+                y: in_arena.y - radius,
+                width: 2.0 * radius,
+                height: 2.0 * radius,
+            };
+            // The racket can be tilted, so it is tested as a turned
+            // rectangle against the ball's circle and bounces the ball off
+            // whichever face it touched.
+            // The top paddle of dual paddle mode sends it back down instead.
+            let centre = Vector2::new(self.ball.x, self.ball.y);
+            let bottom = physics::circle_contact(centre, radius, &self.racket.shape());
+            let top = self
+                .mode
+                .dual_paddle()
+                .then(|| physics::circle_contact(centre, radius, &self.racket.mirrored_shape()))
+                .flatten();
+            let down = top.is_some();
+            self.ball.already_in_collision = match bottom.or(top) {
+                Some(contact) => {
+                    if !self.ball.already_in_collision
+                        && physics::bounce(&mut self.ball.direction, contact.normal)
+                    {
+                        if down {
+                            self.ball.direction.y *= -1.0;
+                        }
+                        physics::clamp_upwards(&mut self.ball.direction, MAX_BOUNCE_ANGLE);
+                        if down {
+                            self.ball.direction.y *= -1.0;
+                        }
+                        self.ball.speed += gain * self.tuning.bounce_speedup;
+                        self.last_contact = Some((contact.point, contact.normal));
+                        self.stats.break_combo();
+                        self.events.emit(GameEvent::PaddleHit {
+                            position: contact.point,
+                            speed: self.ball.speed,
+                        });
+                    }
+                    true
+                }
+                None => false,
+            };
+
+            let hit = physics::first_hit(
+                ball,
+                self.bricks.iter().map(|brick| Rectangle {
+                    x: brick.x,
+                    y: brick.y,
+                    width: brick_width,
+                    height: brick_height,
+                }),
+            );
+            if let Some(hit) = hit {
+                let brick = &mut self.bricks[hit.index];
+                let centre =
+                    Vector2::new(brick.x + brick_width / 2.0, brick.y + brick_height / 2.0);
+                let position = arena.to_screen(centre);
+                let (hp, kind) = (brick.live, brick.kind);
+                brick.live = brick.live.saturating_sub(self.ball.damage);
+                self.stats.brick_hit();
+                if brick.live == 0 {
+                    let points = self.powerups.points(
+                        self.mutators
+                            .points(brick.points() * self.mode.score_multiplier()),
+                    );
+                    self.stats.brick_destroyed(points);
+                    // Races are played on the same rules, so nothing drops.
+                    if !self.versus {
+                        self.powerups.drop_at(position);
+                    }
+                    self.level_score += points;
+                    // It crumbles in the colour it had before the hit, even
+                    // when a heavy ball took several hit points at once.
+                    if let Some(dying) = self.dying_bricks.spawn() {
+                        *dying = DyingBrick {
+                            x: brick.x,
+                            y: brick.y,
+                            hp,
+                            kind,
+                            age: 0.0,
+                        };
+                    }
+                    self.events.emit(GameEvent::BrickDestroyed {
+                        position,
+                        hp,
+                        kind,
+                        points,
+                    });
+                } else {
+                    brick.hit = BRICK_HIT_TIME;
+                    self.events.emit(GameEvent::BrickHit { position, hp, kind });
+                }
+                self.ball.speed += gain * self.tuning.brick_speedup;
+                let before = arena.unturn(self.ball.direction);
+                let mut direction = before;
+                // The side it came in by is judged from the ball's centre.
+                let normal = physics::reflect(in_arena, centre, &hit, &mut direction);
+                // A brick left standing pushes the ball back out, so it
+                // doesn't end the step inside and get hit again.
+                if brick.live > 0 {
+                    let bounds = Rectangle {
+                        x: brick.x,
+                        y: brick.y,
+                        width: brick_width,
+                        height: brick_height,
+                    };
+                    let out = arena.turn(physics::separate(ball, bounds, normal));
+                    self.ball.x += out.x;
+                    self.ball.y += out.y;
+                }
+                // A ball that breaks the brick can keep going, mostly on its
+                // way; one that doesn't always bounces back.
+                if brick.live == 0 && self.ball.deflection < 1.0 {
+                    let through = before.lerp(direction, self.ball.deflection);
+                    if through.length() > 0.1 {
+                        direction = through.normalized() * before.length();
+                    }
+                }
+                self.ball.direction = arena.turn(direction);
+                self.last_contact = Some((arena.to_screen(hit.contact()), arena.turn(normal)));
+            }
+
+            self.bricks.retain(|b| b.live > 0);
+
+            self.stats.time_played += *duration;
+            self.stats.ball_speed(self.ball.speed);
+            self.level_elapsed += *duration;
+            self.trace.record(self.racket.x, (self.ball.x, self.ball.y));
+            if let Some(recorder) = &mut self.ghost_recorder {
+                recorder.record(
+                    &self.level_elapsed,
+                    self.racket.x,
+                    self.racket.width,
+                    Vector2::new(self.ball.x, self.ball.y),
+                );
+            }
+
+            if self.bricks.is_empty() {
+                self.stats.level_cleared(self.level_elapsed);
+                self.splits.split(self.stats.time_played);
+                self.set_state(
+                    if self.level + 1 < ctx.levels.len() || self.mode.endless() {
+                        ST::LevelCleared(LEVEL_CLEARED_PAUSE)
+                    } else {
+                        ST::Winning
+                    },
+                );
+            }
+
+            self.trail.push(Vector2::new(self.ball.x, self.ball.y));
+        }
+    }
+
+    // Tells the other player how this run is going and ends it when either
+    // side clears the level: the first to do so wins.
+    fn race(&mut self, ctx: &mut Context) {
+        if let ST::LevelCleared(_) = self.state {
+            self.set_state(ST::Winning);
+        }
+        let Some(peer) = ctx.peer.as_mut() else {
+            return;
+        };
+        let phase = match self.state {
+            ST::Winning => Phase::Cleared,
+            ST::GameOver => Phase::Out,
+            _ => Phase::Playing,
+        };
+        peer.update(&Status {
+            phase,
+            level: self.level,
+            score: self.stats.score,
+            bricks: self.bricks.len(),
+            lives: self.lives,
+            seed: self.seed,
+        });
+        if phase == Phase::Playing && peer.opponent().is_some_and(|o| o.phase == Phase::Cleared) {
+            self.set_state(ST::GameOver);
+            self.beaten = true;
+        }
+    }
+
+    fn category(&self) -> Category {
+        Category {
+            mode: self.mode,
+            challenge: self.challenge,
+        }
+    }
+
+    // The setup this run was started with, for playing it again.
+    fn run_code(&self) -> RunCode {
+        RunCode {
+            seed: self.seed,
+            mode: self.mode,
+            challenge: self.challenge,
+            mutators: self.mutators,
+            difficulty: self.difficulty,
+        }
+    }
+
+    fn respawn(&mut self, spawn: Spawn) {
+        self.ball = Projectile {
+            x: spawn.position.x,
+            y: spawn.position.y,
+            speed: spawn.speed,
+            direction: spawn.direction,
+            already_in_collision: false,
+            boost: 0.0,
+            ..self.ball
+        };
+        self.set_state(ST::Running);
+        self.stats.break_combo();
+        self.trail.clear();
+    }
+
+    // Freezing, stepping and placing the ball, marking where it respawns, and
+    // picking up edited level files.
+    fn practice_tools(&mut self, ctx: &mut Context, input: &Input) {
+        let frozen = match &mut self.practice {
+            Some(practice) => {
+                if input.pressed(Action::PracticeFreeze) {
+                    practice.frozen = !practice.frozen;
+                }
+                practice.frozen
+            }
+            None => return,
+        };
+        let center = Vector2::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0);
+        if frozen {
+            // The ball turns a quarter at a time.
+            let direction = self.ball.direction;
+            if input.pressed(Action::Up) {
+                self.ball.direction = Vector2::new(direction.y, -direction.x);
+            }
+            if input.pressed(Action::Down) {
+                self.ball.direction = Vector2::new(-direction.y, direction.x);
+            }
+            if input.mouse_clicked {
+                self.ball.x = input.mouse.x;
+                self.ball.y = input.mouse.y;
+                self.set_state(ST::Running);
+                self.trail.clear();
+            }
+        }
+        let Some(practice) = &mut self.practice else {
+            return;
+        };
+        if input.pressed(Action::PracticeMark) {
+            practice.spawn = Some(Spawn {
+                position: Vector2::new(self.ball.x, self.ball.y),
+                direction: self.ball.direction,
+                speed: self.ball.speed,
+            });
+            self.popups.spawn(
+                format_args!("{}", tr!("practice.respawn_set")),
+                center,
+                30,
+                Color::WHITE,
+            );
+        }
+        if practice.levels_changed() {
+            let levels = level::load_all(&ctx.registry);
+            if levels.len() == ctx.levels.len() {
+                ctx.levels = levels;
+                self.load_level(&ctx.levels[self.level]);
+                self.popups.spawn(
+                    format_args!("{}", tr!("practice.reloaded")),
+                    center,
+                    30,
+                    Color::WHITE,
+                );
+            } else {
+                self.popups.spawn(
+                    format_args!("{}", tr!("practice.levels_changed")),
+                    center,
+                    24,
+                    Color::WHITE,
+                );
+            }
+        }
+        // The bricks that are left as a PNG level, to draw on and play.
+        if input.pressed(Action::PracticeExport) {
+            let path = paths::data_subdir(practice::LAYOUTS_DIR).join(format!(
+                "level{}-{}.png",
+                self.level + 1,
+                capture::timestamp()
+            ));
+            let message = match self.layout(ctx).export_image(&path) {
+                Ok(()) => tr!("practice.exported", path.display()),
+                Err(e) => e,
+            };
+            self.popups
+                .spawn(format_args!("{}", message), center, 24, Color::WHITE);
+        }
+    }
+
+    // Launches the ball the way the racket is moving, or towards the middle of
+    // the screen if it is standing still.
+    fn serve(&mut self) {
+        self.ball.direction.x = if self.racket.direction != 0.0 {
+            self.racket.direction
+        } else if self.ball.x < WINDOW_WIDTH / 2.0 {
+            1.0
+        } else {
+            -1.0
+        };
+        self.set_state(ST::Running);
+        self.go_time = GO_TIME;
+    }
+
+    // Angled walls bounce the ball like the edges of the arena, off whichever
+    // way they face.
+    fn hit_walls(&mut self, gain: f32) {
+        let position = Vector2::new(self.ball.x, self.ball.y);
+        let radius = self.ball.radius;
+        let arena = self.arena();
+        for wall in &self.walls {
+            let wall = physics::Segment {
+                a: arena.to_screen(wall.a),
+                b: arena.to_screen(wall.b),
+            };
+            let Some(contact) = physics::segment_contact(position, radius, &wall) else {
+                continue;
+            };
+            if !physics::bounce(&mut self.ball.direction, contact.normal) {
+                continue;
+            }
+            let out = contact.point + contact.normal * (radius + 1.0);
+            self.ball.x = out.x;
+            self.ball.y = out.y;
+            self.ball.speed += gain * self.tuning.bounce_speedup;
+            self.last_contact = Some((contact.point, contact.normal));
+            self.events.emit(GameEvent::WallHit {
+                position: contact.point,
+            });
+            return;
+        }
+    }
+
+    // The paddle's width before any chat mutator: set by the mutators, then
+    // worn down by WEAR_FACTOR for every ball lost on this level, to no less
+    // than MIN_WEAR of it.
+    fn racket_width(&self) -> f32 {
+        let wear = WEAR_FACTOR.powi(self.wear as i32).max(MIN_WEAR);
+        self.mutators.paddle_width(RACKET_WIDTH) * wear * self.oversize
+    }
+
+    // Before the power-ups have their say.
+    fn ball_radius(&self) -> f32 {
+        self.mutators.ball_radius(PROJ_RADIUS) * self.oversize
+    }
+
+    fn new_ball(&self) -> Projectile {
+        Projectile::new(
+            &self.tuning,
+            self.difficulty,
+            &self.mutators,
+            self.ball_radius(),
+        )
+    }
+
+    // Where the arena has turned to by now; it follows the level's clock, so
+    // replays and ghosts see the same.
+    fn arena(&self) -> Arena {
+        self.arena_at(self.level_elapsed)
+    }
+
+    fn arena_at(&self, elapsed: Duration) -> Arena {
+        Arena {
+            angle: self.mutators.arena_angle(elapsed.as_secs_f32()),
+        }
+    }
+
+    // A ball touching a bumper is bounced off it, put back on its surface
+    // so it can't sink in, and kicked away faster for a moment.
+    fn hit_bumpers(&mut self) {
+        let position = Vector2::new(self.ball.x, self.ball.y);
+        let radius = self.ball.radius;
+        let arena = self.arena();
+        for bumper in &mut self.bumpers {
+            let centre = arena.to_screen(bumper.centre);
+            let Some(contact) = physics::circles_contact(position, radius, centre, bumper.radius)
+            else {
+                continue;
+            };
+            if !physics::bounce(&mut self.ball.direction, contact.normal) {
+                continue;
+            }
+            let out = contact.point + contact.normal * (radius + 1.0);
+            self.ball.x = out.x;
+            self.ball.y = out.y;
+            self.ball.boost = BUMPER_KICK;
+            bumper.flash = BUMPER_FLASH_TIME;
+            let points = self.powerups.points(
+                self.mutators
+                    .points(BUMPER_POINTS * self.mode.score_multiplier()),
+            );
+            self.stats.bumper_hit(points);
+            self.level_score += points;
+            self.last_contact = Some((contact.point, contact.normal));
+            self.events.emit(GameEvent::BumperHit {
+                position: contact.point,
+                points,
+            });
+            return;
+        }
+    }
+
+    // Streaks blow through each wind area in the direction of its force.
+    // Where each one is follows from the time alone, so they need no state.
+    fn draw_wind(&self, d: &mut impl RaylibDraw, color: Color) {
+        let time = self.level_elapsed.as_secs_f32();
+        for wind in &self.winds {
+            let area = wind.area;
+            let strength = wind.force.length();
+            if strength == 0.0 || area.width <= 0.0 || area.height <= 0.0 {
+                continue;
+            }
+            let heading = wind.force / strength;
+            let travel = heading * (strength * WIND_STREAK_SPEED * time);
+            let count = (area.width * area.height / WIND_STREAK_AREA).ceil() as usize;
+            for i in 0..count {
+                // Spread evenly over the area by the golden ratio.
+                let u = (i as f32 * 0.618_034).fract() * area.width;
+                let v = (i as f32 * 0.381_966 + 0.5).fract() * area.height;
+                let head = Vector2::new(
+                    area.x + (u + travel.x).rem_euclid(area.width),
+                    area.y + (v + travel.y).rem_euclid(area.height),
+                );
+                d.draw_line_v(head - heading * WIND_STREAK_LENGTH, head, color.alpha(0.15));
+            }
+        }
+    }
+
+    // A ball touching a portal comes out of the other end at once, at the
+    // same place relative to it and with the same speed and direction; both
+    // ends of a pair face the same way, so nothing needs turning.
+    fn pass_through_portals(&mut self) {
+        let position = Vector2::new(self.ball.x, self.ball.y);
+        let (width, height) = self.brick_size;
+        let radius = self.ball.radius;
+        let arena = self.arena();
+        let touching = |centre: Vector2| {
+            let rect = physics::OrientedRect {
+                centre: arena.to_screen(centre),
+                half_size: Vector2::new(width / 2.0, height / 2.0),
+                angle: arena.angle,
+            };
+            physics::circle_contact(position, radius, &rect).is_some()
+        };
+        if let Some((pair, end)) = self.ball.portal {
+            if touching(self.portals[pair][end]) {
+                return;
+            }
+            self.ball.portal = None;
+        }
+        let entered = self.portals.iter().enumerate().find_map(|(pair, ends)| {
+            (0..2)
+                .find(|end| touching(ends[*end]))
+                .map(|end| (pair, end))
+        });
+        let Some((pair, end)) = entered else {
+            return;
+        };
+        let exit = 1 - end;
+        let to = arena.to_screen(self.portals[pair][exit])
+            + (position - arena.to_screen(self.portals[pair][end]));
+        self.ball.x = to.x;
+        self.ball.y = to.y;
+        self.ball.portal = Some((pair, exit));
+        self.trail.clear();
+        self.events
+            .emit(GameEvent::Teleported { from: position, to });
+    }
+
+    // Sets the ball direction on the given axes, reporting a hit only when it
+    // actually turns the ball around rather than on every frame it touches the wall.
+    fn bounce_off_wall(&mut self, x: Option<f32>, y: Option<f32>) {
+        // Only the sign is set, so a ball sent off at an angle keeps it.
+        let before = self.ball.direction;
+        self.ball.direction.x = x.map_or(before.x, |x| x * before.x.abs());
+        self.ball.direction.y = y.map_or(before.y, |y| y * before.y.abs());
+        if self.ball.direction != before {
+            let position = Vector2::new(self.ball.x, self.ball.y);
+            self.last_contact = Some((position, Vector2::new(x.unwrap_or(0.0), y.unwrap_or(0.0))));
+            self.events.emit(GameEvent::WallHit { position });
+        }
+    }
+
+    // Collisions are panned to where they happened on screen. Both the speed
+    // and the combo start over when a life is lost, and so does the pitch.
+    fn cue(&self, event: &GameEvent) -> Option<Cue> {
+        let sfx = match event {
+            // A heavy ball thuds into bricks instead.
+            GameEvent::BrickHit { .. } | GameEvent::BrickDestroyed { .. }
+                if self.ball.damage > 1 =>
+            {
+                Sfx::Thud
+            }
+            event => Sfx::for_event(event)?,
+        };
+        let cue = Cue::new(sfx);
+        let position = match event {
+            GameEvent::WallHit { position }
+            | GameEvent::PaddleHit { position, .. }
+            | GameEvent::BrickHit { position, .. }
+            | GameEvent::BrickDestroyed { position, .. } => *position,
+            GameEvent::PowerUpCaught { position, .. } | GameEvent::GemCaught { position, .. } => {
+                *position
+            }
+            GameEvent::Teleported { to, .. } => *to,
+            GameEvent::BumperHit { position, .. } => *position,
+            GameEvent::LifeLost | GameEvent::ChatVote { .. } => return Some(cue),
+        };
+        let cue = cue.pan(position.x / WINDOW_WIDTH * 2.0 - 1.0);
+        let serve_speed = self.tuning.ball_speed * self.difficulty.ball_speed_factor();
+        let pitch = 1.0
+            + (self.ball.speed / serve_speed - 1.0) * PITCH_PER_SPEED
+            + self.stats.combo() as f32 * PITCH_PER_COMBO;
+        Some(cue.pitch(pitch.clamp(1.0, MAX_PITCH)))
+    }
+
+    // Turns this frame's physics events into visual effects.
+    fn react(&mut self, ctx: &Context) {
+        let _scope = profile::scope("effects");
+        let (brick_width, brick_height) = self.brick_size;
+        let theme = ctx.theme();
+        let sparks = Burst {
+            color: theme.sparks,
+            ..SPARKS
+        };
+        let racket_width = self.racket_width();
+        for event in self.events.drain() {
+            match event {
+                GameEvent::WallHit { position } => self.particles.burst(point(position), &sparks),
+                GameEvent::PaddleHit { position, speed } => {
+                    self.particles.burst(point(position), &sparks);
+                    if speed > FAST_HIT_SPEED {
+                        self.camera.shake.add(0.3);
+                    }
+                }
+                GameEvent::BrickHit { position, hp, kind } => self.particles.burst(
+                    point(position),
+                    &Burst {
+                        color: brick_colors(theme, kind, hp).0,
+                        ..CHIPS
+                    },
+                ),
+                GameEvent::BrickDestroyed {
+                    position,
+                    hp,
+                    kind,
+                    points,
+                } => {
+                    self.particles.burst(
+                        Rectangle {
+                            x: position.x - brick_width / 2.0,
+                            y: position.y - brick_height / 2.0,
+                            width: brick_width,
+                            height: brick_height,
+                        },
+                        &Burst {
+                            color: brick_colors(theme, kind, hp).0,
+                            ..SHARDS
+                        },
+                    );
+                    let combo = self.stats.combo();
+                    self.popups.spawn(
+                        format_args!("+{}", points),
+                        position,
+                        POPUP_SIZE + 2 * combo.min(COMBO_POPUP_MAX) as i32,
+                        if combo >= 2 { theme.sparks } else { theme.text },
+                    );
+                    // The blow that clears the level gets the slow motion zoom.
+                    if self.bricks.is_empty() && ctx.config.settings.visual.zoom() {
+                        self.camera.zoom_to(position);
+                    }
+                }
+                GameEvent::LifeLost => self.camera.shake.add(0.7),
+                GameEvent::BumperHit { position, points } => {
+                    self.particles.burst(point(position), &sparks);
+                    self.popups.spawn(
+                        format_args!("+{}", points),
+                        position,
+                        POPUP_SIZE,
+                        theme.text,
+                    );
+                }
+                GameEvent::Teleported { from, to } => {
+                    self.particles.burst(point(from), &sparks);
+                    self.particles.burst(point(to), &sparks);
+                }
+                GameEvent::PowerUpCaught { position, kind } => self.popups.spawn(
+                    format_args!("{}", kind.name()),
+                    position,
+                    POPUP_SIZE + 4,
+                    theme.sparks,
+                ),
+                GameEvent::GemCaught { position, points } => {
+                    self.particles.burst(point(position), &sparks);
+                    self.popups.spawn(
+                        format_args!("+{}", points),
+                        position,
+                        POPUP_SIZE,
+                        theme.sparks,
+                    );
+                }
+                GameEvent::ChatVote { mutator } => {
+                    match mutator {
+                        Mutator::SpeedUp => self.ball.speed *= MUTATOR_SPEED_UP,
+                        Mutator::ShrinkPaddle => self.racket.resize(racket_width * MUTATOR_SHRINK),
+                        // Straight down onto the paddle, from the top.
+                        Mutator::PowerUp => self
+                            .powerups
+                            .send(Vector2::new(self.racket.x + self.racket.width / 2.0, 0.0)),
+                    }
+                    self.popups.spawn(
+                        format_args!("{}", mutator.announcement()),
+                        Vector2::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0),
+                        POPUP_SIZE * 2,
+                        theme.sparks,
+                    );
+                }
+            }
+        }
+    }
+
+    // The playfield is drawn through the world camera; the HUD stays put.
+    // In high contrast mode textures are skipped and everything gets a thick outline.
+    fn draw_world(&self, ctx: &Context, d: &mut impl RaylibDraw) {
+        let _scope = profile::scope("draw world");
+        let (brick_width, brick_height) = self.brick_size;
+        let theme = ctx.theme();
+        let visual = &ctx.config.settings.visual;
+        let assets = (!visual.high_contrast).then_some(&ctx.assets);
+        let outline = visual.high_contrast.then_some(theme.text);
+        let radius = self.ball.radius;
+        // A heavy ball is drawn darker.
+        let shade = |color: Color| {
+            if self.ball.damage > 1 {
+                color.brightness(HEAVY_BALL_SHADE)
+            } else {
+                color
+            }
+        };
+
+        if visual.trails() {
+            self.trail.render(
+                d,
+                self.ball.speed / self.tuning.ball_speed,
+                radius,
+                shade(theme.ball),
+            );
+        }
+        if let Some(ghost) = self.ghost.as_ref().and_then(|g| g.at(&self.level_elapsed)) {
+            draw_ghost(d, theme, &ghost, radius, GHOST_ALPHA);
+        }
+        let ball = Vector2::new(self.ball.x, self.ball.y);
+        // There is no ball in a bonus stage.
+        let ball_alpha = match self.state {
+            ST::Bonus(_) => 0.0,
+            _ => self.ball_fade.linear(),
+        };
+        match assets.and_then(|a| a.ball.as_ref()) {
+            Some(texture) => draw_stretched(
+                d,
+                texture,
+                Rectangle {
+                    x: self.ball.x - radius,
+                    y: self.ball.y - radius,
+                    width: radius * 2.0,
+                    height: radius * 2.0,
+                },
+                shade(Color::WHITE).alpha(ball_alpha),
+            ),
+            None => d.draw_circle_v(ball, radius, shade(theme.ball).alpha(ball_alpha)),
+        }
+        if let Some(color) = outline {
+            d.draw_ring(
+                ball,
+                radius,
+                radius + OUTLINE,
+                0.0,
+                360.0,
+                24,
+                color.alpha(ball_alpha),
+            );
+        }
+
+        let mut paddles = vec![self.racket.shape()];
+        if self.mode.dual_paddle() {
+            paddles.push(self.racket.mirrored_shape());
+        }
+        let countdown = matches!(self.state, ST::Countdown(_));
+        for shape in paddles
+            .iter()
+            .filter(|_| self.mutators.paddle_visible(countdown))
+        {
+            let racket = Rectangle {
+                x: shape.centre.x - shape.half_size.x,
+                y: shape.centre.y - shape.half_size.y,
+                width: shape.half_size.x * 2.0,
+                height: shape.half_size.y * 2.0,
+            };
+            let _turned = Turned::about(shape.centre, shape.angle);
+            match assets.and_then(|a| a.paddle.as_ref()) {
+                Some(texture) => draw_stretched(d, texture, racket, Color::WHITE),
+                None => d.draw_rectangle_gradient_v(
+                    racket.x as i32,
+                    racket.y as i32,
+                    racket.width as i32,
+                    racket.height as i32,
+                    theme.paddle,
+                    theme.paddle_shade,
+                ),
+            }
+            if let Some(color) = outline {
+                d.draw_rectangle_lines_ex(racket, OUTLINE, color);
+            }
+        }
+
+        let slide = if visual.slides() {
+            (1.0 - self.bricks_slide.ease_out()) * -WINDOW_HEIGHT
+        } else {
+            0.0
+        };
+        // What the level placed turns with a rotating arena; the ball and the
+        // paddle are drawn where they are.
+        let turned = Turned::about(Arena::CENTRE, self.arena().angle);
+        self.draw_wind(d, theme.text);
+        for wall in &self.walls {
+            d.draw_line_ex(wall.a, wall.b, WALL_THICKNESS, theme.paddle);
+        }
+        for zone in &self.dead_zones {
+            d.draw_rectangle_rec(*zone, Color::RED.alpha(0.15));
+            d.draw_rectangle_lines_ex(*zone, 2.0, Color::RED.alpha(0.6));
+        }
+
+        // Portals are a dark hole with a swirl turning inside it.
+        let spin = self.level_elapsed.as_secs_f32() * PORTAL_SPIN;
+        for (i, pair) in self.portals.iter().enumerate() {
+            let color = PORTAL_COLORS[i % PORTAL_COLORS.len()];
+            for centre in pair {
+                let rect = Rectangle {
+                    x: centre.x - brick_width / 2.0,
+                    y: centre.y - brick_height / 2.0 + slide,
+                    width: brick_width,
+                    height: brick_height,
+                };
+                let centre = Vector2::new(centre.x, centre.y + slide);
+                let radius = brick_height / 2.0 - 2.0;
+                d.draw_rectangle_rec(rect, Color::BLACK.alpha(0.6));
+                for start in [spin, spin + 180.0] {
+                    d.draw_ring(
+                        centre,
+                        radius * 0.5,
+                        radius,
+                        start,
+                        start + 120.0,
+                        16,
+                        color,
+                    );
+                }
+                d.draw_rectangle_lines_ex(rect, 2.0, color);
+            }
+        }
+        // Bumpers light up and swell a little when hit.
+        for bumper in &self.bumpers {
+            let flash = bumper.flash / BUMPER_FLASH_TIME;
+            let centre = Vector2::new(bumper.centre.x, bumper.centre.y + slide);
+            let radius = bumper.radius * (1.0 + 0.15 * flash);
+            d.draw_circle_v(centre, radius, BUMPER_COLOR.brightness(-0.5 + flash));
+            d.draw_ring(
+                centre,
+                radius - 4.0,
+                radius,
+                0.0,
+                360.0,
+                32,
+                BUMPER_COLOR.brightness(flash),
+            );
+            if let Some(color) = outline {
+                d.draw_ring(centre, radius, radius + OUTLINE, 0.0, 360.0, 32, color);
+            }
+        }
+        for brick in self.bricks.iter() {
+            let jiggle = (brick.hit * 90.0).sin() * BRICK_JIGGLE * brick.hit / BRICK_HIT_TIME;
+            let rect = Rectangle {
+                x: brick.x + jiggle * visual.shake(),
+                y: brick.y + slide,
+                width: brick_width,
+                height: brick_height,
+            };
+            draw_brick(
+                d,
+                assets,
+                brick_colors(theme, brick.kind, brick.live),
+                rect,
+                1.0,
+            );
+            if brick.hp > 1 {
+                draw_cracks(
+                    d,
+                    rect,
+                    Vector2::new(brick.x, brick.y),
+                    brick.hp - brick.live,
+                );
+                draw_hp_pips(
+                    d,
+                    rect,
+                    brick.live,
+                    brick.hp,
+                    outline.unwrap_or(Color::WHITE),
+                );
+            }
+            if brick.magnet {
+                // A ring swells out to the edge of the field and fades.
+                let pulse = (self.level_elapsed.as_secs_f32() / MAGNET_PULSE_TIME).fract();
+                let centre = Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+                d.draw_circle_v(centre, MAGNET_REACH, MAGNET_COLOR.alpha(0.05));
+                d.draw_ring(
+                    centre,
+                    MAGNET_REACH * pulse - 2.0,
+                    MAGNET_REACH * pulse,
+                    0.0,
+                    360.0,
+                    48,
+                    MAGNET_COLOR.alpha(0.5 * (1.0 - pulse)),
+                );
+                d.draw_rectangle_lines_ex(rect, 3.0, MAGNET_COLOR);
+            }
+            if brick.hit > BRICK_HIT_TIME - BRICK_FLASH_TIME {
+                d.draw_rectangle_rec(rect, Color::WHITE);
+            }
+            if let Some(color) = outline {
+                d.draw_rectangle_lines_ex(rect, OUTLINE, color);
+            }
+        }
+
+        // Dying bricks shrink towards their centre, sink a little and fade out.
+        for brick in self.dying_bricks.iter() {
+            let t = brick.age / BRICK_DYING_TIME;
+            let scale = 1.0 - 0.6 * t;
+            let rect = Rectangle {
+                x: brick.x + brick_width * (1.0 - scale) / 2.0,
+                y: brick.y + brick_height * (1.0 - scale) / 2.0 + 12.0 * t,
+                width: brick_width * scale,
+                height: brick_height * scale,
+            };
+            draw_brick(
+                d,
+                assets,
+                brick_colors(theme, brick.kind, brick.hp),
+                rect,
+                1.0 - t,
+            );
+        }
+        drop(turned);
+
+        if visual.particles() {
+            self.particles.render(d);
+        }
+        self.powerups.render(d, ctx.font());
+        if let Some(bonus) = &self.bonus {
+            bonus.render(d);
+        }
+        self.popups.render(d, ctx.font());
+        if ctx.debug_overlay {
+            self.draw_debug(d);
+        }
+    }
+
+    // The rectangles physics actually tests against, which for the ball is
+    // not the circle that gets drawn.
+    fn draw_debug(&self, d: &mut impl RaylibDraw) {
+        let (brick_width, brick_height) = self.brick_size;
+        d.draw_rectangle_lines_ex(
+            Rectangle {
+                x: self.ball.x,
+                y: self.ball.y,
+                width: self.ball.radius,
+                height: self.ball.radius,
+            },
+            1.0,
+            Color::MAGENTA,
+        );
+        d.draw_circle_lines(
+            self.ball.x as i32,
+            self.ball.y as i32,
+            self.ball.radius,
+            Color::MAGENTA,
+        );
+        let turned = Turned::about(self.racket.centre(), self.racket.tilt);
+        d.draw_rectangle_lines_ex(
+            Rectangle {
+                x: self.racket.x,
+                y: self.racket.y,
+                width: self.racket.width,
+                height: RACKET_HEIGHT,
+            },
+            1.0,
+            Color::MAGENTA,
+        );
+        drop(turned);
+        let turned = Turned::about(Arena::CENTRE, self.arena().angle);
+        for brick in self.bricks.iter() {
+            d.draw_rectangle_lines_ex(
+                Rectangle {
+                    x: brick.x,
+                    y: brick.y,
+                    width: brick_width,
+                    height: brick_height,
+                },
+                1.0,
+                Color::MAGENTA,
+            );
+        }
+        drop(turned);
+
+        let ball = Vector2::new(self.ball.x, self.ball.y);
+        let velocity = self.ball.direction * ((self.ball.speed + self.ball.boost) / 2.0f32.sqrt());
+        d.draw_line_ex(
+            ball,
+            ball + velocity * DEBUG_VELOCITY_SCALE,
+            2.0,
+            Color::LIME,
+        );
+
+        if let Some((position, normal)) = self.last_contact {
+            d.draw_circle_v(position, 4.0, Color::ORANGE);
+            d.draw_line_ex(
+                position,
+                position + normal * DEBUG_NORMAL_LENGTH,
+                2.0,
+                Color::ORANGE,
+            );
+        }
+    }
+
+    fn draw(&self, ctx: &Context, d: &mut Canvas) {
+        let theme = ctx.theme();
+        let font = ctx.font();
+        let high_contrast = ctx.config.settings.visual.high_contrast;
+
+        d.clear_background(theme.background);
+        match &ctx.assets.background {
+            _ if high_contrast => (),
+            Some(texture) => draw_stretched(
+                d,
+                texture,
+                Rectangle {
+                    x: 0.0,
+                    y: 0.0,
+                    width: WINDOW_WIDTH,
+                    height: WINDOW_HEIGHT,
+                },
+                Color::WHITE,
+            ),
+            None => self.starfield.render(d, theme.stars),
+        }
+        {
+            let mut world = d.begin_mode2D(self.camera.camera());
+            let _mirror = self.challenge.mirror.then(Mirror::begin);
+            self.draw_world(ctx, &mut world);
+        }
+
+        Hud {
+            lives: self.lives,
+            score: self.stats.score,
+            level: self.level,
+            combo: self.stats.combo(),
+            timer: (self.mode == GameMode::TimeAttack || ctx.config.settings.speedrun_timer)
+                .then_some(self.stats.time_played),
+            speed: self.ball.speed / (self.tuning.ball_speed * SPEED_GAUGE_MAX),
+            hardcore: self.mode == GameMode::Hardcore,
+            session: (self.mode == GameMode::Zen).then_some(self.session),
+        }
+        .draw(d, &ctx.config.hud, theme, font, high_contrast);
+        self.powerups.render_active(d, font);
+        if ctx.config.settings.speedrun_timer && !self.versus && !self.mode.endless() {
+            self.splits.render(
+                d,
+                font,
+                &ctx.levels,
+                self.stats.time_played,
+                SPLITS_POSITION,
+            );
+        }
+
+        match self.state {
+            ST::Countdown(remaining) => {
+                draw_banner(d, font, &(remaining.ceil() as u32).to_string())
+            }
+            ST::Running if self.go_time > 0.0 => draw_banner(d, font, tr!("game.go")),
+            ST::LevelCleared(_) => draw_banner(d, font, tr!("game.level_cleared")),
+            ST::Bonus(left) if left > BONUS_TIME - BONUS_BANNER_TIME => {
+                draw_banner(d, font, tr!("game.bonus"))
+            }
+            ST::Winning => draw_banner(d, font, tr!("game.won")),
+            ST::GameOver if self.beaten => draw_banner(d, font, tr!("game.too_slow")),
+            ST::GameOver => draw_banner(d, font, tr!("game.over")),
+            _ => (),
+        }
+        if let (&ST::Bonus(left), Some(bonus)) = (&self.state, &self.bonus) {
+            draw_centered(
+                d,
+                font,
+                &tr!("game.bonus_status", left.ceil(), bonus.caught),
+                100,
+                30,
+                theme.text,
+            );
+        }
+        if let Some(hints) = &self.hints {
+            hints.render(d, font, WINDOW_HEIGHT as i32 / 2 + 60, theme.hint);
+        }
+        if self.versus {
+            let text = match ctx.peer.as_ref().and_then(|p| p.opponent()) {
+                Some(o) => tr!("game.opponent", group_thousands(o.score), o.bricks, o.lives),
+                None => tr!("game.opponent_lost").into(),
+            };
+            draw_centered(d, font, &text, WINDOW_HEIGHT as i32 - 40, 20, theme.hint);
+        } else if let Some(practice) = &self.practice {
+            let text = tr!(
+                "practice.hint",
+                if practice.frozen {
+                    tr!("practice.unfreeze")
+                } else {
+                    tr!("practice.freeze")
+                }
+            );
+            draw_centered(d, font, &text, WINDOW_HEIGHT as i32 - 40, 20, theme.hint);
+        } else if let Some(chat) = &ctx.chat {
+            let mut text = tr!(
+                "game.chat_vote",
+                chat.channel(),
+                chat.round_left().as_secs()
+            );
+            for mutator in Mutator::ALL {
+                text += &format!("  {} {}", mutator.command(), chat.tally(mutator));
+            }
+            draw_centered(d, font, &text, WINDOW_HEIGHT as i32 - 40, 20, theme.hint);
+        }
+
+        if let ST::Winning | ST::GameOver = self.state {
+            draw_centered(
+                d,
+                font,
+                tr!("game.continue"),
+                (WINDOW_HEIGHT / 2.0) as i32 + 50,
+                if high_contrast { 30 } else { 20 },
+                theme.hint,
+            );
+        }
+        self.console.render(d);
+    }
+}
+
+impl Scene for Game {
+    fn update(&mut self, ctx: &mut Context, input: &Input, duration: &Duration) -> Transition {
+        if ctx.debug && input.pressed(Action::ToggleConsole) {
+            self.console.open = !self.console.open;
+        }
+        // The game is frozen while the console is open.
+        if self.console.open {
+            if let Some(line) = self.console.edit(input) {
+                let result = commands().execute(self, ctx, &line);
+                self.console.report(result);
+            }
+            return Transition::None;
+        }
+
+        let input = &self.challenge.map(input);
+        let real = duration;
+        self.session += *real;
+        // The game speed setting slows the whole run down, and can be changed
+        // from the pause menu, so the run remembers the slowest it was.
+        let speed = ctx.config.settings.game_speed;
+        if !self.finished {
+            self.slowest = self.slowest.min(speed);
+        }
+        let scaled = duration.mul_f32(self.camera.time_scale() * speed as f32 / 100.0);
+        // The ball takes its new size on the next step; the paddle changes
+        // straight away.
+        let oversize = ctx.config.settings.visual.oversize();
+        if oversize != self.oversize {
+            self.oversize = oversize;
+            self.racket.resize(self.racket_width());
+        }
+        // Debug builds reload pong.toml and the level files when they change.
+        // The tuning takes effect straight away, and the level being played
+        // starts over if it was edited.
+        self.tuning = ctx.config.tuning;
+        let level = &ctx.levels[self.level];
+        if level.revision != self.level_revision && !self.finished {
+            self.bonus = None;
+            self.load_level(level);
+        }
+        let duration = &scaled;
+
+        // The pause after a level runs on real time, so the slow motion zoom
+        // on the last brick doesn't drag it out.
+        if let ST::LevelCleared(left) = self.state {
+            let left = left.saturating_sub(*real);
+            if left.is_zero() {
+                if self.bonus_due() {
+                    self.start_bonus();
+                } else {
+                    self.next_level(&ctx.levels);
+                }
+            } else {
+                self.set_state(ST::LevelCleared(left));
+            }
+        }
+
+        let transition = self.handle_input(ctx, input);
+        // The controls are shown once a session, at the first serve, and
+        // fade once the ball is in play.
+        if !ctx.hints_shown && matches!(self.state, ST::Countdown(_)) {
+            ctx.hints_shown = true;
+            self.hints = Some(Hints::new(self.control_hints(ctx)));
+        }
+        if let Some(hints) = &mut self.hints {
+            if !matches!(self.state, ST::Countdown(_)) {
+                hints.dismiss();
+            }
+            hints.update(input, real.as_secs_f32());
+            if hints.finished() {
+                self.hints = None;
+            }
+        }
+        if self.practice.is_some() {
+            self.practice_tools(ctx, input);
+        }
+        let was_running = matches!(self.state, ST::Running);
+        // Chat only gets a say in the player's own runs, and not while the
+        // ball is waiting to be served.
+        if let (Some(chat), false, true) = (&mut ctx.chat, self.versus, was_running) {
+            if let Some(mutator) = chat.update(duration) {
+                self.events.emit(GameEvent::ChatVote { mutator });
+            }
+        }
+        match &self.practice {
+            Some(practice) if practice.frozen => {
+                if input.pressed(Action::PracticeStep) {
+                    self.calculate_physics(ctx, &practice::STEP);
+                }
+            }
+            _ => self.calculate_physics(ctx, duration),
+        }
+        self.update_scripts(ctx, duration);
+        ctx.sounds
+            .extend(self.events.iter().filter_map(|event| self.cue(event)));
+        self.react(ctx);
+        self.particles.update(duration);
+        self.popups.update(duration);
+        self.go_time = (self.go_time - duration.as_secs_f32()).max(0.0);
+        self.bricks_slide.update(duration);
+        self.ball_fade.update(duration);
+        if ctx.config.settings.visual.animated_background() {
+            self.starfield.update(duration);
+        }
+        for brick in self.bricks.iter_mut() {
+            brick.hit = (brick.hit - duration.as_secs_f32()).max(0.0);
+        }
+        for bumper in self.bumpers.iter_mut() {
+            bumper.flash = (bumper.flash - duration.as_secs_f32()).max(0.0);
+        }
+        if self.racket.resized > 0.0 {
+            self.racket.resized -= duration.as_secs_f32();
+            if self.racket.resized <= 0.0 {
+                self.racket.resize(self.racket_width());
+                self.racket.resized = 0.0;
+            }
+        }
+        self.dying_bricks.retain(|b| {
+            b.age += duration.as_secs_f32();
+            b.age < BRICK_DYING_TIME
+        });
+        self.camera
+            .update(real, duration, ctx.config.settings.visual.shake());
+        if self.versus {
+            self.race(ctx);
+        }
+        if let Some(overlay) = &mut ctx.overlay {
+            overlay.publish(self.overlay_state(&ctx.levels));
+        }
+        if !self.versus && self.practice.is_none() {
+            ctx.achievements.check(&self.stats);
+        }
+        if was_running
+            && matches!(self.state, ST::LevelCleared(_) | ST::Winning)
+            && self.mode.ranked()
+        {
+            ctx.progress
+                .record(self.level, self.level_score, self.level_elapsed);
+            if let Some(recorder) = &mut self.ghost_recorder {
+                recorder.finish(
+                    self.level,
+                    &ctx.levels[self.level].name,
+                    &self.level_elapsed,
+                    self.ghost.as_ref().map(Ghost::time),
+                );
+            }
+        }
+        if matches!(self.state, ST::Winning | ST::GameOver) && !self.finished {
+            self.finished = true;
+            ctx.attention = matches!(self.state, ST::GameOver);
+            ctx.lifetime.record(self.mode, &self.stats);
+            // A race is one level, so it doesn't compete with full runs, and
+            // mutators change the rules too much to compare.
+            if !self.versus && self.mutators.is_none() {
+                if let ST::Winning = self.state {
+                    self.splits.finish(&ctx.levels);
+                }
+                self.rank = ctx.high_scores.rank(self.category(), self.stats.score);
+                // Mods can change the score, so modded runs stay local, and
+                // only the modes with high scores have a global board.
+                if self.stats.score > 0 && ctx.scripts.is_empty() && self.mode.ranked() {
+                    let run = Submission {
+                        name: ctx.config.settings.player_name.clone(),
+                        category: self.category(),
+                        score: self.stats.score,
+                        level: self.level,
+                        time: self.stats.time_played,
+                        // Picked at random or not, the run can be played
+                        // again from its seed.
+                        seed: self.seed,
+                        speed: self.slowest,
+                        trace: self.trace,
+                    };
+                    // A run that made the table goes once it has been named.
+                    if self.rank.is_some() {
+                        self.submission = Some(run);
+                    } else {
+                        ctx.leaderboard.submit(run);
+                    }
+                }
+            }
+        }
+        transition
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        self.draw(ctx, d);
+    }
+
+    // The ball is only heard while it is in play or waiting on the paddle.
+    fn ball_tone(&self, _ctx: &Context) -> Option<BallTone> {
+        matches!(self.state, ST::Running | ST::Countdown(_)).then(|| BallTone {
+            pan: self.ball.x / WINDOW_WIDTH * 2.0 - 1.0,
+            height: 1.0 - self.ball.y / WINDOW_HEIGHT,
+        })
+    }
+
+    // A finished run has been scored once it has been named, or if it didn't
+    // make the table.
+    fn loses_progress(&self) -> bool {
+        !matches!(self.state, ST::Winning | ST::GameOver) || self.rank.is_some()
+    }
+
+    fn music(&self, ctx: &Context) -> Option<Track> {
+        Some(if ctx.levels[self.level].boss {
+            Track::Boss
+        } else {
+            Track::Gameplay
+        })
+    }
+
+    fn title(&self, _ctx: &Context) -> Option<String> {
+        Some(tr!(
+            "game.title",
+            BASE_TITLE,
+            self.level + 1,
+            group_thousands(self.stats.score)
+        ))
+    }
+
+    fn activity(&self, ctx: &Context) -> Option<Activity> {
+        Some(Activity {
+            details: tr!(
+                "presence.playing",
+                if self.versus {
+                    tr!("presence.versus_race")
+                } else {
+                    self.mode.name()
+                },
+                self.level + 1,
+                ctx.levels[self.level].name
+            ),
+            state: tr!("presence.score", group_thousands(self.stats.score)),
+            elapsed: self.stats.time_played,
+        })
+    }
+}
+
+fn commands() -> Commands<Game> {
+    Commands::<Game>::new()
+        .register("setspeed", "setspeed <speed>", |game, _, args| {
+            let speed: f32 = arg(args, 0, "speed")?;
+            if !(speed.is_finite() && speed > 0.0) {
+                return Err("speed must be positive".into());
+            }
+            game.ball.speed = speed;
+            Ok(format!("ball speed {}", speed))
+        })
+        .register("loadlevel", "loadlevel <number>", |game, ctx, args| {
+            let number: usize = arg(args, 0, "level number")?;
+            if !(1..=ctx.levels.len()).contains(&number) {
+                return Err(format!("levels go from 1 to {}", ctx.levels.len()));
+            }
+            game.level = number - 1;
+            game.load_level(&ctx.levels[game.level]);
+            Ok(format!("loaded {}", ctx.levels[game.level].name))
+        })
+        .register(
+            "spawnbrick",
+            "spawnbrick <column> <row> [hp|type]",
+            |game, ctx, args| {
+                let column: usize = arg(args, 0, "column")?;
+                let row: usize = arg(args, 1, "row")?;
+                // Anything but a number is the symbol of a plugin's brick type.
+                let (hp, kind) = match args.get(2) {
+                    None => (1, None),
+                    Some(value) => match value.parse() {
+                        Ok(hp) => (hp, None),
+                        Err(_) => {
+                            let kind = ctx
+                                .registry
+                                .brick_types()
+                                .iter()
+                                .find(|t| t.symbol.to_string() == *value)
+                                .copied()
+                                .ok_or(format!("no plugin adds a '{}' brick", value))?;
+                            (kind.hp, Some(kind))
+                        }
+                    },
+                };
+                game.spawn_brick(column, row, hp, kind)
+            },
+        )
+        .register("lives", "lives <count>", |game, _, args| {
+            game.lives = arg(args, 0, "count")?;
+            Ok(format!("{} lives", game.lives))
+        })
+        .register("exportpng", "exportpng <file>", |game, ctx, args| {
+            let path: String = arg(args, 0, "file")?;
+            game.layout(ctx).export_image(Path::new(&path))?;
+            Ok(format!("exported to {}", path))
+        })
+        .register("save", "save <file>", |game, _, args| {
+            let path: String = arg(args, 0, "file")?;
+            fs::write(&path, game.snapshot()).map_err(|e| e.to_string())?;
+            Ok(format!("saved to {}", path))
+        })
+        .register("load", "load <file>", |game, ctx, args| {
+            let path: String = arg(args, 0, "file")?;
+            let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let restored = Game::restore(ctx, &text)?;
+            // Keep the console, and with it the log, open across the swap.
+            let console = std::mem::replace(&mut game.console, Console::new());
+            *game = restored;
+            game.console = console;
+            Ok(format!("loaded {}", path))
+        })
+        .include(|game| &mut game.powerups, PowerUps::commands())
+}
+
+// What mods can do. Unlike the console, nothing here touches files or
+// reaches outside the running game.
+fn script_api() -> Commands<Game> {
+    Commands::<Game>::new()
+        .register("addscore", "addscore <points>", |game, _, args| {
+            let points: i64 = arg(args, 0, "points")?;
+            let add = |score: u32| (score as i64 + points).clamp(0, u32::MAX as i64) as u32;
+            game.stats.score = add(game.stats.score);
+            game.level_score = add(game.level_score);
+            Ok(format!("score {}", game.stats.score))
+        })
+        .register(
+            "spawnbrick",
+            "spawnbrick <column> <row> [hp]",
+            |game, _, args| {
+                let column: usize = arg(args, 0, "column")?;
+                let row: usize = arg(args, 1, "row")?;
+                let hp: usize = if args.len() > 2 {
+                    arg(args, 2, "hp")?
+                } else {
+                    1
+                };
+                game.spawn_brick(column, row, hp, None)
+            },
+        )
+        .register("ballspeed", "ballspeed <factor>", |game, _, args| {
+            let factor: f32 = arg(args, 0, "factor")?;
+            if !(0.25..=4.0).contains(&factor) {
+                return Err("the factor goes from 0.25 to 4".into());
+            }
+            game.ball.speed *= factor;
+            Ok(format!("ball speed {}", game.ball.speed))
+        })
+        .register("addlife", "addlife [count]", |game, _, args| {
+            let count: usize = if args.is_empty() {
+                1
+            } else {
+                arg(args, 0, "count")?
+            };
+            game.lives = (game.lives + count).min(MAX_SCRIPT_LIVES);
+            Ok(format!("{} lives", game.lives))
+        })
+        .register("popup", "popup <text>", |game, ctx, args| {
+            game.popups.spawn(
+                format_args!("{}", args.join(" ")),
+                Vector2::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0),
+                POPUP_SIZE * 2,
+                ctx.theme().text,
+            );
+            Ok(String::new())
+        })
+}
+
+// The theme's colour for the brick's hit points, unless a plugin gave its
+// type a colour of its own.
+fn brick_colors(theme: &Theme, kind: Option<BrickType>, hp: usize) -> (Color, Color) {
+    match kind {
+        Some(kind) => {
+            let [r, g, b, a] = kind.color;
+            let color = Color::new(r, g, b, a);
+            (color, color.brightness(-0.35))
+        }
+        None => theme.brick(hp),
+    }
+}
+
+// Every wall a level adds to the arena, as segments: the deflectors across
+// the top corners, the lines through each wall's points and the outline of
+// each obstacle.
+fn arena_walls(level: &Level) -> Vec<physics::Segment> {
+    let segment = |pair: &[Vector2]| physics::Segment {
+        a: pair[0],
+        b: pair[1],
+    };
+    let size = level.corners;
+    let corners = [
+        [Vector2::new(0.0, size), Vector2::new(size, 0.0)],
+        [
+            Vector2::new(WINDOW_WIDTH - size, 0.0),
+            Vector2::new(WINDOW_WIDTH, size),
+        ],
+    ];
+    let corners = corners
+        .iter()
+        .filter(|_| size > 0.0)
+        .map(|pair| segment(pair));
+    let walls = level
+        .walls
+        .iter()
+        .flat_map(|points| points.windows(2).map(segment));
+    let obstacles = level.obstacles.iter().flat_map(|points| {
+        let closing = [points[points.len() - 1], points[0]];
+        points
+            .windows(2)
+            .map(segment)
+            .chain(std::iter::once(segment(&closing)))
+    });
+    corners.chain(walls).chain(obstacles).collect()
+}
+
+// The push of every wind area `position` is in, on the screen.
+fn wind_at(winds: &[Wind], arena: Arena, position: Vector2) -> Vector2 {
+    let position = arena.to_arena(position);
+    let force = winds
+        .iter()
+        .filter(|w| w.area.check_collision_point_rec(position))
+        .fold(Vector2::zero(), |total, w| total + w.force);
+    arena.turn(force)
+}
+
+// Hairline cracks running in from the top and bottom edges, one for every
+// hit taken. Where each one runs follows from `seed`, so a brick's cracks
+// stay put from frame to frame.
+fn draw_cracks(d: &mut impl RaylibDraw, rect: Rectangle, seed: Vector2, cracks: usize) {
+    let noise = |n: f32| {
+        ((seed.x * 12.9898 + seed.y * 78.233 + n * 37.719).sin() * 43_758.547)
+            .fract()
+            .abs()
+    };
+    let centre = Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    for i in 0..cracks {
+        let n = i as f32 * 8.0;
+        let edge = if noise(n + 1.0) < 0.5 {
+            rect.y
+        } else {
+            rect.y + rect.height
+        };
+        let mut point = Vector2::new(rect.x + noise(n) * rect.width, edge);
+        let mut heading = (centre - point).normalized();
+        for step in 0..CRACK_STEPS {
+            heading = heading.rotated((noise(n + 2.0 + step as f32) - 0.5) * 1.6);
+            let mut next = point + heading * (rect.height * 0.3);
+            next.x = next.x.clamp(rect.x, rect.x + rect.width);
+            next.y = next.y.clamp(rect.y, rect.y + rect.height);
+            d.draw_line_ex(point, next, 1.5, Color::BLACK.alpha(0.45));
+            point = next;
+        }
+    }
+}
+
+// A pip per hit point the brick started with along its bottom edge, filled
+// for the ones it has left.
+fn draw_hp_pips(d: &mut impl RaylibDraw, rect: Rectangle, live: usize, hp: usize, color: Color) {
+    let width = (hp - 1) as f32 * HP_PIP_SPACING;
+    let y = rect.y + rect.height - HP_PIP_RADIUS * 2.0;
+    for i in 0..hp {
+        let centre = Vector2::new(
+            rect.x + (rect.width - width) / 2.0 + i as f32 * HP_PIP_SPACING,
+            y,
+        );
+        if i < live {
+            d.draw_circle_v(centre, HP_PIP_RADIUS, color);
+        } else {
+            d.draw_ring(
+                centre,
+                HP_PIP_RADIUS - 1.0,
+                HP_PIP_RADIUS,
+                0.0,
+                360.0,
+                12,
+                color.alpha(0.6),
+            );
+        }
+    }
+}
+
+// A brick texture is tinted with the brick's top colour.
+fn draw_brick(
+    d: &mut impl RaylibDraw,
+    assets: Option<&Assets>,
+    (top, bottom): (Color, Color),
+    rect: Rectangle,
+    alpha: f32,
+) {
+    match assets.and_then(|a| a.brick.as_ref()) {
+        Some(texture) => draw_stretched(d, texture, rect, top.alpha(alpha)),
+        None => d.draw_rectangle_gradient_v(
+            rect.x as i32,
+            rect.y as i32,
+            rect.width as i32,
+            rect.height as i32,
+            top.alpha(alpha),
+            bottom.alpha(alpha),
+        ),
+    }
+}
+
+// Takes a snapshot written by an older version of the game up to the current
+// layout, one version at a time, or refuses one from a newer version. A
+// change to `snapshot` that older files wouldn't restore under bumps
+// SNAPSHOT_VERSION and adds its step here, and a file of the old layout goes
+// in `snapshots/v<old version>/` for the tests to keep loading.
+fn upgrade_snapshot(table: &Table) -> Result<(), String> {
+    let version = table.get_int("snapshot.version").unwrap_or(0);
+    if version > SNAPSHOT_VERSION {
+        return Err(format!(
+            "snapshot is in format {}, from a newer version of the game",
+            version
+        ));
+    }
+    for from in version..SNAPSHOT_VERSION {
+        match from {
+            // Snapshots from before there were versions have the same layout.
+            0 => (),
+            _ => unreachable!("no upgrade from snapshot format {}", from),
+        }
+    }
+    Ok(())
+}
+
+fn point(position: Vector2) -> Rectangle {
+    Rectangle {
+        x: position.x,
+        y: position.y,
+        width: 0.0,
+        height: 0.0,
+    }
+}
+
+// A ghost's paddle and ball, drawn where the game draws the real ones.
+pub fn draw_ghost(
+    d: &mut impl RaylibDraw,
+    theme: &Theme,
+    ghost: &GhostFrame,
+    radius: f32,
+    alpha: f32,
+) {
+    d.draw_rectangle(
+        ghost.racket_x as i32,
+        RACKET_POS_Y as i32,
+        ghost.racket_width as i32,
+        RACKET_HEIGHT as i32,
+        theme.paddle.alpha(alpha),
+    );
+    d.draw_circle_v(ghost.ball, radius, theme.ball.alpha(alpha));
+}
+
+pub fn format_time(duration: &Duration) -> String {
+    let secs = duration.as_secs_f32();
+    format!("{}:{:05.2}", (secs / 60.0) as u32, secs % 60.0)
+}
+
+#[derive(Debug)]
+enum State {
+    Running,
+    // Seconds left before the ball is served.
+    Countdown(f32),
+    // Time left before the next level starts.
+    LevelCleared(Duration),
+    // Seconds left of a bonus stage.
+    Bonus(f32),
+    Winning,
+    GameOver,
+}
+
+use State as ST;
+
+#[cfg(test)]
+mod tests;
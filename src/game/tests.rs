@@ -0,0 +1,650 @@
+// Whole games played without a window: a pilot stands in for the player,
+// the clock moves in fixed steps, and the tests look at how the run ended.
+
+use super::*;
+use crate::achievements::Achievements;
+use crate::config::Config;
+use crate::golden;
+use crate::highscores::HighScores;
+use crate::leaderboard::Leaderboard;
+use crate::level::MAX_COLUMNS;
+use crate::paths;
+use crate::powerups::PowerUp;
+use crate::progress::Progress;
+use crate::property::{self, below, close, ensure};
+use crate::stats::LifetimeStats;
+use crate::theme;
+use pong::plugin::{BrickType, Registry};
+
+const STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+// Long enough for any of these games, so a stuck one fails rather than
+// hangs.
+const TIME_LIMIT: f32 = 300.0;
+
+// A level with one column, so its bricks span the screen and the ball can't
+// get past them. Each line of `rows` is one brick of that many hit points.
+fn level(name: &str, rows: &str) -> Level {
+    let text = format!(
+        "[level]\nname = \"{}\"\ncolumns = 1\n\n[bricks]\n{}\n",
+        name, rows
+    );
+    Level::parse(&text, &[]).unwrap()
+}
+
+fn context(mode: GameMode, levels: Vec<Level>) -> Context {
+    let mut config = Config::load_from(&paths::config_dir().join("test.toml")).unwrap();
+    config.settings.input_mode = InputMode::Mouse;
+    Context {
+        mode,
+        challenge: Challenge::default(),
+        mutators: Mutators::default(),
+        progress: Progress::load(levels.len()),
+        high_scores: HighScores::load(),
+        achievements: Achievements::load(),
+        leaderboard: Leaderboard::new(&config.leaderboard),
+        lifetime: LifetimeStats::load(),
+        config,
+        assets: Assets::none(),
+        themes: theme::load_all(),
+        high_contrast_theme: Theme::high_contrast(),
+        debug: false,
+        debug_overlay: false,
+        seed: Some(1),
+        attention: false,
+        hints_shown: false,
+        sounds: Vec::new(),
+        peer: None,
+        chat: None,
+        scripts: Vec::new(),
+        registry: Registry::default(),
+        overlay: None,
+        levels,
+    }
+}
+
+// Keeps the paddle under the ball.
+fn follow(game: &Game) -> Input {
+    aim(game.ball.x)
+}
+
+// Keeps the paddle on the other half of the screen from the ball.
+fn dodge(game: &Game) -> Input {
+    let x = if game.ball.x < WINDOW_WIDTH / 2.0 {
+        WINDOW_WIDTH
+    } else {
+        0.0
+    };
+    aim(x)
+}
+
+// The mouse over `x`, which the paddle centres itself on.
+fn aim(x: f32) -> Input {
+    let mut input = Input::default();
+    input.mouse = Vector2::new(x, RACKET_POS_Y);
+    input
+}
+
+// Steps the game with the pilot's input until `done` says so. Returns false
+// if the time ran out first.
+fn play(
+    game: &mut Game,
+    ctx: &mut Context,
+    pilot: fn(&Game) -> Input,
+    done: impl Fn(&Game) -> bool,
+) -> bool {
+    let mut elapsed = 0.0;
+    while !done(game) {
+        if elapsed > TIME_LIMIT {
+            return false;
+        }
+        let input = pilot(game);
+        game.update(ctx, &input, &STEP);
+        elapsed += STEP.as_secs_f32();
+    }
+    true
+}
+
+#[test]
+fn serves_when_the_countdown_runs_out() {
+    let mut ctx = context(GameMode::Classic, vec![level("Serve", "1")]);
+    let mut game = Game::new(&ctx, 0);
+    assert!(matches!(game.state, ST::Countdown(_)));
+
+    let mut steps = 0;
+    while matches!(game.state, ST::Countdown(_)) {
+        game.update(&mut ctx, &follow(&game), &STEP);
+        steps += 1;
+    }
+    assert!(matches!(game.state, ST::Running));
+    let waited = steps as f32 * STEP.as_secs_f32();
+    assert!(
+        (waited - COUNTDOWN).abs() < 0.05,
+        "served after {}s",
+        waited
+    );
+
+    let start = game.ball.y;
+    game.update(&mut ctx, &follow(&game), &STEP);
+    assert!(game.ball.y < start, "the ball should be on its way up");
+}
+
+#[test]
+fn a_click_serves_early() {
+    let mut ctx = context(GameMode::Classic, vec![level("Serve", "1")]);
+    let mut game = Game::new(&ctx, 0);
+    let mut click = follow(&game);
+    click.mouse_clicked = true;
+    game.update(&mut ctx, &click, &STEP);
+    assert!(matches!(game.state, ST::Running));
+}
+
+#[test]
+fn clearing_a_level_moves_on_to_the_next() {
+    let mut ctx = context(
+        GameMode::Classic,
+        vec![level("First", "2"), level("Second", "1")],
+    );
+    let mut game = Game::new(&ctx, 0);
+    assert!(play(&mut game, &mut ctx, follow, |game| {
+        matches!(game.state, ST::LevelCleared(_))
+    }));
+    assert!(game.bricks.is_empty());
+    assert_eq!(game.stats.levels_cleared, 1);
+
+    assert!(play(&mut game, &mut ctx, follow, |game| {
+        matches!(game.state, ST::Countdown(_))
+    }));
+    assert_eq!(game.level, 1);
+    assert_eq!(game.bricks.len(), 1);
+    assert!(!game.finished);
+}
+
+#[test]
+fn clearing_the_last_level_wins() {
+    let mut ctx = context(
+        GameMode::Classic,
+        vec![level("First", "1"), level("Last", "1\n1")],
+    );
+    let mut game = Game::new(&ctx, 0);
+    assert!(play(&mut game, &mut ctx, follow, |game| game.finished));
+    assert!(matches!(game.state, ST::Winning));
+    assert_eq!(game.level, 1);
+    assert_eq!(game.stats.levels_cleared, 2);
+    assert_eq!(game.stats.bricks_destroyed, 3);
+    assert_eq!(game.stats.balls_lost, 0);
+    assert!(game.stats.score > 0);
+}
+
+#[test]
+fn losing_every_ball_ends_the_run() {
+    let mut ctx = context(GameMode::Classic, vec![level("Wall", "5\n5")]);
+    let mut game = Game::new(&ctx, 0);
+    let balls = game.lives + 1;
+    assert!(play(&mut game, &mut ctx, dodge, |game| game.finished));
+    assert!(matches!(game.state, ST::GameOver));
+    assert_eq!(game.lives, 0);
+    assert_eq!(game.stats.balls_lost as usize, balls);
+    assert_eq!(game.bricks.len(), 2);
+}
+
+// The ball breaking the last brick and then falling past the paddle is
+// still a win.
+#[test]
+fn the_last_brick_counts_with_the_last_ball() {
+    let mut ctx = context(GameMode::Hardcore, vec![level("Only", "1")]);
+    let mut game = Game::new(&ctx, 0);
+    assert_eq!(game.lives, 0);
+    assert!(play(&mut game, &mut ctx, dodge, |game| game.finished));
+    assert!(matches!(game.state, ST::Winning));
+    assert_eq!(game.stats.balls_lost, 0);
+
+    // Nothing more happens once the run is over.
+    for _ in 0..600 {
+        game.update(&mut ctx, &dodge(&game), &STEP);
+    }
+    assert!(matches!(game.state, ST::Winning));
+    assert_eq!(game.stats.balls_lost, 0);
+}
+
+#[test]
+fn a_power_up_vote_drops_a_capsule() {
+    let ctx = context(GameMode::Classic, vec![level("Voted", "1")]);
+    let mut game = Game::new(&ctx, 0);
+    game.events.emit(GameEvent::ChatVote {
+        mutator: Mutator::PowerUp,
+    });
+    game.react(&ctx);
+    assert!(game.snapshot().contains("[capsule1]"));
+}
+
+// A ball passing just beside a brick hits it only if it is big enough to.
+#[test]
+fn a_mega_ball_breaks_a_brick_a_micro_ball_misses() {
+    let shave = |kind| {
+        let mut ctx = context(GameMode::Classic, vec![level("Beside", "1")]);
+        ctx.levels[0].columns = 3;
+        let mut game = Game::new(&ctx, 0);
+        game.state = ST::Running;
+        game.powerups.activate(kind);
+        let brick = &game.bricks[0];
+        let (width, height) = game.brick_size;
+        game.ball.x = brick.x + width + PROJ_RADIUS;
+        game.ball.y = brick.y + height / 2.0;
+        game.ball.direction = Vector2::new(0.0, -1.0);
+        game.ball.speed = 1.0;
+        game.update(&mut ctx, &follow(&game), &STEP);
+        game.bricks.is_empty()
+    };
+    assert!(shave(PowerUp::Mega));
+    assert!(!shave(PowerUp::Micro));
+}
+
+#[test]
+fn a_brick_crumbles_in_the_colour_it_had() {
+    let mut ctx = context(GameMode::Classic, vec![level("Heavy", "3\n3")]);
+    let mut game = Game::new(&ctx, 0);
+    game.powerups.activate(PowerUp::Heavy);
+    assert!(play(&mut game, &mut ctx, follow, |game| game.bricks.len() < 2));
+    assert_eq!(
+        game.dying_bricks.iter().map(|b| b.hp).collect::<Vec<_>>(),
+        [3]
+    );
+}
+
+// Properties of whole games: random levels, rules and players, checked after
+// every step.
+
+// Ten seconds of play, the first three of them the countdown.
+const PROPERTY_STEPS: usize = 600;
+
+// Up to eight rows of random bricks, with gaps.
+fn random_level(rng: &mut Rng) -> Level {
+    let columns = 1 + below(rng, MAX_COLUMNS);
+    let mut grid = String::new();
+    for _ in 0..1 + below(rng, 8) {
+        for _ in 0..columns {
+            grid.push(match below(rng, MAX_BRICK_HP + 2) {
+                0 => '.',
+                hp => char::from_digit(hp.min(MAX_BRICK_HP) as u32, 10).unwrap(),
+            });
+        }
+        grid.push('\n');
+    }
+    let text = format!(
+        "[level]\nname = \"Random\"\ncolumns = {}\n\n[bricks]\n{}",
+        columns, grid
+    );
+    Level::parse(&text, &[]).unwrap()
+}
+
+// A Zen game, which never ends, on a random level with random mutators.
+fn random_game(rng: &mut Rng) -> (Game, Context) {
+    let mut ctx = context(GameMode::Zen, vec![random_level(rng)]);
+    ctx.mutators = Mutators::from_bits(rng.next_u64() as u8);
+    ctx.seed = Some(rng.next_u64());
+    (Game::new(&ctx, 0), ctx)
+}
+
+// The ball's collision box, in the frame the bricks are in.
+fn ball_box(game: &Game) -> Rectangle {
+    let centre = game
+        .arena()
+        .to_arena(Vector2::new(game.ball.x, game.ball.y));
+    let radius = game.ball.radius;
+    Rectangle {
+        x: centre.x - radius,
+        y: centre.y - radius,
+        width: 2.0 * radius,
+        height: 2.0 * radius,
+    }
+}
+
+#[test]
+fn the_paddle_stays_on_screen() {
+    property::check("the_paddle_stays_on_screen", |rng| {
+        let (mut game, mut ctx) = random_game(rng);
+        if rng.next_f32() < 0.5 {
+            ctx.config.settings.input_mode = InputMode::Keyboard;
+        }
+        let band = game.mutators.paddle_band(PADDLE_BAND);
+        let actions = [
+            Action::Left,
+            Action::Right,
+            Action::Up,
+            Action::Down,
+            Action::TiltLeft,
+            Action::TiltRight,
+        ];
+        for step in 0..PROPERTY_STEPS {
+            // Anything held, and the mouse anywhere, even off the window.
+            let mut input = aim(rng.range(-WINDOW_WIDTH, WINDOW_WIDTH * 2.0));
+            input.mouse.y = rng.range(-WINDOW_HEIGHT, WINDOW_HEIGHT * 2.0);
+            input.mouse_clicked = rng.next_f32() < 0.05;
+            for action in actions {
+                if rng.next_f32() < 0.3 {
+                    input.hold(action);
+                }
+            }
+            game.update(&mut ctx, &input, &STEP);
+            let racket = &game.racket;
+            ensure!(
+                racket.x >= 0.0 && racket.x + racket.width <= WINDOW_WIDTH + 1e-3,
+                "step {}: the paddle is at x {} and {} wide",
+                step,
+                racket.x,
+                racket.width
+            );
+            ensure!(
+                racket.y >= RACKET_POS_Y - band && racket.y <= RACKET_POS_Y,
+                "step {}: the paddle is at y {}",
+                step,
+                racket.y
+            );
+        }
+        Ok(())
+    });
+}
+
+// Every bounce adds the configured gain to the speed and nothing else
+// changes it. The gains are picked so any sum of them can be told apart:
+// whole threes for walls and the paddle, a quarter for the one brick a step
+// can hit. The direction keeps its length throughout.
+#[test]
+fn bounces_keep_the_speed_plus_the_gain() {
+    const BOUNCE: f32 = 3.0;
+    const BRICK: f32 = 0.25;
+    property::check("bounces_keep_the_speed_plus_the_gain", |rng| {
+        let (mut game, mut ctx) = random_game(rng);
+        let constant = rng.next_f32() < 0.25;
+        ctx.config.settings.constant_ball_speed = constant;
+        // The game takes the tuning from the config on every update.
+        ctx.config.tuning.bounce_speedup = BOUNCE;
+        ctx.config.tuning.brick_speedup = BRICK;
+        let gain = !constant && !game.mutators.no_speed_ups;
+        for step in 0..PROPERTY_STEPS {
+            let before = (game.ball.speed, game.stats.balls_lost);
+            let running = matches!(game.state, ST::Running);
+            let offset = rng.range(-40.0, 40.0);
+            game.update(&mut ctx, &aim(game.ball.x + offset), &STEP);
+            ensure!(
+                close(game.ball.direction.length(), 2.0f32.sqrt()),
+                "step {}: the direction {:?} changed length",
+                step,
+                game.ball.direction
+            );
+            // A new ball starts at the serve speed.
+            if !running || game.stats.balls_lost != before.1 {
+                continue;
+            }
+            let change = game.ball.speed - before.0;
+            let bounces = (change / BOUNCE).floor();
+            let rest = change - bounces * BOUNCE;
+            ensure!(
+                bounces >= 0.0 && (rest == 0.0 || rest == BRICK),
+                "step {}: the speed went from {} to {}",
+                step,
+                before.0,
+                game.ball.speed
+            );
+            ensure!(
+                gain || change == 0.0,
+                "step {}: the speed went from {} to {} with the speed-ups off",
+                step,
+                before.0,
+                game.ball.speed
+            );
+        }
+        Ok(())
+    });
+}
+
+// A brick that was hit and is still standing has let go of the ball by the
+// end of the step. The ball's box is bigger than the gaps between bricks, so
+// in one of those it can still end up overlapping a neighbour.
+#[test]
+fn a_brick_left_standing_lets_go_of_the_ball() {
+    property::check("a_brick_left_standing_lets_go_of_the_ball", |rng| {
+        let (mut game, mut ctx) = random_game(rng);
+        let (width, height) = game.brick_size;
+        for step in 0..PROPERTY_STEPS {
+            let before: Vec<_> = game.bricks.iter().map(|b| (b.x, b.y, b.live)).collect();
+            let offset = rng.range(-40.0, 40.0);
+            game.update(&mut ctx, &aim(game.ball.x + offset), &STEP);
+            let ball = ball_box(&game);
+            for brick in &game.bricks {
+                let hit = before
+                    .iter()
+                    .any(|&(x, y, live)| x == brick.x && y == brick.y && live > brick.live);
+                if !hit {
+                    continue;
+                }
+                let bounds = Rectangle {
+                    x: brick.x,
+                    y: brick.y,
+                    width,
+                    height,
+                };
+                let overlap = physics::overlap(ball, bounds);
+                ensure!(
+                    overlap.width.min(overlap.height) <= 1e-3,
+                    "step {}: the ball's box {:?} is still in the brick it hit at {:?}",
+                    step,
+                    ball,
+                    bounds
+                );
+            }
+        }
+        Ok(())
+    });
+}
+
+// Save files, checked against the copies kept in `snapshots/`.
+
+fn saved_context() -> Context {
+    context(GameMode::Classic, vec![level("Saved", "3\n2\n1")])
+}
+
+// A game part way through the level, set up by hand rather than played so
+// the numbers don't depend on the platform's maths library.
+fn game_in_play(ctx: &Context) -> Game {
+    let mut game = Game::new(ctx, 0);
+    game.state = ST::Running;
+    game.lives = 2;
+    game.level_score = 150;
+    game.level_elapsed = Duration::from_millis(12_345);
+    game.stats.score = 150;
+    game.stats.bricks_destroyed = 1;
+    game.ball.x = 310.5;
+    game.ball.y = 420.25;
+    game.ball.speed = 9.5;
+    game.ball.direction = Vector2::new(1.0, -1.0);
+    game.racket.x = 280.0;
+    game.racket.target = Some(300.0);
+    game.bricks[0].live = 1;
+    game.bricks[0].hit = 0.5;
+    game.bricks.pop();
+    game
+}
+
+#[test]
+fn a_new_game_saves_as_before() {
+    let ctx = saved_context();
+    golden::check("new_game.snap", &Game::new(&ctx, 0).snapshot());
+}
+
+#[test]
+fn a_game_in_play_saves_as_before() {
+    let ctx = saved_context();
+    golden::check("game_in_play.snap", &game_in_play(&ctx).snapshot());
+}
+
+#[test]
+fn a_restored_game_saves_the_same() {
+    let ctx = saved_context();
+    let text = game_in_play(&ctx).snapshot();
+    let game = Game::restore(&ctx, &text).unwrap();
+    assert_eq!(game.snapshot(), text);
+}
+
+// What plugins added, the power-ups and the random numbers come back as they
+// were, rather than as a fresh game would have them.
+#[test]
+fn a_restored_game_keeps_brick_types_and_power_ups() {
+    let gold = BrickType {
+        symbol: 'g',
+        hp: 2,
+        points: 250,
+        color: [255, 215, 0, 255],
+    };
+    let text =
+        "[level]\nname = \"Typed\"\ncolumns = 2\n\n[bumper1]\nx = 640\ny = 360\n\n[bricks]\ng1\n";
+    let typed = || {
+        let mut ctx = context(
+            GameMode::Classic,
+            vec![Level::parse(text, &[gold]).unwrap()],
+        );
+        ctx.registry.brick_type(gold).unwrap();
+        ctx
+    };
+    let mut ctx = typed();
+    ctx.challenge = Challenge::ALL[3];
+    ctx.seed = Some(77);
+    let mut game = Game::new(&ctx, 0);
+    game.powerups.activate(PowerUp::Heavy);
+    game.powerups.activate(PowerUp::Mega);
+    game.bumpers[0].flash = 0.25;
+    let saved = game.snapshot();
+
+    let restored = Game::restore(&typed(), &saved).unwrap();
+    assert_eq!(restored.bricks[0].kind, Some(gold));
+    assert_eq!(restored.bricks[1].kind, None);
+    assert_eq!(restored.seed, 77);
+    assert_eq!(restored.challenge, Challenge::ALL[3]);
+    assert_eq!(restored.snapshot(), saved);
+
+    // Without the plugin the brick can't come back as it was.
+    let plain = context(GameMode::Classic, vec![level("Plain", "1")]);
+    assert!(Game::restore(&plain, &saved).is_err());
+}
+
+#[test]
+fn the_console_gives_power_ups_and_plugin_bricks() {
+    let mut ctx = context(GameMode::Classic, vec![level("Console", "1")]);
+    ctx.registry
+        .brick_type(BrickType {
+            symbol: 'g',
+            hp: 2,
+            points: 250,
+            color: [255, 215, 0, 255],
+        })
+        .unwrap();
+    let mut game = Game::new(&ctx, 0);
+    let console = commands();
+
+    console.execute(&mut game, &ctx, "give heavy").unwrap();
+    assert!(game.powerups.ball_damage(1) > 1);
+    assert!(console.execute(&mut game, &ctx, "give nothing").is_err());
+
+    console
+        .execute(&mut game, &ctx, "spawnbrick 0 2 g")
+        .unwrap();
+    let brick = game.bricks.last().unwrap();
+    assert_eq!((brick.live, brick.kind.map(|k| k.symbol)), (2, Some('g')));
+    assert!(console
+        .execute(&mut game, &ctx, "spawnbrick 0 2 h")
+        .is_err());
+}
+
+#[test]
+fn snapshots_from_before_versions_still_restore() {
+    let ctx = saved_context();
+    let old = golden::read("v0/game_in_play.snap");
+    assert!(!old.contains("[snapshot]"));
+    let game = Game::restore(&ctx, &old).unwrap();
+    assert_eq!(game.snapshot(), game_in_play(&ctx).snapshot());
+}
+
+#[test]
+fn snapshots_from_a_newer_version_are_refused() {
+    let ctx = saved_context();
+    let text = game_in_play(&ctx).snapshot().replace(
+        &format!("version = {}", SNAPSHOT_VERSION),
+        &format!("version = {}", SNAPSHOT_VERSION + 1),
+    );
+    assert!(Game::restore(&ctx, &text).is_err());
+}
+
+#[test]
+fn snapshots_with_negative_counts_are_refused() {
+    let ctx = saved_context();
+    let text = game_in_play(&ctx).snapshot();
+    for (from, to) in [("level = 0", "level = -1"), ("lives = 2", "lives = -2")] {
+        assert!(Game::restore(&ctx, &text.replacen(from, to, 1)).is_err());
+    }
+}
+
+// A two-paddle game keeps its bricks in the middle of the field, where the
+// classic layout a restore starts from doesn't have them.
+#[test]
+fn a_restored_two_paddle_game_keeps_its_layout() {
+    let ctx = context(GameMode::DualPaddle, vec![level("Dual", "1")]);
+    let game = Game::new(&ctx, 0);
+    let plain = context(GameMode::Classic, vec![level("Dual", "1")]);
+    let restored = Game::restore(&plain, &game.snapshot()).unwrap();
+    assert_eq!(restored.mode, GameMode::DualPaddle);
+    assert_eq!(restored.bricks_top, game.bricks_top);
+}
+
+// Levels made from images rather than text files.
+
+// What is left of a level, exported the way practice mode does it, loads
+// back as the same bricks.
+#[test]
+fn an_exported_layout_loads_back() {
+    let ctx = saved_context();
+    let game = game_in_play(&ctx);
+    let path = paths::data_subdir(practice::LAYOUTS_DIR).join("exported.png");
+    game.layout(&ctx).export_image(&path).unwrap();
+    let level = Level::from_image(&path).unwrap();
+    let cells = |bricks: &[BrickSpec]| {
+        bricks
+            .iter()
+            .map(|b| (b.column, b.row, b.hp))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(cells(&level.bricks), cells(&game.layout(&ctx).bricks));
+    assert_eq!(level.bricks.len(), 2);
+}
+
+// What a debug build does when a level's file or pong.toml changes.
+
+#[test]
+fn an_edited_level_starts_over() {
+    let mut ctx = context(GameMode::Classic, vec![level("Edited", "1\n1")]);
+    let mut game = Game::new(&ctx, 0);
+    assert!(play(&mut game, &mut ctx, follow, |game| game.bricks.len() == 1));
+    ctx.levels[0] = Level {
+        revision: 1,
+        ..level("Edited", "1\n1\n1")
+    };
+    game.update(&mut ctx, &follow(&game), &STEP);
+    assert!(matches!(game.state, ST::Countdown(_)));
+    assert_eq!(game.bricks.len(), 3);
+    assert_eq!(game.level_score, 0);
+}
+
+#[test]
+fn edited_tuning_applies_straight_away() {
+    let mut ctx = context(GameMode::Classic, vec![level("Tuned", "5")]);
+    let mut game = Game::new(&ctx, 0);
+    let mut click = follow(&game);
+    click.mouse_clicked = true;
+    game.update(&mut ctx, &click, &STEP);
+    let serve = game.ball.speed;
+    ctx.config.tuning.bounce_speedup = 100.0;
+    ctx.config.tuning.brick_speedup = 0.0;
+    assert!(play(&mut game, &mut ctx, follow, |game| {
+        game.ball.speed != serve
+    }));
+    assert!(game.ball.speed >= serve + 100.0, "{}", game.ball.speed);
+}
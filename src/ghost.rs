@@ -0,0 +1,200 @@
+// The fastest time attack clear of each level, kept as the paddle and ball
+// positions over the level's running time and drawn faintly behind later
+// attempts so the player can race their best. The game isn't deterministic,
+// so the ghost is a recording rather than a second simulation.
+use crate::paths;
+use raylib::prelude::Vector2;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const GHOSTS_DIR: &str = "ghosts";
+// Ghost files start with this and the version of their layout. Files from
+// before there were versions start straight with the level's name.
+const HEADER: &str = "pong ghost";
+const VERSION: u32 = 1;
+// Positions in between samples are interpolated.
+const SAMPLE_INTERVAL: f32 = 1.0 / 30.0;
+
+#[derive(Clone, Copy)]
+struct Sample {
+    time: f32,
+    racket_x: f32,
+    racket_width: f32,
+    ball: Vector2,
+}
+
+// Where the ghost's paddle and ball are at a point in time.
+pub struct GhostFrame {
+    pub racket_x: f32,
+    pub racket_width: f32,
+    pub ball: Vector2,
+}
+
+pub struct Ghost {
+    samples: Vec<Sample>,
+}
+
+fn path(level: usize) -> PathBuf {
+    paths::data_dir()
+        .join(GHOSTS_DIR)
+        .join(format!("level{}.ghost", level + 1))
+}
+
+impl Ghost {
+    // Ghost files name the level after the header, so a ghost stops counting
+    // when the level list changes under it.
+    pub fn load(level: usize, name: &str) -> Option<Self> {
+        let text = fs::read_to_string(path(level)).ok()?;
+        let (version, file_name, samples) = parse(&text)?;
+        if version > VERSION {
+            log::warn!(
+                "ignoring the ghost of level {}, which is in format {} from a newer version of the game",
+                level + 1,
+                version
+            );
+            return None;
+        }
+        if file_name != name {
+            return None;
+        }
+        (!samples.is_empty()).then_some(Self { samples })
+    }
+
+    // Any ghost file, with the name of the level it was recorded on, for
+    // `--replay` to play back.
+    pub fn open(path: &Path) -> Result<(String, Self), String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let (version, name, samples) = parse(&text).ok_or("it is not a ghost file")?;
+        if version > VERSION {
+            return Err(format!(
+                "it is in format {} from a newer version of the game",
+                version
+            ));
+        }
+        if samples.is_empty() {
+            return Err("nothing is recorded in it".into());
+        }
+        Ok((name.to_string(), Self { samples }))
+    }
+
+    // How long the run took.
+    pub fn time(&self) -> Duration {
+        Duration::from_secs_f32(self.samples.last().map_or(0.0, |s| s.time))
+    }
+
+    // None once the ghost has cleared the level.
+    pub fn at(&self, time: &Duration) -> Option<GhostFrame> {
+        let time = time.as_secs_f32();
+        let next = self.samples.iter().position(|s| s.time >= time)?;
+        let b = self.samples[next];
+        let a = self.samples[next.saturating_sub(1)];
+        let t = if b.time > a.time {
+            (time - a.time) / (b.time - a.time)
+        } else {
+            1.0
+        };
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Some(GhostFrame {
+            racket_x: lerp(a.racket_x, b.racket_x),
+            racket_width: lerp(a.racket_width, b.racket_width),
+            ball: Vector2::new(lerp(a.ball.x, b.ball.x), lerp(a.ball.y, b.ball.y)),
+        })
+    }
+}
+
+// The version, level name and samples of a ghost file.
+fn parse(text: &str) -> Option<(u32, &str, Vec<Sample>)> {
+    let mut lines = text.lines().peekable();
+    let version = match lines.peek()?.strip_prefix(HEADER) {
+        Some(version) => {
+            lines.next();
+            version.trim().parse().ok()?
+        }
+        None => 0,
+    };
+    let name = lines.next()?;
+    // The samples have had the same layout in every version so far. A change
+    // to them bumps VERSION and reads the older layout here, and a file of the
+    // old layout goes in `snapshots/v<old version>/` for the tests to keep
+    // loading.
+    let samples = lines
+        .filter_map(|line| {
+            let mut numbers = line.split_whitespace().map(|n| n.parse::<f32>().ok());
+            let mut next = || numbers.next().flatten();
+            Some(Sample {
+                time: next()?,
+                racket_x: next()?,
+                racket_width: next()?,
+                ball: Vector2::new(next()?, next()?),
+            })
+        })
+        .collect();
+    Some((version, name, samples))
+}
+
+// Records the current attempt at a level.
+pub struct GhostRecorder {
+    samples: Vec<Sample>,
+}
+
+impl GhostRecorder {
+    pub fn new() -> Self {
+        Self {
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, time: &Duration, racket_x: f32, racket_width: f32, ball: Vector2) {
+        let time = time.as_secs_f32();
+        if self
+            .samples
+            .last()
+            .is_some_and(|s| time - s.time < SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.samples.push(Sample {
+            time,
+            racket_x,
+            racket_width,
+            ball,
+        });
+    }
+
+    // Keeps the attempt as the level's ghost if it beat the old one, which
+    // took `best`.
+    pub fn finish(&mut self, level: usize, name: &str, time: &Duration, best: Option<Duration>) {
+        let samples = std::mem::take(&mut self.samples);
+        if best.is_some_and(|best| best <= *time) || samples.is_empty() {
+            return;
+        }
+        let mut text = format!("{} {}\n{}\n", HEADER, VERSION, name);
+        for s in &samples {
+            text += &format!(
+                "{:.3} {:.1} {:.1} {:.1} {:.1}\n",
+                s.time, s.racket_x, s.racket_width, s.ball.x, s.ball.y
+            );
+        }
+        // The last sample marks when the level was cleared.
+        if let Some(last) = samples.last() {
+            text += &format!(
+                "{:.3} {:.1} {:.1} {:.1} {:.1}\n",
+                time.as_secs_f32(),
+                last.racket_x,
+                last.racket_width,
+                last.ball.x,
+                last.ball.y
+            );
+        }
+        let path = path(level);
+        let result =
+            fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(&path, text));
+        if let Err(e) = result {
+            log::error!("could not write {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,78 @@
+// Ghost files, checked against the copies kept in `snapshots/`. Each test
+// uses a level of its own, as they share the data directory.
+use super::*;
+use crate::golden;
+
+const NAME: &str = "Ghostly";
+
+// Records a short run on `level` and keeps it as the level's ghost.
+fn record(level: usize) {
+    let mut recorder = GhostRecorder::new();
+    for i in 0..5 {
+        let i = i as f32;
+        recorder.record(
+            &Duration::from_millis(i as u64 * 40),
+            100.0 + i * 10.0,
+            80.0,
+            Vector2::new(200.0 + i * 5.0, 300.0 - i * 7.5),
+        );
+    }
+    recorder.finish(level, NAME, &Duration::from_millis(175), None);
+}
+
+// Where the ghost is at a few points along the run.
+fn frames(ghost: &Ghost) -> Vec<(f32, f32, f32, f32)> {
+    [0, 30, 90, 170]
+        .iter()
+        .filter_map(|&ms| ghost.at(&Duration::from_millis(ms)))
+        .map(|f| (f.racket_x, f.racket_width, f.ball.x, f.ball.y))
+        .collect()
+}
+
+#[test]
+fn a_recorded_ghost_saves_as_before() {
+    record(90);
+    golden::check("level.ghost", &fs::read_to_string(path(90)).unwrap());
+}
+
+#[test]
+fn a_recorded_ghost_loads_back() {
+    record(91);
+    let ghost = Ghost::load(91, NAME).unwrap();
+    assert_eq!(ghost.time(), Duration::from_secs_f32(0.175));
+    assert_eq!(frames(&ghost)[0], (100.0, 80.0, 200.0, 300.0));
+    assert!(Ghost::load(91, "Another level").is_none());
+}
+
+#[test]
+fn ghosts_from_before_versions_still_load() {
+    record(92);
+    let old = golden::read("v0/level.ghost");
+    assert!(!old.starts_with(HEADER));
+    fs::write(path(93), old).unwrap();
+    let new = Ghost::load(92, NAME).unwrap();
+    let old = Ghost::load(93, NAME).unwrap();
+    assert_eq!(old.time(), new.time());
+    assert_eq!(frames(&old), frames(&new));
+}
+
+#[test]
+fn ghosts_from_a_newer_version_are_ignored() {
+    record(94);
+    let text = fs::read_to_string(path(94)).unwrap().replace(
+        &format!("{} {}", HEADER, VERSION),
+        &format!("{} {}", HEADER, VERSION + 1),
+    );
+    fs::write(path(94), text).unwrap();
+    assert!(Ghost::load(94, NAME).is_none());
+}
+
+#[test]
+fn any_ghost_file_opens_for_playback() {
+    record(95);
+    let (name, ghost) = Ghost::open(&path(95)).unwrap();
+    assert_eq!(name, NAME);
+    assert_eq!(frames(&ghost), frames(&Ghost::load(95, NAME).unwrap()));
+    fs::write(path(96), "").unwrap();
+    assert!(Ghost::open(&path(96)).is_err());
+}
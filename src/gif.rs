@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+// Color indices have 8 bits, so LZW codes start at 9 bits and go up to 12.
+const MIN_CODE_SIZE: u8 = 8;
+const CLEAR: u16 = 256;
+const END: u16 = 257;
+const MAX_CODES: u16 = 4096;
+
+// Encodes an endlessly looping animated GIF. Every frame is `width` x `height`
+// indices into the shared 256 colour palette, shown for `delay` hundredths of
+// a second.
+pub fn encode(
+    width: u16,
+    height: u16,
+    palette: &[[u8; 3]; 256],
+    frames: &[Vec<u8>],
+    delay: u16,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"GIF89a");
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    // A global colour table of 2^(7 + 1) entries, 8 bits per channel.
+    out.extend_from_slice(&[0xF7, 0, 0]);
+    for color in palette {
+        out.extend_from_slice(color);
+    }
+    // NETSCAPE2.0 extension: loop forever.
+    out.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    out.extend_from_slice(b"NETSCAPE2.0");
+    out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        // Graphic control extension with the frame delay.
+        out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        out.extend_from_slice(&delay.to_le_bytes());
+        out.extend_from_slice(&[0x00, 0x00]);
+        // Image descriptor covering the whole canvas, no local colour table.
+        out.push(0x2C);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.push(0x00);
+        out.push(MIN_CODE_SIZE);
+        for block in compress(frame).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0x00);
+    }
+    out.push(0x3B);
+    out
+}
+
+// Packs variable width codes least significant bit first.
+struct Codes {
+    out: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+    size: u32,
+}
+
+impl Codes {
+    fn write(&mut self, code: u16) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += self.size;
+        while self.bits >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    // The decoder widens its codes once the table outgrows them, which it
+    // notices one code later than the encoder adds the entry.
+    fn write_and_grow(&mut self, code: u16, next: u16) {
+        self.write(code);
+        if next >= 1 << self.size && self.size < 12 {
+            self.size += 1;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn compress(indices: &[u8]) -> Vec<u8> {
+    let reset_size = MIN_CODE_SIZE as u32 + 1;
+    let mut codes = Codes {
+        out: Vec::new(),
+        buffer: 0,
+        bits: 0,
+        size: reset_size,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = END + 1;
+    codes.write(CLEAR);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        codes.write(END);
+        return codes.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        codes.write_and_grow(prefix, next);
+        if next < MAX_CODES {
+            table.insert((prefix, index), next);
+            next += 1;
+        } else {
+            codes.write(CLEAR);
+            codes.size = reset_size;
+            table.clear();
+            next = END + 1;
+        }
+        prefix = index as u16;
+    }
+    codes.write_and_grow(prefix, next);
+    codes.write_and_grow(END, next);
+    codes.finish()
+}
@@ -0,0 +1,63 @@
+// Golden files: output that has to stay the same from one version to the
+// next, such as the save and ghost formats, is compared against a copy kept
+// in `snapshots/`. After a deliberate change `PONG_UPDATE_SNAPSHOTS=1 cargo
+// test` writes the new output over the old, for the diff to be reviewed.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where the golden file `name` is kept.
+pub fn path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(name)
+}
+
+// The contents of the golden file `name`.
+pub fn read(name: &str) -> String {
+    let path = path(name);
+    match fs::read_to_string(&path) {
+        Ok(text) => text.replace("\r\n", "\n"),
+        Err(e) => panic!("could not read {}: {}", path.display(), e),
+    }
+}
+
+// Fails unless `actual` is what the golden file `name` says.
+pub fn check(name: &str, actual: &str) {
+    let path = path(name);
+    if env::var_os("PONG_UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "there is no {} yet; PONG_UPDATE_SNAPSHOTS=1 cargo test writes it",
+            path.display()
+        );
+    }
+    let expected = read(name);
+    if expected == actual {
+        return;
+    }
+    let mut diff = String::new();
+    let (expected, actual): (Vec<_>, Vec<_>) =
+        (expected.lines().collect(), actual.lines().collect());
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            diff += &format!("line {}:\n", i + 1);
+            if let Some(e) = e {
+                diff += &format!("- {}\n", e);
+            }
+            if let Some(a) = a {
+                diff += &format!("+ {}\n", a);
+            }
+        }
+    }
+    panic!(
+        "{} changed; PONG_UPDATE_SNAPSHOTS=1 cargo test takes the new output if that was meant\n{}",
+        path.display(),
+        diff
+    );
+}
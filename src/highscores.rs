@@ -0,0 +1,442 @@
+use crate::challenge::Category;
+use crate::config::{Table, Value, Writer};
+use crate::game::format_time;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::leaderboard::Submission;
+use crate::paths;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::{draw_centered, Align, Text};
+use crate::ui;
+use crate::WINDOW_WIDTH;
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const HIGH_SCORES_FILE: &str = "highscores.toml";
+const MAX_ENTRIES: usize = 10;
+// The longest name that fits in the table next to the score.
+const NAME_MAX_LENGTH: usize = 10;
+// What up and down step through for each letter, the way arcade cabinets
+// take names. A space at the end of the name is dropped.
+const LETTERS: &[u8] = b" ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-.";
+const SLOT_WIDTH: i32 = 48;
+const NAME_FONT_SIZE: i32 = 50;
+
+#[derive(Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    pub level: usize,
+    pub time: Duration,
+    // The slowest game speed the run was played at, in percent.
+    pub speed: u8,
+}
+
+// The best runs of every mode, best first, stored as `[<mode>.<rank>]` sections.
+pub struct HighScores {
+    path: PathBuf,
+    entries: HashMap<Category, Vec<HighScore>>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let path = paths::file(paths::data_dir(), HIGH_SCORES_FILE);
+        let table = fs::read_to_string(&path)
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let entries = Category::all()
+            .map(|category| {
+                let list = (1..=MAX_ENTRIES)
+                    .map_while(|rank| {
+                        let section = format!("{}.{}", category.key(), rank);
+                        Some(HighScore {
+                            // Tables from before names were asked for have
+                            // none.
+                            name: table
+                                .get_str(&format!("{}.name", section))
+                                .unwrap_or_default()
+                                .to_string(),
+                            score: table.get_int(&format!("{}.score", section))? as u32,
+                            level: table.get_int(&format!("{}.level", section))? as usize,
+                            time: Duration::from_millis(
+                                table.get_int(&format!("{}.time_ms", section))? as u64,
+                            ),
+                            speed: table
+                                .get_int(&format!("{}.speed", section))
+                                .map_or(100, |s| s.clamp(0, 100) as u8),
+                        })
+                    })
+                    .collect();
+                (category, list)
+            })
+            .collect();
+        Self { path, entries }
+    }
+
+    pub fn get(&self, category: Category) -> &[HighScore] {
+        self.entries.get(&category).map_or(&[], Vec::as_slice)
+    }
+
+    // The zero-based rank a run with `score` would get, if it makes the
+    // table at all.
+    pub fn rank(&self, category: Category, score: u32) -> Option<usize> {
+        let list = self.get(category);
+        let rank = list
+            .iter()
+            .position(|e| score > e.score)
+            .unwrap_or(list.len());
+        (rank < MAX_ENTRIES && score > 0).then_some(rank)
+    }
+
+    // Inserts the run if it makes the table and returns its zero-based rank.
+    pub fn submit(&mut self, category: Category, entry: HighScore) -> Option<usize> {
+        let rank = self.rank(category, entry.score)?;
+        let list = self.entries.entry(category).or_default();
+        list.insert(rank, entry);
+        list.truncate(MAX_ENTRIES);
+        self.save();
+        Some(rank)
+    }
+
+    fn save(&self) {
+        let mut writer = Writer::default();
+        for category in Category::all() {
+            for (i, entry) in self.get(category).iter().enumerate() {
+                writer
+                    .section(&format!("{}.{}", category.key(), i + 1))
+                    .value("name", Value::Str(entry.name.clone()))
+                    .value("score", Value::Int(entry.score as i64))
+                    .value("level", Value::Int(entry.level as i64))
+                    .value("time_ms", Value::Int(entry.time.as_millis() as i64))
+                    .value("speed", Value::Int(entry.speed as i64));
+            }
+        }
+        if let Err(e) = fs::write(&self.path, writer.finish()) {
+            log::error!("could not write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+pub struct HighScoresScreen {
+    categories: Vec<Category>,
+    selected: usize,
+    // The category whose global list was last asked for.
+    fetched: Option<Category>,
+}
+
+impl HighScoresScreen {
+    // Opens on `category`, or the first one if it keeps no scores.
+    pub fn new(category: Category) -> Self {
+        let categories: Vec<Category> = Category::all().collect();
+        Self {
+            selected: categories.iter().position(|c| *c == category).unwrap_or(0),
+            categories,
+            fetched: None,
+        }
+    }
+
+    fn category(&self) -> Category {
+        self.categories[self.selected]
+    }
+}
+
+impl Scene for HighScoresScreen {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) || input.pressed(Action::Confirm) {
+            return Transition::Pop;
+        }
+        let delta = input.pressed(Action::Right) as isize - input.pressed(Action::Left) as isize;
+        if delta != 0 {
+            self.selected = (self.selected as isize + delta)
+                .rem_euclid(self.categories.len() as isize) as usize;
+        }
+        if self.fetched != Some(self.category()) {
+            ctx.leaderboard.refresh(self.category());
+            self.fetched = Some(self.category());
+        }
+        ctx.leaderboard.update();
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        draw_centered(d, font, tr!("high_scores.title"), 60, 70, Color::YELLOW);
+        draw_centered(
+            d,
+            font,
+            &format!("< {} >", self.category().name()),
+            150,
+            30,
+            Color::WHITE,
+        );
+
+        // With a leaderboard the local list moves over to make room for the
+        // global one and leaves out the times.
+        let global = ctx.leaderboard.enabled();
+        let entries = ctx.high_scores.get(self.category());
+        let left = if global {
+            WINDOW_WIDTH as i32 / 2 - 560
+        } else {
+            WINDOW_WIDTH as i32 / 2 - 360
+        };
+        if global {
+            Text::new(tr!("high_scores.local"), 24, Color::GRAY).draw(d, font, left, 188);
+            if entries.is_empty() {
+                Text::new(tr!("high_scores.empty"), 30, Color::DARKGRAY).draw(d, font, left, 240);
+            }
+        } else if entries.is_empty() {
+            draw_centered(d, font, tr!("high_scores.empty"), 340, 30, Color::DARKGRAY);
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let y = 220 + i as i32 * 38;
+            let color = if i == 0 { Color::YELLOW } else { Color::WHITE };
+            Text::new(&format!("{:>2}.", i + 1), 30, color).draw(d, font, left, y);
+            Text::new(&flagged(entry.score, entry.speed), 30, color).draw(d, font, left + 80, y);
+            let name = if entry.name.is_empty() {
+                "-"
+            } else {
+                &entry.name
+            };
+            Text::new(name, 30, color).draw(d, font, left + 220, y);
+            Text::new(
+                &tr!("high_scores.level", entry.level + 1),
+                30,
+                Color::LIGHTGRAY,
+            )
+            .draw(d, font, left + 420, y);
+            if !global {
+                Text::new(&format_time(&entry.time), 30, Color::LIGHTGRAY).draw(
+                    d,
+                    font,
+                    left + 600,
+                    y,
+                );
+            }
+        }
+        if global {
+            self.render_global(ctx, d, WINDOW_WIDTH as i32 / 2 + 80);
+        }
+
+        let global_slowed = match ctx.leaderboard.top(self.category()) {
+            Some(Ok(entries)) if global => entries.iter().any(|e| e.speed < 100),
+            _ => false,
+        };
+        if global_slowed || entries.iter().any(|e| e.speed < 100) {
+            draw_centered(d, font, tr!("high_scores.slowed"), 616, 20, Color::GRAY);
+        }
+        draw_centered(d, font, tr!("high_scores.hint"), 650, 20, Color::GRAY);
+    }
+}
+
+// Scores played at less than full game speed get a star, explained at the
+// bottom of the screen.
+fn flagged(score: u32, speed: u8) -> String {
+    if speed < 100 {
+        format!("{}*", score)
+    } else {
+        score.to_string()
+    }
+}
+
+impl HighScoresScreen {
+    fn render_global(&self, ctx: &Context, d: &mut Canvas, left: i32) {
+        let font = ctx.font();
+        Text::new(tr!("high_scores.global"), 24, Color::GRAY).draw(d, font, left, 188);
+        let entries = match ctx.leaderboard.top(self.category()) {
+            None => {
+                Text::new(tr!("high_scores.loading"), 30, Color::DARKGRAY).draw(d, font, left, 240);
+                return;
+            }
+            Some(Err(_)) => {
+                Text::new(tr!("high_scores.offline"), 30, Color::DARKGRAY).draw(d, font, left, 240);
+                return;
+            }
+            Some(Ok(entries)) => entries,
+        };
+        if entries.is_empty() {
+            Text::new(tr!("high_scores.empty"), 30, Color::DARKGRAY).draw(d, font, left, 240);
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let y = 220 + i as i32 * 38;
+            let color = if i == 0 { Color::YELLOW } else { Color::WHITE };
+            Text::new(&format!("{:>2}.", i + 1), 30, color).draw(d, font, left, y);
+            Text::new(&flagged(entry.score, entry.speed), 30, color).draw(d, font, left + 80, y);
+            Text::new(&entry.name, 30, Color::LIGHTGRAY).draw(d, font, left + 240, y);
+        }
+    }
+}
+
+// Asks a run that made the table who played it before it is written down,
+// then carries on to `next`. The name can be typed, or picked a letter at a
+// time with up and down the way arcade cabinets do it, which also works from
+// a gamepad. It starts as the last name entered.
+pub struct NameEntry {
+    category: Category,
+    entry: HighScore,
+    name: Vec<char>,
+    cursor: usize,
+    next: Option<Box<dyn Scene>>,
+    // Sent to the online leaderboard under the name once it is entered.
+    submission: Option<Submission>,
+}
+
+impl NameEntry {
+    pub fn new(ctx: &Context, category: Category, entry: HighScore, next: Box<dyn Scene>) -> Self {
+        let name: Vec<char> = ctx
+            .config
+            .settings
+            .player_name
+            .chars()
+            .take(NAME_MAX_LENGTH)
+            .collect();
+        Self {
+            category,
+            entry,
+            cursor: name.len().min(NAME_MAX_LENGTH - 1),
+            name,
+            next: Some(next),
+            submission: None,
+        }
+    }
+
+    pub fn submission(mut self, submission: Option<Submission>) -> Self {
+        self.submission = submission;
+        self
+    }
+
+    // The last letter of a full name is as far as the cursor goes.
+    fn move_cursor(&mut self, delta: isize) {
+        let end = self.name.len().min(NAME_MAX_LENGTH - 1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(end);
+    }
+
+    fn write(&mut self, c: char) {
+        if self.cursor < self.name.len() {
+            self.name[self.cursor] = c;
+        } else {
+            self.name.push(c);
+        }
+        self.move_cursor(1);
+    }
+
+    // Steps the letter under the cursor up or down the arcade letters.
+    fn cycle(&mut self, delta: isize) {
+        let current = self.name.get(self.cursor).copied().unwrap_or(' ');
+        let index = LETTERS
+            .iter()
+            .position(|l| *l as char == current.to_ascii_uppercase())
+            .unwrap_or(0);
+        let index = (index as isize + delta).rem_euclid(LETTERS.len() as isize) as usize;
+        let cursor = self.cursor;
+        self.write(LETTERS[index] as char);
+        self.cursor = cursor;
+    }
+
+    fn erase(&mut self) {
+        // With the name full the cursor sits on the last letter rather than
+        // after it.
+        if self.cursor == NAME_MAX_LENGTH - 1 && self.name.len() == NAME_MAX_LENGTH {
+            self.name.pop();
+        } else if self.cursor > 0 {
+            self.cursor -= 1;
+            self.name.remove(self.cursor);
+        }
+    }
+
+    fn finish(&mut self, ctx: &mut Context) -> Transition {
+        let name: String = self.name.iter().collect();
+        let name = name.trim().to_string();
+        if ctx.config.settings.player_name != name {
+            ctx.config.settings.player_name = name.clone();
+            ctx.config.save();
+        }
+        if let Some(mut run) = self.submission.take() {
+            run.name = name.clone();
+            ctx.leaderboard.submit(run);
+        }
+        self.entry.name = name;
+        ctx.high_scores.submit(self.category, self.entry.clone());
+        match self.next.take() {
+            Some(next) => Transition::fade(Transition::Replace(next)),
+            None => Transition::Pop,
+        }
+    }
+}
+
+impl Scene for NameEntry {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        for c in input.typed.chars() {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '.' {
+                self.write(c);
+            }
+        }
+        if input.erase {
+            self.erase();
+        }
+        // Space is bound to Confirm too, but here it types a space, and
+        // backspace is bound to Back but erases. Escape keeps the name as it
+        // is, since the score is saved either way.
+        let confirm = input.pressed(Action::Confirm) && input.typed.is_empty();
+        if input.submit || confirm || (input.pressed(Action::Back) && !input.erase) {
+            return self.finish(ctx);
+        }
+        self.move_cursor(
+            input.pressed(Action::Right) as isize - input.pressed(Action::Left) as isize,
+        );
+        let delta = input.pressed(Action::Up) as isize - input.pressed(Action::Down) as isize;
+        if delta != 0 {
+            self.cycle(delta);
+        }
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("name_entry.title"), 120);
+        let rank = ctx.high_scores.rank(self.category, self.entry.score);
+        draw_centered(
+            d,
+            font,
+            &tr!(
+                "name_entry.rank",
+                rank.map_or(MAX_ENTRIES, |r| r + 1),
+                self.entry.score
+            ),
+            220,
+            30,
+            Color::WHITE,
+        );
+
+        // One slot a letter, so the cursor has somewhere to be past the end
+        // of the name.
+        let slots = (self.name.len() + 1).min(NAME_MAX_LENGTH) as i32;
+        let left = WINDOW_WIDTH as i32 / 2 - slots * SLOT_WIDTH / 2;
+        let y = 320;
+        for i in 0..slots as usize {
+            let x = left + i as i32 * SLOT_WIDTH;
+            let color = if i == self.cursor {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            if let Some(c) = self.name.get(i) {
+                Text::new(&c.to_string(), NAME_FONT_SIZE, color)
+                    .align(Align::Center)
+                    .draw(d, font, x + SLOT_WIDTH / 2, y);
+            }
+            d.draw_rectangle(x + 6, y + NAME_FONT_SIZE + 8, SLOT_WIDTH - 12, 4, color);
+        }
+        ui::hint(d, font, tr!("name_entry.hint"), 600);
+    }
+
+    // The run isn't in the table until the name is in.
+    fn loses_progress(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,87 @@
+use crate::input::{self, Action, Input};
+use crate::lang::{self, tr};
+use crate::text::draw_centered;
+use raylib::prelude::*;
+
+// How long the hints show before they start to fade, and how long the fade
+// takes, in seconds.
+const HINT_TIME: f32 = 5.0;
+const HINT_FADE: f32 = 1.0;
+const HINT_FONT_SIZE: i32 = 20;
+
+#[derive(Clone, Copy)]
+pub enum Control {
+    // Whatever is bound to these actions.
+    Bound(&'static [Action]),
+    // The mouse has no bindings, so it is named as itself.
+    Mouse,
+    Click,
+    RightClick,
+}
+
+// A line of what to press, e.g. `LEFT RIGHT move   ENTER serve   P pause`,
+// that fades out after a few seconds. The controls are named from the input
+// bindings as they are drawn, so the line shows a gamepad's buttons while one
+// is plugged in.
+pub struct Hints {
+    // Each control with the key of the string saying what it does.
+    controls: Vec<(Control, &'static str)>,
+    left: f32,
+    gamepad: bool,
+}
+
+impl Hints {
+    pub fn new(controls: Vec<(Control, &'static str)>) -> Self {
+        Self {
+            controls,
+            left: HINT_TIME + HINT_FADE,
+            gamepad: false,
+        }
+    }
+
+    pub fn update(&mut self, input: &Input, seconds: f32) {
+        self.gamepad = input.gamepad;
+        self.left = (self.left - seconds).max(0.0);
+    }
+
+    // Skips ahead to the fade, for when what the hints are about is over.
+    pub fn dismiss(&mut self) {
+        self.left = self.left.min(HINT_FADE);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.left <= 0.0
+    }
+
+    fn text(&self) -> String {
+        self.controls
+            .iter()
+            .map(|(control, label)| {
+                let name = match control {
+                    Control::Bound(actions) => {
+                        let mut names: Vec<&str> = actions
+                            .iter()
+                            .map(|a| input::control_name(*a, self.gamepad))
+                            .collect();
+                        // The d-pad is one name for all four directions.
+                        names.dedup();
+                        names.join(" ")
+                    }
+                    Control::Mouse => tr!("hints.mouse").to_string(),
+                    Control::Click => tr!("hints.click").to_string(),
+                    Control::RightClick => tr!("hints.right_click").to_string(),
+                };
+                format!("{} {}", name, lang::get(label))
+            })
+            .collect::<Vec<_>>()
+            .join("   ")
+    }
+
+    pub fn render(&self, d: &mut impl RaylibDraw, font: &ffi::Font, y: i32, color: Color) {
+        if self.finished() {
+            return;
+        }
+        let alpha = (self.left / HINT_FADE).min(1.0);
+        draw_centered(d, font, &self.text(), y, HINT_FONT_SIZE, color.alpha(alpha));
+    }
+}
@@ -0,0 +1,89 @@
+// Debug builds pick up edits to the level files and to pong.toml while the
+// game runs, for trying out layouts and speeds without a restart. An edited
+// level takes the place of the old one, and starts over if it is being
+// played. The rest of pong.toml, the tuning included, applies straight away.
+use crate::config::Config;
+use crate::level::{self, Level, LEVELS_DIR};
+use crate::paths;
+use crate::scene::Context;
+use crate::watch::Watcher;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct HotReload {
+    // The built-in levels' folder in the source tree and the one in the
+    // data directory.
+    levels: Vec<Watcher>,
+    config: Watcher,
+}
+
+impl HotReload {
+    pub fn spawn(config: &Path) -> Self {
+        let config_dir = match config.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        Self {
+            levels: [
+                PathBuf::from(LEVELS_DIR),
+                paths::data_dir().join(LEVELS_DIR),
+            ]
+            .into_iter()
+            .map(Watcher::spawn)
+            .collect(),
+            config: Watcher::spawn(config_dir),
+        }
+    }
+
+    // Reads the files that changed back into `ctx`. Returns the ones that
+    // were reloaded, and why the others couldn't be.
+    pub fn apply(&self, ctx: &mut Context) -> Vec<Result<PathBuf, String>> {
+        let mut ret: Vec<_> = self
+            .levels
+            .iter()
+            .flat_map(Watcher::changed)
+            .filter_map(|path| reload_level(ctx, &path))
+            .collect();
+        let config = ctx.config.path().file_name();
+        if self
+            .config
+            .changed()
+            .iter()
+            .any(|path| path.file_name() == config)
+        {
+            ret.extend(reload_config(ctx).map(Ok));
+        }
+        ret
+    }
+}
+
+// Levels that are new or gone are left for a restart, as the others would
+// move along.
+fn reload_level(ctx: &mut Context, path: &Path) -> Option<Result<PathBuf, String>> {
+    let level = ctx
+        .levels
+        .iter_mut()
+        .find(|l| l.file.as_deref() == Some(path) && path.exists())?;
+    Some(match level::load(path, ctx.registry.brick_types()) {
+        Ok(new) => {
+            *level = Level {
+                revision: level.revision + 1,
+                ..new
+            };
+            Ok(path.to_path_buf())
+        }
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    })
+}
+
+// A config file that is gone or can't be read keeps the settings as they
+// are, like one that is only the game saving its own.
+fn reload_config(ctx: &mut Context) -> Option<PathBuf> {
+    let path = ctx.config.path().to_path_buf();
+    let text = fs::read_to_string(&path).ok()?;
+    if text == ctx.config.text() {
+        return None;
+    }
+    ctx.config = Config::parse(&path, &text);
+    Some(path)
+}
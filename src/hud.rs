@@ -0,0 +1,326 @@
+use crate::config::{Table, Value, Writer};
+use crate::game::format_time;
+use crate::lang::tr;
+use crate::screen::Canvas;
+use crate::text::{Align, Text};
+use crate::theme::Theme;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::time::Duration;
+
+const MARGIN: i32 = 10;
+const SPACING: i32 = 5;
+const LIFE_RADIUS: f32 = 16.0;
+const LIFE_SPACING: f32 = 5.0;
+const LIFE_PADDLE_WIDTH: i32 = 40;
+const LIFE_PADDLE_HEIGHT: i32 = 10;
+// Combos shorter than this are not worth showing.
+const COMBO_MIN: u32 = 2;
+// The combo bar is full at this many hits.
+const COMBO_FULL: u32 = 20;
+const BAR_WIDTH: i32 = 160;
+const BAR_HEIGHT: i32 = 8;
+const HARDCORE_TEXT: Color = Color::new(255, 70, 60, 255);
+const HARDCORE_HINT: Color = Color::new(170, 40, 40, 255);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Anchor {
+    Hidden,
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    const ALL: [Anchor; 7] = [
+        Anchor::Hidden,
+        Anchor::TopLeft,
+        Anchor::TopCenter,
+        Anchor::TopRight,
+        Anchor::BottomLeft,
+        Anchor::BottomCenter,
+        Anchor::BottomRight,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Anchor::Hidden => "hidden",
+            Anchor::TopLeft => "top_left",
+            Anchor::TopCenter => "top_center",
+            Anchor::TopRight => "top_right",
+            Anchor::BottomLeft => "bottom_left",
+            Anchor::BottomCenter => "bottom_center",
+            Anchor::BottomRight => "bottom_right",
+        }
+    }
+
+    fn align(&self) -> Align {
+        match self {
+            Anchor::TopLeft | Anchor::BottomLeft | Anchor::Hidden => Align::Left,
+            Anchor::TopCenter | Anchor::BottomCenter => Align::Center,
+            Anchor::TopRight | Anchor::BottomRight => Align::Right,
+        }
+    }
+
+    fn x(&self) -> i32 {
+        match self.align() {
+            Align::Left => MARGIN,
+            Align::Center => WINDOW_WIDTH as i32 / 2,
+            Align::Right => WINDOW_WIDTH as i32 - MARGIN,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(
+            self,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LifeStyle {
+    Balls,
+    Paddles,
+}
+
+impl LifeStyle {
+    fn key(&self) -> &'static str {
+        match self {
+            LifeStyle::Balls => "balls",
+            LifeStyle::Paddles => "paddles",
+        }
+    }
+}
+
+// Where each HUD element goes, read from the `[hud]` section of pong.toml.
+// Elements sharing a corner are stacked away from the edge in this order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HudLayout {
+    pub lives: Anchor,
+    pub life_style: LifeStyle,
+    pub score: Anchor,
+    pub level: Anchor,
+    pub timer: Anchor,
+    pub combo: Anchor,
+    pub speed: Anchor,
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self {
+            lives: Anchor::TopLeft,
+            life_style: LifeStyle::Balls,
+            score: Anchor::TopCenter,
+            level: Anchor::TopCenter,
+            timer: Anchor::TopRight,
+            combo: Anchor::TopRight,
+            speed: Anchor::Hidden,
+        }
+    }
+}
+
+impl HudLayout {
+    pub fn from_table(table: &Table) -> Self {
+        let default = Self::default();
+        let anchor = |key: &str, default: Anchor| {
+            table
+                .get_str(&format!("hud.{}", key))
+                .and_then(|s| Anchor::ALL.into_iter().find(|a| a.key() == s))
+                .unwrap_or(default)
+        };
+        Self {
+            lives: anchor("lives", default.lives),
+            life_style: match table.get_str("hud.life_style") {
+                Some("paddles") => LifeStyle::Paddles,
+                Some("balls") => LifeStyle::Balls,
+                _ => default.life_style,
+            },
+            score: anchor("score", default.score),
+            level: anchor("level", default.level),
+            timer: anchor("timer", default.timer),
+            combo: anchor("combo", default.combo),
+            speed: anchor("speed", default.speed),
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer) {
+        let anchor = |a: Anchor| Value::Str(a.key().into());
+        writer
+            .section("hud")
+            .value("lives", anchor(self.lives))
+            .value("life_style", Value::Str(self.life_style.key().into()))
+            .value("score", anchor(self.score))
+            .value("level", anchor(self.level))
+            .value("timer", anchor(self.timer))
+            .value("combo", anchor(self.combo))
+            .value("speed", anchor(self.speed));
+    }
+}
+
+// What the in-game HUD shows; filled in from the game every frame.
+pub struct Hud {
+    pub lives: usize,
+    pub score: u32,
+    pub level: usize,
+    pub combo: u32,
+    pub timer: Option<Duration>,
+    // Ball speed from 0.0 to 1.0 of the gauge.
+    pub speed: f32,
+    // Tints the HUD red and puts a reminder where the lives would be.
+    pub hardcore: bool,
+    // Shown in place of the lives when set.
+    pub session: Option<Duration>,
+}
+
+impl Hud {
+    // `large` draws all text half as big again, for high contrast mode.
+    pub fn draw(
+        &self,
+        d: &mut Canvas,
+        layout: &HudLayout,
+        theme: &Theme,
+        font: &ffi::Font,
+        large: bool,
+    ) {
+        let size = |size: i32| if large { size * 3 / 2 } else { size };
+        let mut tinted;
+        let theme = if self.hardcore {
+            tinted = theme.clone();
+            tinted.text = HARDCORE_TEXT;
+            tinted.hint = HARDCORE_HINT;
+            tinted.sparks = HARDCORE_TEXT;
+            &tinted
+        } else {
+            theme
+        };
+        // How far each anchor's stack has grown from its edge so far.
+        let mut used = [MARGIN; 7];
+        let mut place = |anchor: Anchor, height: i32| -> Option<(Anchor, i32)> {
+            if anchor == Anchor::Hidden || height == 0 {
+                return None;
+            }
+            let offset = &mut used[anchor as usize];
+            let y = if anchor.is_bottom() {
+                WINDOW_HEIGHT as i32 - *offset - height
+            } else {
+                *offset
+            };
+            *offset += height + SPACING;
+            Some((anchor, y))
+        };
+
+        let lives_height = match layout.life_style {
+            LifeStyle::Balls => (LIFE_RADIUS * 2.0) as i32,
+            LifeStyle::Paddles => LIFE_PADDLE_HEIGHT,
+        };
+        if let Some(session) = &self.session {
+            if let Some((anchor, y)) = place(layout.lives, size(30)) {
+                Text::new(&format_time(session), size(30), theme.text)
+                    .align(anchor.align())
+                    .draw(d, font, anchor.x(), y);
+            }
+        } else if self.hardcore && self.lives == 0 {
+            if let Some((anchor, y)) = place(layout.lives, size(20)) {
+                Text::new(tr!("hud.hardcore"), size(20), theme.text)
+                    .align(anchor.align())
+                    .draw(d, font, anchor.x(), y);
+            }
+        } else if let Some((anchor, y)) = place(layout.lives, lives_height) {
+            self.draw_lives(d, layout.life_style, theme, anchor, y);
+        }
+
+        if let Some((anchor, y)) = place(layout.score, size(30)) {
+            Text::new(&self.score.to_string(), size(30), theme.text)
+                .align(anchor.align())
+                .draw(d, font, anchor.x(), y);
+        }
+
+        if let Some((anchor, y)) = place(layout.level, size(20)) {
+            Text::new(&tr!("hud.level", self.level + 1), size(20), theme.hint)
+                .align(anchor.align())
+                .draw(d, font, anchor.x(), y);
+        }
+
+        if let Some(timer) = &self.timer {
+            if let Some((anchor, y)) = place(layout.timer, size(30)) {
+                Text::new(&format_time(timer), size(30), theme.text)
+                    .align(anchor.align())
+                    .draw(d, font, anchor.x(), y);
+            }
+        }
+
+        if self.combo >= COMBO_MIN {
+            if let Some((anchor, y)) = place(layout.combo, size(20) + 4 + BAR_HEIGHT) {
+                Text::new(&tr!("hud.combo", self.combo), size(20), theme.sparks)
+                    .align(anchor.align())
+                    .draw(d, font, anchor.x(), y);
+                let filled = self.combo.min(COMBO_FULL) as f32 / COMBO_FULL as f32;
+                draw_bar(d, theme, anchor, y + size(20) + 4, filled);
+            }
+        }
+
+        if let Some((anchor, y)) = place(layout.speed, size(20) + 4 + BAR_HEIGHT) {
+            Text::new(tr!("hud.speed"), size(20), theme.hint)
+                .align(anchor.align())
+                .draw(d, font, anchor.x(), y);
+            draw_bar(
+                d,
+                theme,
+                anchor,
+                y + size(20) + 4,
+                self.speed.clamp(0.0, 1.0),
+            );
+        }
+    }
+
+    fn draw_lives(&self, d: &mut Canvas, style: LifeStyle, theme: &Theme, anchor: Anchor, y: i32) {
+        let (width, height) = match style {
+            LifeStyle::Balls => ((LIFE_RADIUS * 2.0) as i32, (LIFE_RADIUS * 2.0) as i32),
+            LifeStyle::Paddles => (LIFE_PADDLE_WIDTH, LIFE_PADDLE_HEIGHT),
+        };
+        let step = width + LIFE_SPACING as i32;
+        let total = self.lives as i32 * step - LIFE_SPACING as i32;
+        let left = match anchor.align() {
+            Align::Left => anchor.x(),
+            Align::Center => anchor.x() - total / 2,
+            Align::Right => anchor.x() - total,
+        };
+        for i in 0..self.lives as i32 {
+            let x = left + i * step;
+            match style {
+                LifeStyle::Balls => {
+                    d.draw_circle(x + width / 2, y + height / 2, LIFE_RADIUS, theme.text)
+                }
+                LifeStyle::Paddles => d.draw_rectangle_gradient_v(
+                    x,
+                    y,
+                    width,
+                    height,
+                    theme.paddle,
+                    theme.paddle_shade,
+                ),
+            }
+        }
+    }
+}
+
+// A horizontal gauge under an element, filling away from the edge it is anchored to.
+fn draw_bar(d: &mut Canvas, theme: &Theme, anchor: Anchor, y: i32, filled: f32) {
+    let left = match anchor.align() {
+        Align::Left => anchor.x(),
+        Align::Center => anchor.x() - BAR_WIDTH / 2,
+        Align::Right => anchor.x() - BAR_WIDTH,
+    };
+    let fill = (BAR_WIDTH as f32 * filled) as i32;
+    d.draw_rectangle(left, y, BAR_WIDTH, BAR_HEIGHT, theme.hint.alpha(0.4));
+    match anchor.align() {
+        Align::Right => {
+            d.draw_rectangle(left + BAR_WIDTH - fill, y, fill, BAR_HEIGHT, theme.sparks)
+        }
+        _ => d.draw_rectangle(left, y, fill, BAR_HEIGHT, theme.sparks),
+    }
+}
@@ -0,0 +1,290 @@
+use crate::lang::tr;
+use raylib::prelude::*;
+
+const GAMEPAD: i32 = 0;
+const GAMEPAD_DEADZONE: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Confirm,
+    Back,
+    Pause,
+    ToggleFullscreen,
+    ToggleStats,
+    ToggleDebug,
+    ToggleConsole,
+    SaveReplay,
+    Screenshot,
+    VolumeUp,
+    VolumeDown,
+    PracticeFreeze,
+    PracticeStep,
+    PracticeMark,
+    PracticeExport,
+    TiltLeft,
+    TiltRight,
+    // The one-handed controls, which take over from the usual ones in play
+    // when they are turned on.
+    HandLeft,
+    HandRight,
+    HandServe,
+    HandPause,
+}
+
+const ACTION_COUNT: usize = 25;
+
+#[derive(Clone, Default)]
+pub struct Input {
+    down: [bool; ACTION_COUNT],
+    pressed: [bool; ACTION_COUNT],
+    pub mouse: Vector2,
+    pub mouse_moved: bool,
+    pub mouse_clicked: bool,
+    pub mouse_right_clicked: bool,
+    // Raw text entry for the console, which must not go through the
+    // bindings since space and backspace double as Confirm and Back.
+    pub typed: String,
+    pub erase: bool,
+    pub submit: bool,
+    // Whether a gamepad is plugged in, so hints can name its buttons.
+    pub gamepad: bool,
+    // The window's close button was pressed.
+    pub close: bool,
+}
+
+impl Input {
+    pub fn poll(rl: &mut RaylibHandle) -> Self {
+        let mut ret = Self {
+            mouse: rl.get_mouse_position(),
+            mouse_moved: {
+                let delta = rl.get_mouse_delta();
+                delta.x != 0.0 || delta.y != 0.0
+            },
+            mouse_clicked: rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT),
+            mouse_right_clicked: rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT),
+            erase: rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE),
+            submit: rl.is_key_pressed(KeyboardKey::KEY_ENTER)
+                || rl.is_key_pressed(KeyboardKey::KEY_KP_ENTER),
+            close: rl.window_should_close(),
+            ..Default::default()
+        };
+
+        while let Some(c) = rl.get_char_pressed() {
+            if c != '`' && c != '~' {
+                ret.typed.push(c);
+            }
+        }
+
+        let pad = rl.is_gamepad_available(GAMEPAD);
+        ret.gamepad = pad;
+        let axis_x = if pad {
+            rl.get_gamepad_axis_movement(GAMEPAD, GamepadAxis::GAMEPAD_AXIS_LEFT_X)
+        } else {
+            0.0
+        };
+
+        for (action, keys, buttons) in BINDINGS {
+            let i = *action as usize;
+            ret.down[i] = keys.iter().any(|k| rl.is_key_down(*k))
+                || (pad
+                    && buttons
+                        .iter()
+                        .any(|b| rl.is_gamepad_button_down(GAMEPAD, *b)));
+            ret.pressed[i] = keys.iter().any(|k| rl.is_key_pressed(*k))
+                || (pad
+                    && buttons
+                        .iter()
+                        .any(|b| rl.is_gamepad_button_pressed(GAMEPAD, *b)));
+        }
+
+        ret.down[Action::Left as usize] |= axis_x < -GAMEPAD_DEADZONE;
+        ret.down[Action::Right as usize] |= axis_x > GAMEPAD_DEADZONE;
+
+        // Alt+Enter toggles fullscreen and must not also confirm a menu item.
+        let alt =
+            rl.is_key_down(KeyboardKey::KEY_LEFT_ALT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_ALT);
+        if alt && rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            ret.pressed[Action::Confirm as usize] = false;
+            ret.pressed[Action::ToggleFullscreen as usize] = true;
+        }
+        ret
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.down[action as usize]
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed[action as usize]
+    }
+
+    // Holds `action` down, for the tests to play the game with.
+    #[cfg(test)]
+    pub fn hold(&mut self, action: Action) {
+        self.down[action as usize] = true;
+    }
+
+    // Makes each of the two actions report what the other one did.
+    pub fn swap(&mut self, a: Action, b: Action) {
+        self.down.swap(a as usize, b as usize);
+        self.pressed.swap(a as usize, b as usize);
+    }
+}
+
+// What to press for `action`, as the control hints name it: the first key
+// bound to it, or its first button while a gamepad is plugged in. An action
+// with no button falls back to its key.
+pub fn control_name(action: Action, gamepad: bool) -> &'static str {
+    let Some((_, keys, buttons)) = BINDINGS.iter().find(|(a, _, _)| *a == action) else {
+        return "";
+    };
+    match (gamepad, buttons.first(), keys.first()) {
+        (true, Some(button), _) => button_name(*button),
+        (_, _, Some(key)) => key_name(*key),
+        _ => "",
+    }
+}
+
+fn key_name(key: KeyboardKey) -> &'static str {
+    match key {
+        KeyboardKey::KEY_LEFT => tr!("key.left"),
+        KeyboardKey::KEY_RIGHT => tr!("key.right"),
+        KeyboardKey::KEY_UP => tr!("key.up"),
+        KeyboardKey::KEY_DOWN => tr!("key.down"),
+        KeyboardKey::KEY_SPACE => tr!("key.space"),
+        KeyboardKey::KEY_ENTER => "ENTER",
+        KeyboardKey::KEY_ESCAPE => "ESC",
+        KeyboardKey::KEY_GRAVE => "`",
+        KeyboardKey::KEY_EQUAL => "=",
+        KeyboardKey::KEY_MINUS => "-",
+        KeyboardKey::KEY_PERIOD => ".",
+        KeyboardKey::KEY_F3 => "F3",
+        KeyboardKey::KEY_F4 => "F4",
+        KeyboardKey::KEY_F9 => "F9",
+        KeyboardKey::KEY_F10 => "F10",
+        KeyboardKey::KEY_A => "A",
+        KeyboardKey::KEY_D => "D",
+        KeyboardKey::KEY_E => "E",
+        KeyboardKey::KEY_F => "F",
+        KeyboardKey::KEY_M => "M",
+        KeyboardKey::KEY_P => "P",
+        KeyboardKey::KEY_Q => "Q",
+        KeyboardKey::KEY_S => "S",
+        KeyboardKey::KEY_W => "W",
+        _ => "?",
+    }
+}
+
+fn button_name(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT
+        | GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT
+        | GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP
+        | GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN => "D-PAD",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN => "A",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT => "B",
+        GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT => "START",
+        GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1 => "LB",
+        GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB => "L3",
+        GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2 => "LT",
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2 => "RT",
+        _ => "?",
+    }
+}
+
+const BINDINGS: &[(Action, &[KeyboardKey], &[GamepadButton])] = &[
+    (
+        Action::Left,
+        &[KeyboardKey::KEY_LEFT],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT],
+    ),
+    (
+        Action::Right,
+        &[KeyboardKey::KEY_RIGHT],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT],
+    ),
+    (
+        Action::Up,
+        &[KeyboardKey::KEY_UP],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP],
+    ),
+    (
+        Action::Down,
+        &[KeyboardKey::KEY_DOWN],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN],
+    ),
+    (
+        Action::Confirm,
+        &[KeyboardKey::KEY_ENTER, KeyboardKey::KEY_SPACE],
+        &[GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN],
+    ),
+    (
+        Action::Back,
+        &[KeyboardKey::KEY_ESCAPE, KeyboardKey::KEY_BACKSPACE],
+        &[GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT],
+    ),
+    (
+        Action::Pause,
+        &[KeyboardKey::KEY_P],
+        &[GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT],
+    ),
+    (Action::ToggleStats, &[KeyboardKey::KEY_F3], &[]),
+    (Action::ToggleDebug, &[KeyboardKey::KEY_F4], &[]),
+    (Action::ToggleConsole, &[KeyboardKey::KEY_GRAVE], &[]),
+    (Action::SaveReplay, &[KeyboardKey::KEY_F9], &[]),
+    // F12 is taken by raylib's own screenshot, which always goes to the
+    // working directory.
+    (Action::Screenshot, &[KeyboardKey::KEY_F10], &[]),
+    (
+        Action::VolumeUp,
+        &[KeyboardKey::KEY_EQUAL, KeyboardKey::KEY_KP_ADD],
+        &[],
+    ),
+    (
+        Action::VolumeDown,
+        &[KeyboardKey::KEY_MINUS, KeyboardKey::KEY_KP_SUBTRACT],
+        &[],
+    ),
+    (Action::PracticeFreeze, &[KeyboardKey::KEY_F], &[]),
+    (Action::PracticeStep, &[KeyboardKey::KEY_PERIOD], &[]),
+    (Action::PracticeMark, &[KeyboardKey::KEY_M], &[]),
+    (Action::PracticeExport, &[KeyboardKey::KEY_X], &[]),
+    (
+        Action::TiltLeft,
+        &[KeyboardKey::KEY_Q],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2],
+    ),
+    (
+        Action::TiltRight,
+        &[KeyboardKey::KEY_E],
+        &[GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2],
+    ),
+    // Everything within reach of one hand: the arrow keys for the right,
+    // WASD for the left, or the left stick with the buttons above and under
+    // it. Moving left and right with the stick and d-pad goes through Left
+    // and Right as usual.
+    (
+        Action::HandLeft,
+        &[KeyboardKey::KEY_LEFT, KeyboardKey::KEY_A],
+        &[],
+    ),
+    (
+        Action::HandRight,
+        &[KeyboardKey::KEY_RIGHT, KeyboardKey::KEY_D],
+        &[],
+    ),
+    (
+        Action::HandServe,
+        &[KeyboardKey::KEY_UP, KeyboardKey::KEY_W],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1],
+    ),
+    (
+        Action::HandPause,
+        &[KeyboardKey::KEY_DOWN, KeyboardKey::KEY_S],
+        &[GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB],
+    ),
+];
@@ -0,0 +1,123 @@
+use crate::config::Table;
+use crate::paths;
+use std::fmt;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+const LANGUAGES_DIR: &str = "languages";
+// English comes first: it is the default, and what any string missing from
+// another language falls back to.
+const BUILTIN: [&str; 2] = [
+    include_str!("../languages/en.lang"),
+    include_str!("../languages/de.lang"),
+];
+
+// The text on screen in one language. A language file has a `[language]`
+// header with its `code` and `name`, then a section per screen holding the
+// strings by key, so `tr!("pause.title")` is `title` in `[pause]`.
+pub struct Language {
+    pub code: String,
+    pub name: String,
+    strings: Table,
+}
+
+impl Language {
+    fn parse(text: &str) -> Result<Self, String> {
+        let strings = Table::parse(text);
+        let get = |key: &str| {
+            strings
+                .get_str(key)
+                .map(str::to_string)
+                .ok_or(format!("missing {}", key))
+        };
+        Ok(Self {
+            code: get("language.code")?,
+            name: get("language.name")?,
+            strings,
+        })
+    }
+}
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+// The built-in languages, then any `.lang` files in the languages folder of
+// the data directory, in file name order. A file with the code of a language
+// already loaded is skipped.
+pub fn all() -> &'static [Language] {
+    static LANGUAGES: OnceLock<Vec<Language>> = OnceLock::new();
+    LANGUAGES.get_or_init(|| {
+        let mut languages: Vec<Language> = BUILTIN
+            .iter()
+            .map(|text| Language::parse(text).expect("built-in language is valid"))
+            .collect();
+        let Ok(entries) = fs::read_dir(paths::data_dir().join(LANGUAGES_DIR)) else {
+            return languages;
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "lang"))
+            .collect();
+        files.sort();
+        for path in files {
+            let language = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Language::parse(&text));
+            match language {
+                Ok(l) if languages.iter().all(|other| other.code != l.code) => languages.push(l),
+                Ok(_) => (),
+                Err(e) => log::warn!("could not load language {}: {}", path.display(), e),
+            }
+        }
+        languages
+    })
+}
+
+// Switches the text to the language with this code; an unknown one means
+// English.
+pub fn select(code: &str) {
+    let index = all().iter().position(|l| l.code == code).unwrap_or(0);
+    CURRENT.store(index, Ordering::Relaxed);
+}
+
+// The string under `key` in the current language, or in English if it has
+// none. A key neither has shows as itself, so it is easy to spot.
+pub fn get(key: &'static str) -> &'static str {
+    let languages = all();
+    languages[CURRENT.load(Ordering::Relaxed)]
+        .strings
+        .get_str(key)
+        .or_else(|| languages[0].strings.get_str(key))
+        .unwrap_or(key)
+}
+
+// `template` with each `{}` in it replaced by the next of `args`.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    out.push_str(parts.next().unwrap_or(""));
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+// `tr!("key")` is a string of the current language. With arguments,
+// `tr!("key", a, b)` fills in the `{}`s of the string in order and gives a
+// `String`.
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::lang::get($key)
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {
+        $crate::lang::fill(
+            $crate::lang::get($key),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+pub(crate) use tr;
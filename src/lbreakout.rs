@@ -0,0 +1,142 @@
+use crate::level::{Background, BrickSpec, Level, LEVELS_DIR, MAX_BRICK_HP};
+use crate::paths;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// LBreakout2 levels are a fixed 14 by 18 grid.
+const WIDTH: usize = 14;
+const HEIGHT: usize = 18;
+
+// The nearest native brick for an LBreakout2 brick character, or None for an
+// empty cell. Walls are left out since the arena already has its own, and
+// indestructible bricks become the toughest native brick so the level can
+// still be cleared. Regenerating, growing and exploding bricks lose their
+// special behaviour and keep only how many hits they take.
+fn brick_hp(c: char) -> Result<Option<usize>, String> {
+    Ok(match c {
+        '.' | ' ' | 'E' => None,
+        '#' | '@' => Some(MAX_BRICK_HP),
+        'a' => Some(1),
+        'b' => Some(2),
+        'c' => Some(3),
+        'v' => Some(1),
+        'x' => Some(2),
+        'y' => Some(3),
+        'z' => Some(4),
+        'd'..='w' | '*' | '!' => Some(1),
+        _ => return Err(format!("unknown brick '{}'", c)),
+    })
+}
+
+// A levelset is a run of levels, each a `Level:` line followed by the author
+// and title, a `Bricks:` grid and a `Bonus:` grid of hidden power-ups, which
+// is ignored. Empty rows above and below the bricks are dropped so the rest
+// get more room.
+pub fn parse(text: &str) -> Result<Vec<Level>, String> {
+    let mut lines = text.lines().map(str::trim_end);
+    let mut levels = Vec::new();
+    while let Some(line) = lines.next() {
+        if line.trim() != "Level:" {
+            continue;
+        }
+        let number = levels.len() + 1;
+        let fail = |e: String| format!("level {}: {}", number, e);
+        let mut field = |prefix: &str| {
+            lines
+                .next()
+                .map(|l| {
+                    l.trim()
+                        .strip_prefix(prefix)
+                        .unwrap_or(l)
+                        .trim()
+                        .to_string()
+                })
+                .ok_or_else(|| fail("the file ends early".into()))
+        };
+        let _author = field("Author:")?;
+        let title = field("Title:")?;
+        if field("")? != "Bricks:" {
+            return Err(fail("expected 'Bricks:' after the title".into()));
+        }
+
+        let mut rows = Vec::new();
+        for row in 0..HEIGHT {
+            let line = lines
+                .next()
+                .ok_or_else(|| fail("the brick grid is cut short".into()))?;
+            let mut cells = Vec::new();
+            for (column, c) in line.chars().take(WIDTH).enumerate() {
+                if let Some(hp) =
+                    brick_hp(c).map_err(|e| fail(format!("{} in row {}", e, row + 1)))?
+                {
+                    cells.push((column, hp));
+                }
+            }
+            rows.push(cells);
+        }
+        let first = rows.iter().position(|r| !r.is_empty());
+        let last = rows.iter().rposition(|r| !r.is_empty());
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(fail("level has no bricks".into()));
+        };
+        let bricks = rows[first..=last]
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| {
+                cells.iter().map(move |&(column, hp)| BrickSpec {
+                    column,
+                    row,
+                    hp,
+                    kind: None,
+                    magnet: false,
+                    points: None,
+                })
+            })
+            .collect();
+        levels.push(Level {
+            name: if title.is_empty() {
+                format!("Level {}", number)
+            } else {
+                title
+            },
+            boss: false,
+            corners: 0.0,
+            columns: WIDTH,
+            background: Background::default(),
+            bricks,
+            portals: Vec::new(),
+            winds: Vec::new(),
+            bumpers: Vec::new(),
+            walls: Vec::new(),
+            obstacles: Vec::new(),
+            dead_zones: Vec::new(),
+            file: None,
+            revision: 0,
+        });
+    }
+    if levels.is_empty() {
+        return Err("no levels found, is this an LBreakout2 levelset?".into());
+    }
+    Ok(levels)
+}
+
+// Converts a levelset into native level files in the levels folder of the
+// data directory, named after the levelset, and returns their paths.
+pub fn import(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let levels = parse(&text)?;
+    let set = path
+        .file_stem()
+        .map_or("levelset".into(), |s| s.to_string_lossy().to_lowercase());
+    let dir = paths::data_subdir(LEVELS_DIR);
+    levels
+        .iter()
+        .enumerate()
+        .map(|(i, level)| {
+            let out = dir.join(format!("{}-{:02}.lvl", set, i + 1));
+            fs::write(&out, level.to_text())
+                .map(|_| out.clone())
+                .map_err(|e| format!("could not write {}: {}", out.display(), e))
+        })
+        .collect()
+}
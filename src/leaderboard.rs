@@ -0,0 +1,342 @@
+// An online leaderboard that finished runs are sent to and the global top 10
+// is read from, next to the local high scores. It is only compiled in with
+// `--features leaderboard` and only used once `[leaderboard] url` is set.
+//
+// The server API is two plain HTTP calls under that URL:
+//
+// - `POST <url>/scores` with a form encoded body of `name` (the one entered
+//   for the high scores), `mode`, `score`, `level`, `time_ms`, `seed` (the
+//   one the run was played with, so it can be played again), `speed` (the
+//   game speed in percent) and `hash`
+// - `GET <url>/scores?mode=<mode>&limit=10` answering with one
+//   `<score>\t<name>` line per entry, best first, optionally followed by
+//   `\t<speed>` for runs played slower than full speed
+//
+// `mode` is the key of the run's category: the mode, with the challenge it
+// was played under appended, e.g. `classic` or `classic_mirror`.
+use crate::challenge::Category;
+use crate::config::{Table, Value, Writer};
+use std::time::Duration;
+
+// The `[leaderboard]` section of pong.toml.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeaderboardSettings {
+    // Empty means no leaderboard.
+    pub url: String,
+}
+
+impl LeaderboardSettings {
+    pub fn from_table(table: &Table) -> Self {
+        Self {
+            url: table.get_str("leaderboard.url").unwrap_or("").to_string(),
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer) {
+        writer
+            .section("leaderboard")
+            .comment("http:// address of a leaderboard server; see the README")
+            .value("url", Value::Str(self.url.clone()));
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+// FNV-1a.
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, b| (h ^ *b as u64).wrapping_mul(0x100_0000_01b3))
+}
+
+// A running hash of how a run was played: where the paddle and the ball were
+// on every step. It goes into the submission's hash, so the hash stands for
+// the play behind a score and not just the numbers sent with it.
+#[derive(Clone, Copy)]
+pub struct Trace(u64);
+
+impl Trace {
+    pub fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+
+    pub fn record(&mut self, racket_x: f32, ball: (f32, f32)) {
+        for value in [racket_x, ball.0, ball.1] {
+            self.0 = fnv(self.0, &value.to_bits().to_le_bytes());
+        }
+    }
+}
+
+pub struct Submission {
+    pub name: String,
+    pub category: Category,
+    pub score: u32,
+    pub level: usize,
+    pub time: Duration,
+    pub seed: u64,
+    // The slowest game speed the run was played at, in percent.
+    pub speed: u8,
+    pub trace: Trace,
+}
+
+impl Submission {
+    // A checksum of the run that the server can store with it, over the
+    // submitted numbers and the run's trace.
+    #[cfg_attr(not(feature = "leaderboard"), allow(dead_code))]
+    fn hash(&self) -> String {
+        let text = format!(
+            "{}|{}|{}|{}|{}|{}",
+            self.category.key(),
+            self.score,
+            self.level,
+            self.time.as_millis(),
+            self.seed,
+            self.speed
+        );
+        format!("{:016x}", fnv(self.trace.0, text.as_bytes()))
+    }
+}
+
+pub struct GlobalScore {
+    pub name: String,
+    pub score: u32,
+    pub speed: u8,
+}
+
+#[cfg(feature = "leaderboard")]
+mod imp {
+    use super::{GlobalScore, LeaderboardSettings, Submission};
+    use crate::challenge::Category;
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    const TOP: usize = 10;
+
+    enum Job {
+        Submit(Submission),
+        Fetch(Category),
+    }
+
+    type Top = Result<Vec<GlobalScore>, String>;
+
+    // Requests go to a background thread one at a time so a slow server
+    // never holds up a frame.
+    pub struct Leaderboard {
+        jobs: Option<Sender<Job>>,
+        results: Option<Receiver<(Category, Top)>>,
+        top: HashMap<Category, Top>,
+    }
+
+    impl Leaderboard {
+        pub fn new(settings: &LeaderboardSettings) -> Self {
+            let mut ret = Self {
+                jobs: None,
+                results: None,
+                top: HashMap::new(),
+            };
+            if settings.url.is_empty() {
+                return ret;
+            }
+            let (jobs, job_receiver) = mpsc::channel();
+            let (result_sender, results) = mpsc::channel();
+            let settings = settings.clone();
+            thread::spawn(move || {
+                for job in job_receiver {
+                    match job {
+                        Job::Submit(run) => match submit(&settings, &run) {
+                            Ok(()) => log::info!("submitted a score of {}", run.score),
+                            Err(e) => log::warn!("could not submit to the leaderboard: {}", e),
+                        },
+                        Job::Fetch(category) => {
+                            let top = fetch(&settings.url, category);
+                            if let Err(e) = &top {
+                                log::warn!("could not fetch the leaderboard: {}", e);
+                            }
+                            let _ = result_sender.send((category, top));
+                        }
+                    }
+                }
+            });
+            ret.jobs = Some(jobs);
+            ret.results = Some(results);
+            ret
+        }
+
+        pub fn enabled(&self) -> bool {
+            self.jobs.is_some()
+        }
+
+        pub fn submit(&self, run: Submission) {
+            if let Some(jobs) = &self.jobs {
+                let _ = jobs.send(Job::Submit(run));
+            }
+        }
+
+        // Asks for a fresh top 10; `top` has it once it arrives.
+        pub fn refresh(&mut self, category: Category) {
+            if let Some(jobs) = &self.jobs {
+                self.top.remove(&category);
+                let _ = jobs.send(Job::Fetch(category));
+            }
+        }
+
+        pub fn update(&mut self) {
+            if let Some(results) = &self.results {
+                self.top.extend(results.try_iter());
+            }
+        }
+
+        // None while the list is still loading.
+        pub fn top(&self, category: Category) -> Option<&Top> {
+            self.top.get(&category)
+        }
+    }
+
+    fn submit(settings: &LeaderboardSettings, run: &Submission) -> Result<(), String> {
+        let body = [
+            ("name", run.name.clone()),
+            ("mode", run.category.key()),
+            ("score", run.score.to_string()),
+            ("level", (run.level + 1).to_string()),
+            ("time_ms", run.time.as_millis().to_string()),
+            ("seed", run.seed.to_string()),
+            ("speed", run.speed.to_string()),
+            ("hash", run.hash()),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+        request(&settings.url, "POST", "/scores", Some(&body)).map(|_| ())
+    }
+
+    fn fetch(url: &str, category: Category) -> Top {
+        let path = format!("/scores?mode={}&limit={}", category.key(), TOP);
+        let body = request(url, "GET", &path, None)?;
+        Ok(body
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let score = fields.next()?.trim().parse().ok()?;
+                let name = fields.next()?.trim().to_string();
+                // Servers from before the game speed setting don't send it.
+                let speed = fields
+                    .next()
+                    .and_then(|s| s.trim().parse().ok())
+                    .unwrap_or(100);
+                Some(GlobalScore { name, score, speed })
+            })
+            .take(TOP)
+            .collect())
+    }
+
+    // A bare HTTP/1.0 request, so the answer is never chunked. Only plain
+    // http:// URLs are supported.
+    fn request(url: &str, method: &str, path: &str, body: Option<&str>) -> Result<String, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or("only http:// leaderboard URLs are supported")?;
+        let (host, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let prefix = prefix.trim_end_matches('/');
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:80", host)
+        };
+        let address = address
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or("the leaderboard host has no address")?;
+        let mut stream =
+            TcpStream::connect_timeout(&address, TIMEOUT).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        stream
+            .set_write_timeout(Some(TIMEOUT))
+            .map_err(|e| e.to_string())?;
+
+        let mut message = format!(
+            "{} {}{}{} HTTP/1.0\r\nHost: {}\r\nUser-Agent: pong\r\n",
+            method,
+            if prefix.is_empty() { "" } else { "/" },
+            prefix,
+            path,
+            host
+        );
+        if let Some(body) = body {
+            message += &format!(
+                "Content-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        } else {
+            message += "\r\n";
+        }
+        stream
+            .write_all(message.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .map_err(|e| e.to_string())?;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+        let status = head.split_whitespace().nth(1).unwrap_or("");
+        if !status.starts_with('2') {
+            return Err(format!(
+                "the server answered {}",
+                head.lines().next().unwrap_or("")
+            ));
+        }
+        Ok(body.to_string())
+    }
+
+    fn encode(value: &str) -> String {
+        value
+            .bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{:02X}", b),
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(feature = "leaderboard"))]
+mod imp {
+    use super::{GlobalScore, LeaderboardSettings, Submission};
+    use crate::challenge::Category;
+
+    pub struct Leaderboard;
+
+    impl Leaderboard {
+        pub fn new(_settings: &LeaderboardSettings) -> Self {
+            Self
+        }
+
+        pub fn enabled(&self) -> bool {
+            false
+        }
+
+        pub fn submit(&self, _run: Submission) {}
+
+        pub fn refresh(&mut self, _category: Category) {}
+
+        pub fn update(&mut self) {}
+
+        pub fn top(&self, _category: Category) -> Option<&Result<Vec<GlobalScore>, String>> {
+            None
+        }
+    }
+}
+
+pub use imp::Leaderboard;
@@ -0,0 +1,536 @@
+use crate::capture;
+use crate::config::{Table, Value, Writer};
+use crate::paths;
+use crate::theme::CLASSIC_BRICKS;
+use pong::plugin::{BrickType, Registry};
+use raylib::prelude::{Color, Image, Rectangle, Vector2};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Levels are this wide unless they set `columns`.
+pub const GRID_COLUMNS: usize = 10;
+pub const MAX_COLUMNS: usize = 20;
+pub const MAX_ROWS: usize = 30;
+const MAX_CORNERS: f64 = 360.0;
+const MAX_BRICK_POINTS: i64 = 10_000;
+pub use pong::plugin::MAX_BRICK_HP;
+// `@` in the grid is a magnet brick, which pulls the ball towards it and
+// takes MAGNET_HP hits.
+pub const MAGNET: char = '@';
+pub const MAGNET_HP: usize = 2;
+// `*` is one end of a portal. They pair up in the order they come in the
+// grid, reading across each row: the first with the second, the third with
+// the fourth and so on.
+pub const PORTAL: char = '*';
+// Levels in the data directory are played after the built-in ones.
+pub const LEVELS_DIR: &str = "levels";
+
+// Each with the name of its file in `levels/`.
+const BUILTIN: [(&str, &str); 6] = [
+    ("01.lvl", include_str!("../levels/01.lvl")),
+    ("02.lvl", include_str!("../levels/02.lvl")),
+    ("03.lvl", include_str!("../levels/03.lvl")),
+    ("04.lvl", include_str!("../levels/04.lvl")),
+    ("05.lvl", include_str!("../levels/05.lvl")),
+    ("06.lvl", include_str!("../levels/06.lvl")),
+];
+
+pub struct BrickSpec {
+    pub column: usize,
+    pub row: usize,
+    pub hp: usize,
+    // Set for bricks of a type a plugin registered.
+    pub kind: Option<BrickType>,
+    pub magnet: bool,
+    // What breaking it scores, when the level's `[points]` section sets it
+    // for this brick's symbol.
+    pub points: Option<u32>,
+}
+
+// A `[wind1]`, `[wind2]`, ... section of a level file: an area of the screen,
+// in pixels, where balls and falling capsules are pushed by `force`, in
+// pixels per second squared.
+#[derive(Clone, Copy)]
+pub struct Wind {
+    pub area: Rectangle,
+    pub force: Vector2,
+}
+
+// A `[bumper1]`, `[bumper2]`, ... section of a level file: a round obstacle
+// centred on `x` and `y`, in pixels, that kicks the ball away.
+#[derive(Clone, Copy)]
+pub struct BumperSpec {
+    pub centre: Vector2,
+    pub radius: f32,
+}
+
+pub const BUMPER_RADIUS: f32 = 28.0;
+
+// The optional `[background]` section of a level file.
+pub struct Background {
+    pub stars: usize,
+    pub star_speed: f32,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            stars: 120,
+            star_speed: 20.0,
+        }
+    }
+}
+
+pub struct Level {
+    pub name: String,
+    // Boss levels get their own music.
+    pub boss: bool,
+    // How far along each edge the 45 degree deflectors across the top
+    // corners reach, in pixels; 0 for square corners.
+    pub corners: f32,
+    pub columns: usize,
+    pub background: Background,
+    pub bricks: Vec<BrickSpec>,
+    // The two cells, column then row, of each pair of portals.
+    pub portals: Vec<[(usize, usize); 2]>,
+    pub winds: Vec<Wind>,
+    pub bumpers: Vec<BumperSpec>,
+    // The shape of the playfield besides its edges and `corners`, in pixels:
+    // walls are open lines through their points, obstacles closed outlines,
+    // and a ball that goes into a dead zone is lost.
+    pub walls: Vec<Vec<Vector2>>,
+    pub obstacles: Vec<Vec<Vector2>>,
+    pub dead_zones: Vec<Rectangle>,
+    // The file the level was read from, if any, and how many times a debug
+    // build has read it again since because it changed. The built-in levels
+    // count as the files they were built from.
+    pub file: Option<PathBuf>,
+    pub revision: u32,
+}
+
+impl Level {
+    // A level file is a `[level]` header followed by a `[bricks]` grid where
+    // `.` is an empty cell, a digit is the hit points of the brick there,
+    // MAGNET a magnet brick and PORTAL a portal. The symbols of `brick_types`
+    // can be used as well, and a `[points]` section before the grid can set
+    // what the bricks of each symbol score.
+    pub fn parse(text: &str, brick_types: &[BrickType]) -> Result<Level, String> {
+        let (header, grid) = text
+            .split_once("[bricks]")
+            .ok_or("missing [bricks] section")?;
+        let table = Table::parse(header);
+        let name = table
+            .get_str("level.name")
+            .ok_or("missing level name")?
+            .to_string();
+        let boss = table.get_bool("level.boss").unwrap_or(false);
+        let corners = table
+            .get_float("level.corners")
+            .map_or(0.0, |c| c.clamp(0.0, MAX_CORNERS) as f32);
+        let columns = match table.get_int("level.columns") {
+            None => GRID_COLUMNS,
+            Some(n @ 1..) if n as usize <= MAX_COLUMNS => n as usize,
+            Some(_) => return Err(format!("levels are 1 to {} columns wide", MAX_COLUMNS)),
+        };
+        let default = Background::default();
+        let background = Background {
+            stars: table
+                .get_int("background.stars")
+                .map_or(default.stars, |n| n.clamp(0, 2000) as usize),
+            star_speed: table
+                .get_float("background.star_speed")
+                .map_or(default.star_speed, |s| s as f32),
+        };
+        let winds = (1..)
+            .map_while(|i| {
+                let get = |key: &str| {
+                    table
+                        .get_float(&format!("wind{}.{}", i, key))
+                        .map(|v| v as f32)
+                };
+                Some(Wind {
+                    area: Rectangle {
+                        x: get("x")?,
+                        y: get("y")?,
+                        width: get("width")?,
+                        height: get("height")?,
+                    },
+                    force: Vector2::new(
+                        get("force_x").unwrap_or(0.0),
+                        get("force_y").unwrap_or(0.0),
+                    ),
+                })
+            })
+            .collect();
+        let bumpers = (1..)
+            .map_while(|i| {
+                let get = |key: &str| {
+                    table
+                        .get_float(&format!("bumper{}.{}", i, key))
+                        .map(|v| v as f32)
+                };
+                Some(BumperSpec {
+                    centre: Vector2::new(get("x")?, get("y")?),
+                    radius: get("radius").unwrap_or(BUMPER_RADIUS).max(1.0),
+                })
+            })
+            .collect();
+        let walls = shapes(&table, "wall", 2)?;
+        let obstacles = shapes(&table, "obstacle", 3)?;
+        let dead_zones = (1..)
+            .map_while(|i| {
+                let get = |key: &str| {
+                    table
+                        .get_float(&format!("dead_zone{}.{}", i, key))
+                        .map(|v| v as f32)
+                };
+                Some(Rectangle {
+                    x: get("x")?,
+                    y: get("y")?,
+                    width: get("width")?,
+                    height: get("height")?,
+                })
+            })
+            .collect();
+
+        let points = |c: char| match table.get_int(&format!("points.{}", c)) {
+            None => Ok(None),
+            Some(p @ 0..=MAX_BRICK_POINTS) => Ok(Some(p as u32)),
+            Some(_) => Err(format!(
+                "points for '{}' go from 0 to {}",
+                c, MAX_BRICK_POINTS
+            )),
+        };
+
+        let mut bricks = Vec::new();
+        let mut portals = Vec::new();
+        for (row, line) in grid
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .enumerate()
+        {
+            if line.chars().count() > columns {
+                return Err(format!("row {} is wider than {} columns", row + 1, columns));
+            }
+            for (column, c) in line.chars().enumerate() {
+                let kind = brick_types.iter().find(|t| t.symbol == c);
+                match (kind, c.to_digit(10).map(|d| d as usize)) {
+                    _ if c == '.' => (),
+                    _ if c == PORTAL => portals.push((column, row)),
+                    _ if c == MAGNET => bricks.push(BrickSpec {
+                        column,
+                        row,
+                        hp: MAGNET_HP,
+                        kind: None,
+                        magnet: true,
+                        points: points(c)?,
+                    }),
+                    (Some(kind), _) => bricks.push(BrickSpec {
+                        column,
+                        row,
+                        hp: kind.hp,
+                        kind: Some(*kind),
+                        magnet: false,
+                        points: points(c)?,
+                    }),
+                    (None, Some(hp @ 1..=MAX_BRICK_HP)) => bricks.push(BrickSpec {
+                        column,
+                        row,
+                        hp,
+                        kind: None,
+                        magnet: false,
+                        points: points(c)?,
+                    }),
+                    _ => return Err(format!("unknown brick '{}' in row {}", c, row + 1)),
+                }
+            }
+        }
+
+        if bricks.is_empty() {
+            return Err("level has no bricks".into());
+        }
+        if portals.len() % 2 != 0 {
+            return Err(format!(
+                "portals come in pairs, but there are {}",
+                portals.len()
+            ));
+        }
+        let level = Level {
+            name,
+            boss,
+            corners,
+            columns,
+            background,
+            bricks,
+            portals: portals.chunks(2).map(|p| [p[0], p[1]]).collect(),
+            winds,
+            bumpers,
+            walls,
+            obstacles,
+            dead_zones,
+            file: None,
+            revision: 0,
+        };
+        if level.rows() > MAX_ROWS {
+            return Err(format!("levels are at most {} rows tall", MAX_ROWS));
+        }
+        Ok(level)
+    }
+
+    // A level drawn as a small image, one pixel per cell and named after the
+    // file. Transparent and black pixels are empty cells; any other colour is
+    // the brick whose colour in the classic theme is closest.
+    pub fn from_image(path: &Path) -> Result<Level, String> {
+        let image = Image::load_image(&path.to_string_lossy())?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if width > MAX_COLUMNS || height > MAX_ROWS {
+            return Err(format!(
+                "the image is {}x{}, levels are at most {}x{}",
+                width, height, MAX_COLUMNS, MAX_ROWS
+            ));
+        }
+        let pixels = image.get_image_data();
+        let bricks: Vec<BrickSpec> = pixels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pixel)| {
+                let hp = brick_for_color(*pixel)?;
+                Some(BrickSpec {
+                    column: i % width,
+                    row: i / width,
+                    hp,
+                    kind: None,
+                    magnet: false,
+                    points: None,
+                })
+            })
+            .collect();
+        if bricks.is_empty() {
+            return Err("level has no bricks".into());
+        }
+        Ok(Level {
+            name: path
+                .file_stem()
+                .map_or("Untitled".into(), |s| s.to_string_lossy().into_owned()),
+            boss: false,
+            corners: 0.0,
+            columns: width,
+            background: Background::default(),
+            bricks,
+            portals: Vec::new(),
+            winds: Vec::new(),
+            bumpers: Vec::new(),
+            walls: Vec::new(),
+            obstacles: Vec::new(),
+            dead_zones: Vec::new(),
+            file: None,
+            revision: 0,
+        })
+    }
+
+    // Writes `to_image` to `path` as a PNG.
+    pub fn export_image(&self, path: &Path) -> Result<(), String> {
+        capture::export_png(&self.to_image(), path)
+    }
+
+    // The image `from_image` reads back into this level.
+    pub fn to_image(&self) -> Image {
+        let mut image =
+            Image::gen_image_color(self.columns as i32, self.rows() as i32, Color::BLANK);
+        for brick in &self.bricks {
+            image.draw_pixel(
+                brick.column as i32,
+                brick.row as i32,
+                CLASSIC_BRICKS[brick.hp - 1],
+            );
+        }
+        image
+    }
+
+    pub fn rows(&self) -> usize {
+        let portals = self.portals.iter().flatten().map(|(_, row)| *row);
+        self.bricks
+            .iter()
+            .map(|b| b.row)
+            .chain(portals)
+            .map(|row| row + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // The level in the file format `parse` reads.
+    pub fn to_text(&self) -> String {
+        let mut writer = Writer::default();
+        writer
+            .section("level")
+            .value("name", Value::Str(self.name.clone()));
+        if self.boss {
+            writer.value("boss", Value::Bool(true));
+        }
+        if self.corners > 0.0 {
+            writer.value("corners", Value::Float(self.corners as f64));
+        }
+        if self.columns != GRID_COLUMNS {
+            writer.value("columns", Value::Int(self.columns as i64));
+        }
+        writer
+            .section("background")
+            .value("stars", Value::Int(self.background.stars as i64))
+            .value(
+                "star_speed",
+                Value::Float(self.background.star_speed as f64),
+            );
+        for (i, wind) in self.winds.iter().enumerate() {
+            let f = |v: f32| Value::Float(v as f64);
+            writer
+                .section(&format!("wind{}", i + 1))
+                .value("x", f(wind.area.x))
+                .value("y", f(wind.area.y))
+                .value("width", f(wind.area.width))
+                .value("height", f(wind.area.height))
+                .value("force_x", f(wind.force.x))
+                .value("force_y", f(wind.force.y));
+        }
+        for (i, bumper) in self.bumpers.iter().enumerate() {
+            let f = |v: f32| Value::Float(v as f64);
+            writer
+                .section(&format!("bumper{}", i + 1))
+                .value("x", f(bumper.centre.x))
+                .value("y", f(bumper.centre.y))
+                .value("radius", f(bumper.radius));
+        }
+        for (name, shapes) in [("wall", &self.walls), ("obstacle", &self.obstacles)] {
+            for (i, points) in shapes.iter().enumerate() {
+                let points: Vec<String> =
+                    points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+                writer
+                    .section(&format!("{}{}", name, i + 1))
+                    .value("points", Value::Str(points.join(" ")));
+            }
+        }
+        for (i, zone) in self.dead_zones.iter().enumerate() {
+            let f = |v: f32| Value::Float(v as f64);
+            writer
+                .section(&format!("dead_zone{}", i + 1))
+                .value("x", f(zone.x))
+                .value("y", f(zone.y))
+                .value("width", f(zone.width))
+                .value("height", f(zone.height));
+        }
+        let symbol = |brick: &BrickSpec| match brick.kind {
+            _ if brick.magnet => MAGNET,
+            Some(kind) => kind.symbol,
+            None => char::from_digit(brick.hp as u32, 10).unwrap(),
+        };
+        let mut scored = Vec::new();
+        for brick in &self.bricks {
+            let c = symbol(brick);
+            if let Some(points) = brick.points.filter(|_| !scored.contains(&c)) {
+                if scored.is_empty() {
+                    writer.section("points");
+                }
+                writer.value(&c.to_string(), Value::Int(points as i64));
+                scored.push(c);
+            }
+        }
+        let mut text = writer.finish();
+        let mut grid = vec![vec!['.'; self.columns]; self.rows()];
+        for brick in &self.bricks {
+            grid[brick.row][brick.column] = symbol(brick);
+        }
+        for (column, row) in self.portals.iter().flatten() {
+            grid[*row][*column] = PORTAL;
+        }
+        text.push_str("\n[bricks]\n");
+        for row in grid {
+            text.extend(row);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+// The `[wall1]`, `[wall2]`, ... (or other `name`) sections, each with
+// `points = "x,y x,y ..."` and at least `min` points.
+fn shapes(table: &Table, name: &str, min: usize) -> Result<Vec<Vec<Vector2>>, String> {
+    let mut shapes = Vec::new();
+    while let Some(text) = table.get_str(&format!("{}{}.points", name, shapes.len() + 1)) {
+        let section = format!("{}{}", name, shapes.len() + 1);
+        let points = text
+            .split_whitespace()
+            .map(|pair| {
+                let point = pair.split_once(',').and_then(|(x, y)| {
+                    Some(Vector2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+                });
+                point.ok_or_else(|| format!("'{}' in {} isn't a point like 100,200", pair, section))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if points.len() < min {
+            return Err(format!("{} needs at least {} points", section, min));
+        }
+        shapes.push(points);
+    }
+    Ok(shapes)
+}
+
+fn brick_for_color(color: Color) -> Option<usize> {
+    if color.a < 128 || (color.r as u32 + color.g as u32 + color.b as u32) < 96 {
+        return None;
+    }
+    let distance = |c: &Color| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(c.r, color.r) + d(c.g, color.g) + d(c.b, color.b)
+    };
+    (1..=MAX_BRICK_HP).min_by_key(|hp| distance(&CLASSIC_BRICKS[hp - 1]))
+}
+
+// The built-in levels, then the ones plugins added, then any `.lvl` or `.png`
+// files in the levels folder of the data directory, in file name order.
+// Broken levels are reported and skipped.
+pub fn load_all(registry: &Registry) -> Vec<Level> {
+    let brick_types = registry.brick_types();
+    let mut levels: Vec<Level> = BUILTIN
+        .iter()
+        .map(|(file, text)| Level {
+            file: Some(Path::new(LEVELS_DIR).join(file)),
+            ..Level::parse(text, brick_types).expect("built-in level is valid")
+        })
+        .collect();
+    for text in registry.levels() {
+        match Level::parse(text, brick_types) {
+            Ok(level) => levels.push(level),
+            Err(e) => log::warn!("could not load a plugin level: {}", e),
+        }
+    }
+    let Ok(entries) = fs::read_dir(paths::data_dir().join(LEVELS_DIR)) else {
+        return levels;
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "lvl" || e == "png"))
+        .collect();
+    files.sort();
+    for path in files {
+        match load(&path, brick_types) {
+            Ok(level) => levels.push(level),
+            Err(e) => log::warn!("could not load {}: {}", path.display(), e),
+        }
+    }
+    levels
+}
+
+// A `.png` level or a level file.
+pub fn load(path: &Path, brick_types: &[BrickType]) -> Result<Level, String> {
+    let level = if path.extension().is_some_and(|e| e == "png") {
+        Level::from_image(path)
+    } else {
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Level::parse(&text, brick_types))
+    }?;
+    Ok(Level {
+        file: Some(path.to_path_buf()),
+        ..level
+    })
+}
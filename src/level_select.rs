@@ -0,0 +1,120 @@
+use crate::game::{format_time, Game};
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::mode::GameMode;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::Text;
+use crate::ui::{self, Grid};
+use raylib::prelude::*;
+use std::time::Duration;
+
+const COLUMNS: usize = 3;
+const TILE_WIDTH: f32 = 340.0;
+const TILE_HEIGHT: f32 = 170.0;
+const TILE_SPACING: f32 = 30.0;
+const GRID_TOP: f32 = 180.0;
+// Rows of tiles on screen at once; the rest scroll into view.
+const VISIBLE_ROWS: usize = 2;
+
+pub struct LevelSelect {
+    grid: Grid,
+}
+
+impl LevelSelect {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            grid: Grid::new(
+                ctx.levels.len(),
+                COLUMNS,
+                VISIBLE_ROWS,
+                (TILE_WIDTH, TILE_HEIGHT),
+                TILE_SPACING,
+            )
+            .top(GRID_TOP),
+        }
+    }
+}
+
+// Any level can be practised.
+fn unlocked(ctx: &Context, level: usize) -> bool {
+    ctx.mode == GameMode::Practice || ctx.progress.is_unlocked(level)
+}
+
+impl Scene for LevelSelect {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        match self.grid.update(input) {
+            Some(level) if unlocked(ctx, level) => {
+                Transition::fade(Transition::Replace(Box::new(Game::new(ctx, level))))
+            }
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("level_select.title"), 60);
+
+        for i in self.grid.visible() {
+            let level = &ctx.levels[i];
+            let rect = self.grid.rect(i);
+            let record = ctx.progress.get(i);
+            let unlocked = unlocked(ctx, i);
+            let (x, y) = (rect.x as i32 + 16, rect.y as i32 + 14);
+
+            let border = if i == self.grid.selected() {
+                Color::YELLOW
+            } else if record.cleared {
+                Color::GREEN
+            } else {
+                Color::DARKGRAY
+            };
+            d.draw_rectangle_lines_ex(rect, 3.0, border);
+
+            let text_color = if unlocked {
+                Color::WHITE
+            } else {
+                Color::DARKGRAY
+            };
+            Text::new(&format!("{}", i + 1), 40, text_color).draw(d, font, x, y);
+            Text::new(&level.name, 24, text_color).draw(d, font, x + 50, y + 10);
+
+            if !unlocked {
+                Text::new(tr!("level_select.locked"), 24, Color::DARKGRAY).draw(d, font, x, y + 70);
+                continue;
+            }
+            if record.cleared {
+                Text::new(tr!("level_select.cleared"), 20, Color::GREEN).draw(
+                    d,
+                    font,
+                    x + 200,
+                    y + 10,
+                );
+            }
+            let best_score = record.best_score.map_or("-".to_string(), |s| s.to_string());
+            let best_time = record
+                .best_time
+                .as_ref()
+                .map_or("-".to_string(), format_time);
+            Text::new(
+                &tr!("level_select.best_score", best_score),
+                20,
+                Color::LIGHTGRAY,
+            )
+            .draw(d, font, x, y + 70);
+            Text::new(
+                &tr!("level_select.best_time", best_time),
+                20,
+                Color::LIGHTGRAY,
+            )
+            .draw(d, font, x, y + 100);
+        }
+
+        self.grid.render_arrows(d);
+        ui::hint(d, font, tr!("level_select.hint"), 650);
+    }
+}
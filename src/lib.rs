@@ -0,0 +1,3 @@
+// The game itself is the `pong` binary. The library only holds what plugins
+// are compiled against.
+pub mod plugin;
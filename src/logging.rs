@@ -0,0 +1,114 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use raylib::consts::TraceLogLevel;
+use std::time::Instant;
+
+// What to log: warnings and errors unless `--verbose` asks for debug
+// messages too, then whatever `RUST_LOG` says on top, in env_logger's form.
+// That is comma-separated directives, each a level for everything, a target
+// to log everything from, or `target=level`, e.g. `info,pong::net=trace`.
+// The longest matching target wins. raylib's own messages count as the
+// `raylib` target.
+struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    fn parse(spec: &str, default: LevelFilter) -> Self {
+        let mut ret = Self {
+            default,
+            targets: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => match level.trim().parse() {
+                    Ok(level) => ret.targets.push((target.trim().to_string(), level)),
+                    Err(_) => eprintln!("ignoring '{}' in RUST_LOG: unknown level", directive),
+                },
+                None => match directive.parse() {
+                    Ok(level) => ret.default = level,
+                    Err(_) => ret
+                        .targets
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        ret
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(t, _)| {
+                target
+                    .strip_prefix(t.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(t, _)| t.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    // The most detailed level anything is logged at, so the log macros can
+    // skip the rest without asking.
+    fn max(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+// Writes to standard error, each message stamped with the seconds since
+// startup, its level and the module it came from.
+struct Logger {
+    filter: Filter,
+    start: Instant,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{:8.3} {:<5} {}: {}",
+                self.start.elapsed().as_secs_f32(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Sets up logging for the rest of the run. Returns how much raylib should
+// log, which has to be passed on when the window is opened.
+pub fn init(verbose: bool) -> TraceLogLevel {
+    let default = if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Warn
+    };
+    let filter = Filter::parse(&std::env::var("RUST_LOG").unwrap_or_default(), default);
+    let raylib = match filter.level("raylib") {
+        LevelFilter::Off => TraceLogLevel::LOG_NONE,
+        LevelFilter::Error => TraceLogLevel::LOG_ERROR,
+        LevelFilter::Warn => TraceLogLevel::LOG_WARNING,
+        LevelFilter::Info => TraceLogLevel::LOG_INFO,
+        LevelFilter::Debug => TraceLogLevel::LOG_DEBUG,
+        LevelFilter::Trace => TraceLogLevel::LOG_TRACE,
+    };
+    log::set_max_level(filter.max());
+    let logger = Box::leak(Box::new(Logger {
+        filter,
+        start: Instant::now(),
+    }));
+    if log::set_logger(logger).is_err() {
+        eprintln!("logging was already set up");
+    }
+    raylib
+}
@@ -1,348 +1,407 @@
-use ffi::Rectangle;
-use raylib::color::Color;
-use raylib::prelude::*;
+mod achievements;
+mod args;
+mod assets;
+mod bonus;
+mod camera;
+mod capture;
+mod challenge;
+mod config;
+mod console;
+mod credits;
+mod error;
+mod events;
+mod game;
+mod ghost;
+mod gif;
+#[cfg(test)]
+mod golden;
+mod highscores;
+mod hints;
+mod hot_reload;
+mod hud;
+mod input;
+mod json;
+mod lang;
+mod lbreakout;
+mod leaderboard;
+mod level;
+mod level_select;
+mod logging;
+mod main_loop;
+mod menu;
+mod mode;
+mod music;
+mod mutators;
+mod net;
+mod notice;
+mod overlay;
+mod pacing;
+mod particles;
+mod paths;
+mod perf;
+mod physics;
+mod playback;
+mod plugins;
+mod pool;
+mod popups;
+mod postfx;
+mod powerups;
+mod practice;
+mod presence;
+mod profile;
+mod progress;
+#[cfg(test)]
+mod property;
+mod rng;
+mod run_code;
+mod scene;
+mod screen;
+mod script;
+mod settings;
+mod shake;
+mod sonify;
+mod sounds;
+mod splits;
+mod starfield;
+mod stats;
+mod summary;
+mod text;
+mod theme;
+mod trail;
+mod tuning;
+mod tween;
+mod twitch;
+mod ui;
+mod versus;
+mod watch;
+mod window;
+
+use achievements::Achievements;
+use args::Args;
+use assets::Assets;
+use capture::Recorder;
+use challenge::Challenge;
+use config::Config;
+use error::Error;
+use game::Game;
+use ghost::Ghost;
+use highscores::HighScores;
+use hot_reload::HotReload;
+use input::{Action, Input};
+use lang::tr;
+use leaderboard::Leaderboard;
+use menu::MainMenu;
+use mode::GameMode;
+use music::Jukebox;
+use net::Peer;
+use notice::Notice;
+use overlay::OverlayServer;
+use pacing::FramePacer;
+use perf::PerfOverlay;
+use playback::Playback;
+use presence::{Activity, Presence};
+use progress::Progress;
+use raylib::prelude::RaylibAudio;
+use rng::Rng;
+use scene::{Context, SceneStack};
+use screen::Screen;
+use settings::{step_volume, Settings};
+use sonify::Sonifier;
+use sounds::SoundBank;
+use stats::LifetimeStats;
+use std::path::PathBuf;
+use std::thread;
 use std::time::{Duration, Instant};
+use theme::Theme;
+use twitch::Chat;
+use versus::Lobby;
+use watch::Watcher;
+use window::Window;
 
 const FPS: f32 = 60.0;
 const WINDOW_WIDTH: f32 = 1280.0;
 const WINDOW_HEIGHT: f32 = 720.0;
-const PROJ_RADIUS: f32 = 16.0;
-const PROJ_SPEED: f32 = 500.0;
-const FRAME_DURATION: f32 = 1.0 / FPS;
-const RACKET_WIDTH: f32 = 8.0 * PROJ_RADIUS;
-const RACKET_HEIGHT: f32 = 16.0;
-const RACKET_POS_Y: f32 = WINDOW_HEIGHT - RACKET_HEIGHT * 5.0;
-const RACKET_SPEED: f32 = 700.0;
-const BRICK_WIDTH: f32 = ((WINDOW_WIDTH - 5.0) / 10.0) - 5.0;
-const BRICK_HEIGHT: f32 = 32.0;
 
-const HI_COLOR: [Color; 6] = [
-    Color::new(0xFF, 0, 0, 0xFF),
-    Color::new(0xFF, 0xFF, 0, 0xFF),
-    Color::new(0, 0xFF, 0, 0xFF),
-    Color::new(0, 0xFF, 0xFF, 0xFF),
-    Color::new(0, 0, 0xFF, 0xFF),
-    Color::new(0xFF, 0, 0xFF, 0xFF),
-];
-
-const LO_COLOR: [Color; 6] = [
-    Color::new(0x3F, 0, 0, 0xFF),
-    Color::new(0x3F, 0x2F, 0, 0xFF),
-    Color::new(0, 0x3F, 0, 0xFF),
-    Color::new(0, 0x3F, 0x3F, 0xFF),
-    Color::new(0, 0, 0x3F, 0xFF),
-    Color::new(0x3F, 0, 0x3F, 0xFF),
-];
-
-fn check_collision_recs(rec1: Rectangle, rec2: Rectangle) -> bool {
-    unsafe { ffi::CheckCollisionRecs(rec1, rec2) }
-}
-
-fn get_collision_recs(rec1: Rectangle, rec2: Rectangle) -> Rectangle {
-    unsafe { ffi::GetCollisionRec(rec1, rec2) }
-}
-
-struct Brick {
-    x: f32,
-    y: f32,
-    live: usize,
-}
-
-struct Projectile {
-    x: f32,
-    y: f32,
-    speed: f32,
-    direction: Vector2,
-    already_in_collision: bool,
-}
-
-impl Projectile {
-    fn new() -> Self {
-        Self {
-            x: WINDOW_WIDTH / 2.0,
-            y: RACKET_POS_Y - PROJ_RADIUS - 1.0,
-            speed: PROJ_SPEED,
-            direction: Vector2 { x: 1.0, y: -1.0 },
-            already_in_collision: false,
+fn main() {
+    let args = Args::from_env();
+    let raylib_log_level = logging::init(args.verbose);
+    if let Some(path) = &args.import {
+        match lbreakout::import(path) {
+            Ok(files) => {
+                for file in files {
+                    println!("{}", file.display());
+                }
+                return;
+            }
+            Err(e) => {
+                eprintln!("could not import {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
         }
     }
-}
-struct Racket {
-    x: f32,
-    direction: f32,
-}
-
-impl Racket {
-    fn new() -> Self {
-        Self {
-            x: WINDOW_WIDTH / 2.0 - RACKET_WIDTH / 2.0,
-            direction: 0.0,
+    let config = match &args.config {
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    };
+    // A config that can't be read is reported in a default-sized window.
+    let (width, height) = config
+        .as_ref()
+        .map_or(Settings::default().window_size, |c| c.settings.window_size);
+    let (mut rl, thread) = match window::open(width, height, raylib_log_level) {
+        Ok(handles) => handles,
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(1);
         }
+    };
+    let mut config = match config {
+        Ok(config) => config,
+        Err(e) => error::fatal(&mut rl, &thread, &e),
+    };
+    if args.fullscreen {
+        config.settings.fullscreen = true;
     }
-}
-struct Game {
-    bricks: Vec<Brick>,
-    ball: Projectile,
-    racket: Racket,
-    lives: usize,
-    state: State,
-    last_frame_instant: Instant,
-}
-
-impl Game {
-    fn new() -> Self {
-        let mut ret = Self {
-            ball: Projectile::new(),
-            bricks: Vec::new(),
-            last_frame_instant: Instant::now(),
-            lives: 3,
-            racket: Racket::new(),
-            state: State::InitialBreak(Instant::now()),
-        };
-        for j in 0..5 {
-            for i in 0..10 {
-                ret.bricks.push(Brick {
-                    x: 5.0 + (i as f32) * (BRICK_WIDTH + 5.0),
-                    y: 100.0 + (j as f32) * (BRICK_HEIGHT + 5.0),
-                    live: 1,
-                })
-            }
+    lang::select(&config.settings.language);
+    let debug = args.debug && cfg!(feature = "devtools");
+    if args.debug && !debug {
+        log::warn!("built without the devtools feature, --debug does nothing");
+    }
+    let frame_duration = Duration::from_secs_f32(1.0 / args.fps.unwrap_or(FPS));
+    rl.set_exit_key(None);
+    rl.set_window_min_size(WINDOW_WIDTH as i32 / 4, WINDOW_HEIGHT as i32 / 4);
+
+    let registry = plugins::load();
+    let levels = level::load_all(&registry);
+    let leaderboard = Leaderboard::new(&config.leaderboard);
+    let chat = Chat::connect(&config.twitch);
+    let overlay = args.overlay_server.and_then(|port| {
+        OverlayServer::start(port)
+            .map_err(|e| log::error!("could not start the overlay server on port {}: {}", port, e))
+            .ok()
+    });
+    let mut ctx = Context {
+        mode: GameMode::Classic,
+        challenge: Challenge::default(),
+        mutators: args.mutators,
+        config,
+        progress: Progress::load(levels.len()),
+        high_scores: HighScores::load(),
+        achievements: Achievements::load(),
+        leaderboard,
+        lifetime: LifetimeStats::load(),
+        assets: Assets::load(&mut rl, &thread),
+        themes: theme::load_all(),
+        high_contrast_theme: Theme::high_contrast(),
+        debug,
+        debug_overlay: false,
+        seed: args.seed,
+        attention: false,
+        hints_shown: false,
+        sounds: Vec::new(),
+        peer: None,
+        chat,
+        scripts: script::load_all(&registry),
+        registry,
+        overlay,
+        levels,
+    };
+    let peer = match (args.host, &args.join) {
+        (Some(port), _) => Some(Peer::host(
+            port,
+            args.level.unwrap_or(0),
+            args.seed.unwrap_or_else(Rng::pick_seed),
+        )),
+        (None, Some(address)) => Some(Peer::join(address)),
+        (None, None) => None,
+    };
+    let mut scenes = SceneStack::new(Box::new(MainMenu::new()));
+    match peer {
+        Some(Ok(peer)) => {
+            ctx.peer = Some(peer);
+            scenes.push(Box::new(Lobby::new()));
         }
-        ret
+        Some(Err(e)) => error::fatal(&mut rl, &thread, &Error::Versus(e)),
+        None => (),
     }
-
-    fn handle_input(&mut self, rl: &RaylibHandle) {
-        self.racket.direction = 0.0;
-        match (
-            rl.is_key_down(KeyboardKey::KEY_LEFT),
-            rl.is_key_down(KeyboardKey::KEY_RIGHT),
-        ) {
-            (true, false) => {
-                if let ST::InitialBreak(grace) = self.state {
-                    if Instant::now().duration_since(grace) > Duration::from_millis(500) {
-                        self.ball.direction.x = -1.0;
-                        self.state = ST::Running
-                    }
-                }
-                self.racket.direction = -1.0;
-            }
-            (false, true) => {
-                if let ST::InitialBreak(grace) = self.state {
-                    if Instant::now().duration_since(grace) > Duration::from_millis(500) {
-                        self.ball.direction.x = 1.0;
-                        self.state = ST::Running
-                    }
-                }
-                self.racket.direction = 1.0;
+    // When racing, `--level` picks the level for the race instead.
+    match args.level.filter(|_| ctx.peer.is_none()) {
+        Some(level) if level < ctx.levels.len() => scenes.push(Box::new(Game::new(&ctx, level))),
+        Some(level) => log::warn!(
+            "there is no level {}, the last one is {}",
+            level + 1,
+            ctx.levels.len()
+        ),
+        None => (),
+    }
+    if let Some(path) = &args.replay {
+        match Ghost::open(path) {
+            Ok((name, ghost)) => scenes.push(Box::new(Playback::new(name, ghost))),
+            Err(message) => error::fatal(
+                &mut rl,
+                &thread,
+                &Error::Replay {
+                    path: path.clone(),
+                    message,
+                },
+            ),
+        }
+    }
+    let mut screen = match Screen::new(&mut rl, &thread) {
+        Ok(screen) => screen,
+        Err(e) => error::fatal(&mut rl, &thread, &e),
+    };
+    screen.apply(&mut rl, &ctx.config.settings);
+    // The sounds borrow the audio device, and in a browser the frame loop
+    // outlives `main`, so the device is kept for the rest of the program.
+    let audio: &'static Option<RaylibAudio> = Box::leak(Box::new(
+        cfg!(feature = "audio")
+            .then(|| {
+                RaylibAudio::init_audio_device()
+                    .map_err(|e| log::warn!("could not open the audio device: {}", e))
+                    .ok()
+            })
+            .flatten(),
+    ));
+    let mut jukebox = Jukebox::load(audio.as_ref());
+    let mut sound_bank = SoundBank::load(audio.as_ref());
+    let mut sonifier = Sonifier::load(audio.as_ref());
+    let mut window = Window::new(&mut rl);
+    let mut perf = PerfOverlay::new();
+    let mut recorder = Recorder::new();
+    let mut notice = Notice::new();
+    // Debug builds pick up edited art, sounds, levels and settings without a
+    // restart.
+    let watch =
+        cfg!(debug_assertions) && cfg!(feature = "devtools") && !cfg!(target_os = "emscripten");
+    let watcher = watch.then(|| Watcher::spawn(PathBuf::from(assets::ASSETS_DIR)));
+    let hot_reload = watch.then(|| HotReload::spawn(ctx.config.path()));
+    let mut pacer = FramePacer::new();
+    let mut presence = Presence::new();
+    let mut last_frame_instant = Instant::now();
+
+    main_loop::run(move || {
+        if scenes.should_quit() {
+            profile::finish();
+            return false;
+        }
+        // Sleep until the next frame is due rather than spinning on the clock.
+        // Frames are timed from start to start so the work done in one
+        // doesn't slow the game down. Browsers pace the frames themselves.
+        let deadline = last_frame_instant + frame_duration;
+        if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+            if !cfg!(target_os = "emscripten") {
+                thread::sleep(wait);
             }
-            _ => self.racket.direction = 0.0,
+        }
+        let frame_time = last_frame_instant.elapsed();
+        last_frame_instant = Instant::now();
+        let duration = pacer.step(&frame_time);
+        let _frame_scope = profile::scope("frame");
+
+        let input = {
+            let _scope = profile::scope("input");
+            screen.update_mouse(&mut rl);
+            Input::poll(&mut rl)
         };
-
-        if rl.is_key_pressed(KeyboardKey::KEY_P) {
-            match self.state {
-                ST::Paused => self.state = ST::Running,
-                ST::Running => self.state = ST::Paused,
-                _ => (),
-            }
+        if input.pressed(Action::ToggleStats) {
+            perf.visible = !perf.visible;
         }
-
-        if let ST::Winning | ST::GameOver = self.state {
-            if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                *self = Game::new();
-            }
+        if debug && input.pressed(Action::ToggleDebug) {
+            ctx.debug_overlay = !ctx.debug_overlay;
         }
-    }
-
-    fn calculate_physics(&mut self, duration: &Duration) {
-        if self.ball.y >= WINDOW_HEIGHT + PROJ_RADIUS {
-            if self.lives == 0 {
-                self.state = ST::GameOver;
-            } else {
-                self.state = ST::InitialBreak(Instant::now());
-                self.lives -= 1;
-                self.ball = Projectile::new();
-                self.racket = Racket::new();
-            }
+        if input.pressed(Action::ToggleFullscreen) {
+            ctx.config.settings.fullscreen = !ctx.config.settings.fullscreen;
+            ctx.config.save();
+        }
+        if input.pressed(Action::SaveReplay) && recorder.save() {
+            notice.show(tr!("notice.saving_replay").into());
+        }
+        let volume_delta =
+            input.pressed(Action::VolumeUp) as isize - input.pressed(Action::VolumeDown) as isize;
+        if volume_delta != 0 {
+            let audio = &mut ctx.config.settings.audio;
+            audio.master_volume = step_volume(audio.master_volume, volume_delta);
+            notice.show(tr!("notice.volume", audio.master_volume));
+            ctx.config.save();
         }
 
-        if let ST::Running = self.state {
-            if self.ball.y <= 0.0 {
-                self.ball.speed += 2.0;
-                self.ball.direction.y = 1.0;
-            }
-
-            if self.ball.x <= PROJ_RADIUS {
-                self.ball.speed += 2.0;
-                self.ball.direction.x = 1.0;
-            }
-
-            if self.ball.x >= WINDOW_WIDTH - PROJ_RADIUS {
-                self.ball.speed += 2.0;
-                self.ball.direction.x = -1.0;
-            }
-
-            self.racket.x += self.racket.direction * RACKET_SPEED * duration.as_secs_f32();
-
-            if self.racket.x <= 0.0 {
-                self.racket.x = 0.0;
-            }
-
-            if self.racket.x >= WINDOW_WIDTH - RACKET_WIDTH - 0.0 {
-                self.racket.x = WINDOW_WIDTH - RACKET_WIDTH - 0.0;
-            }
-
-            let collision_result = check_collision_recs(
-                Rectangle {
-                    x: self.ball.x,
-                    y: self.ball.y,
-                    width: PROJ_RADIUS,
-                    height: PROJ_RADIUS,
-                },
-                Rectangle {
-                    x: self.racket.x,
-                    y: RACKET_POS_Y,
-                    width: RACKET_WIDTH,
-                    height: RACKET_HEIGHT,
-                },
-            );
-
-            self.ball.already_in_collision = if collision_result {
-                if !self.ball.already_in_collision {
-                    self.ball.speed += 2.0;
-                    self.ball.direction.y *= -1.0;
-                }
-                true
-            } else {
-                false
-            };
-
-            for brick in self.bricks.iter_mut() {
-                let coll = get_collision_recs(
-                    Rectangle {
-                        x: self.ball.x,
-                        y: self.ball.y,
-                        width: PROJ_RADIUS,
-                        height: PROJ_RADIUS,
-                    },
-                    Rectangle {
-                        x: brick.x,
-                        y: brick.y,
-                        width: BRICK_WIDTH,
-                        height: BRICK_HEIGHT,
-                    },
-                );
+        for path in watcher.iter().flat_map(Watcher::changed) {
+            ctx.assets.reload(&mut rl, &thread, &path);
+            sound_bank.reload(&path);
+            jukebox.reload(&path);
+            notice.show(tr!("notice.reloaded", path.display()));
+        }
+        for result in hot_reload.iter().flat_map(|h| h.apply(&mut ctx)) {
+            notice.show(match result {
+                Ok(path) => tr!("notice.reloaded", path.display()),
+                Err(e) => tr!("notice.reload_failed", e),
+            });
+        }
 
-                if coll.width * coll.height > 0.0 {
-                    brick.live -= 1;
-                    self.ball.speed += 4.0;
-                    if coll.width > coll.height {
-                        self.ball.direction.y *= -1.0;
-                    } else if coll.width < coll.height {
-                        self.ball.direction.x *= -1.0;
-                    } else {
-                        self.ball.direction.y *= -1.0;
-                        self.ball.direction.x *= -1.0;
-                    }
-                    break;
-                }
+        let update_start = Instant::now();
+        {
+            let _scope = profile::scope("update");
+            scenes.update(&mut ctx, &input, &duration);
+        }
+        let update_time = update_start.elapsed();
+
+        screen.apply(&mut rl, &ctx.config.settings);
+        {
+            let _scope = profile::scope("audio");
+            let (track, ducked) = scenes.music(&ctx);
+            let audio_settings = &ctx.config.settings.audio;
+            if let Some(audio) = &audio {
+                audio.set_master_volume(if args.mute {
+                    0.0
+                } else {
+                    audio_settings.master()
+                });
             }
-
-            self.bricks.retain(|b| b.live > 0);
-
-            if self.bricks.is_empty() {
-                self.state = ST::Winning;
+            jukebox.update(&duration, track, ducked, audio_settings.music());
+            for cue in ctx.sounds.drain(..) {
+                sound_bank.play(&cue, audio_settings.sfx());
             }
-
-            self.ball.x +=
-                self.ball.direction.x * self.ball.speed / 2.0f32.sqrt() * duration.as_secs_f32();
-            self.ball.y +=
-                self.ball.direction.y * self.ball.speed / 2.0f32.sqrt() * duration.as_secs_f32();
+            let tone = audio_settings
+                .ball_tone
+                .then(|| scenes.ball_tone(&ctx))
+                .flatten();
+            sonifier.update(tone, audio_settings.sfx());
         }
-    }
-
-    fn render(&self, mut d: RaylibDrawHandle) {
-        d.clear_background(Color::BLACK);
-        d.draw_circle(
-            self.ball.x as i32,
-            self.ball.y as i32,
-            PROJ_RADIUS,
-            Color::WHITE,
-        );
-
-        d.draw_rectangle_gradient_v(
-            self.racket.x as i32,
-            RACKET_POS_Y as i32,
-            RACKET_WIDTH as i32,
-            RACKET_HEIGHT as i32,
-            Color::RED,
-            Color::new(80, 0, 0, 255),
+        let title = scenes.title(&ctx);
+        window.set_title(&rl, &thread, title.unwrap_or(window::BASE_TITLE.into()));
+        if std::mem::take(&mut ctx.attention) {
+            window.request_attention(&rl);
+        }
+        presence.update(
+            ctx.config
+                .settings
+                .discord_presence
+                .then(|| scenes.activity(&ctx).unwrap_or_else(Activity::menus)),
         );
-
-        for brick in self.bricks.iter() {
-            d.draw_rectangle_gradient_v(
-                brick.x as i32,
-                brick.y as i32,
-                BRICK_WIDTH as i32,
-                BRICK_HEIGHT as i32,
-                HI_COLOR[brick.live],
-                LO_COLOR[brick.live],
-            );
+        let render_start = Instant::now();
+        let effects = ctx.effects();
+        {
+            let _scope = profile::scope("render");
+            screen.draw(&mut rl, &thread, &effects, |d| {
+                scenes.render(&ctx, d);
+                perf.render(d);
+                ctx.achievements.render(d, ctx.font());
+                notice.render(d, ctx.font());
+            });
         }
-
-        for i in 0..self.lives {
-            d.draw_circle(
-                (5.0 + PROJ_RADIUS + (i as f32) * (PROJ_RADIUS * 2.0 + 5.0)) as i32,
-                (5.0 + PROJ_RADIUS) as i32,
-                PROJ_RADIUS,
-                Color::WHITE,
-            );
+        let _capture_scope = profile::scope("capture");
+        if input.pressed(Action::Screenshot) {
+            notice.show(match capture::screenshot(screen.frame()) {
+                Ok(path) => tr!("notice.screenshot_saved", path.display()),
+                Err(e) => tr!("notice.screenshot_failed", e),
+            });
         }
-
-        match self.state {
-            ST::Paused => draw_center_string(&mut d, "PAUSED"),
-            ST::Winning => draw_center_string(&mut d, "YOU WON"),
-            ST::GameOver => draw_center_string(&mut d, "GAME OVER"),
-            _ => (),
+        if let Some(message) = recorder.update(&duration, screen.frame()) {
+            notice.show(message);
         }
-    }
-}
-
-enum State {
-    Running,
-    InitialBreak(Instant),
-    Paused,
-    Winning,
-    GameOver,
-}
-
-use State as ST;
-
-fn draw_center_string(d: &mut RaylibDrawHandle, s: &str) {
-    let width = d.measure_text(s, 50);
-    d.draw_text(
-        s,
-        (WINDOW_WIDTH / 2.0) as i32 - width / 2,
-        (WINDOW_HEIGHT / 2.0) as i32 - 50 / 2,
-        50,
-        Color::YELLOW,
-    );
-}
-fn main() {
-    let mut game = Game::new();
-
-    let (mut rl, thread) = raylib::init()
-        .size(WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32)
-        .title("Pong")
-        .build();
-
-    while !rl.window_should_close() {
-        let duration = Instant::now().duration_since(game.last_frame_instant);
-        if duration > Duration::from_secs_f32(FRAME_DURATION) {
-            game.handle_input(&rl);
-            game.calculate_physics(&duration);
-            let d = rl.begin_drawing(&thread);
-            game.render(d);
-            game.last_frame_instant = Instant::now();
-        }
-    }
+        notice.update(&duration);
+        ctx.achievements.update(&duration);
+        perf.record(&frame_time, &update_time, &render_start.elapsed());
+        true
+    });
 }
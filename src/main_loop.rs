@@ -0,0 +1,35 @@
+// Calls `frame` until it returns false. A native build simply loops; in a
+// browser a blocking loop would freeze the page, so there the browser calls
+// the frame once per animation frame instead and this never returns.
+
+#[cfg(not(target_os = "emscripten"))]
+pub fn run<F: FnMut() -> bool + 'static>(mut frame: F) {
+    while frame() {}
+}
+
+#[cfg(target_os = "emscripten")]
+pub fn run<F: FnMut() -> bool + 'static>(frame: F) {
+    use std::ffi::{c_int, c_void};
+
+    extern "C" {
+        fn emscripten_set_main_loop_arg(
+            func: extern "C" fn(*mut c_void),
+            arg: *mut c_void,
+            fps: c_int,
+            simulate_infinite_loop: c_int,
+        );
+        fn emscripten_cancel_main_loop();
+    }
+
+    extern "C" fn call<F: FnMut() -> bool>(arg: *mut c_void) {
+        let frame = unsafe { &mut *(arg as *mut F) };
+        if !frame() {
+            unsafe { emscripten_cancel_main_loop() };
+        }
+    }
+
+    // The frame is leaked because the browser keeps calling it after `main`
+    // has been unwound. An fps of 0 follows requestAnimationFrame.
+    let frame = Box::into_raw(Box::new(frame));
+    unsafe { emscripten_set_main_loop_arg(call::<F>, frame as *mut c_void, 0, 1) };
+}
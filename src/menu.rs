@@ -0,0 +1,789 @@
+use crate::challenge::{Category, Challenge};
+use crate::credits::Credits;
+use crate::game::Game;
+use crate::highscores::HighScoresScreen;
+use crate::hints::{Control, Hints};
+use crate::input::{Action, Input};
+use crate::lang::{self, tr};
+use crate::level_select::LevelSelect;
+use crate::mode::GameMode;
+use crate::music::Track;
+use crate::mutators::Mutators;
+use crate::run_code::RunCode;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::{native_resolution, Canvas};
+use crate::settings::{step_game_speed, step_oversize, step_volume, Settings};
+use crate::stats::StatsScreen;
+use crate::text::draw_centered;
+use crate::theme::Theme;
+use crate::ui::{self, Event, Item, List, Widget};
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::time::Duration;
+
+const MAIN_PLAY: usize = 0;
+const MAIN_LEVELS: usize = 1;
+const MAIN_MODE: usize = 2;
+const MAIN_CHALLENGES: usize = 3;
+const MAIN_CODE: usize = 4;
+const MAIN_SETTINGS: usize = 5;
+const MAIN_HIGH_SCORES: usize = 6;
+const MAIN_STATS: usize = 7;
+const MAIN_CREDITS: usize = 8;
+const MAIN_QUIT: usize = 9;
+// Packed closer than other menus so every row fits under the title.
+const MAIN_SPACING: f32 = 48.0;
+
+pub struct MainMenu {
+    list: List,
+}
+
+impl MainMenu {
+    pub fn new() -> Self {
+        let labels = Self::labels();
+        Self {
+            list: List::new(labels.into_iter().map(Item::button).collect(), 220.0)
+                .spacing(MAIN_SPACING),
+        }
+    }
+
+    fn labels() -> [&'static str; 10] {
+        [
+            tr!("menu.play"),
+            tr!("menu.level_select"),
+            tr!("menu.mode_select"),
+            tr!("menu.challenges"),
+            tr!("menu.run_code"),
+            tr!("menu.settings"),
+            tr!("menu.high_scores"),
+            tr!("menu.stats"),
+            tr!("menu.credits"),
+            tr!("menu.quit"),
+        ]
+    }
+}
+
+impl Scene for MainMenu {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        // The language may have changed in the settings.
+        self.list.set_labels(&Self::labels());
+        match self.list.update(input) {
+            Some(Event::Pressed(MAIN_PLAY)) => {
+                Transition::fade(Transition::Push(Box::new(Game::new(ctx, 0))))
+            }
+            Some(Event::Pressed(MAIN_LEVELS)) => Transition::Push(Box::new(LevelSelect::new(ctx))),
+            Some(Event::Pressed(MAIN_MODE)) => {
+                Transition::Push(Box::new(ModeSelect::new(ctx.mode)))
+            }
+            Some(Event::Pressed(MAIN_CHALLENGES)) => {
+                Transition::Push(Box::new(ChallengeMenu::new(ctx)))
+            }
+            Some(Event::Pressed(MAIN_CODE)) => Transition::Push(Box::new(CodeEntry::new())),
+            Some(Event::Pressed(MAIN_SETTINGS)) => {
+                Transition::Push(Box::new(SettingsMenu::new(ctx, false)))
+            }
+            Some(Event::Pressed(MAIN_HIGH_SCORES)) => {
+                Transition::Push(Box::new(HighScoresScreen::new(Category {
+                    mode: ctx.mode,
+                    challenge: ctx.challenge,
+                })))
+            }
+            Some(Event::Pressed(MAIN_STATS)) => Transition::Push(Box::new(StatsScreen::new())),
+            Some(Event::Pressed(MAIN_CREDITS)) => Transition::Push(Box::new(Credits::new())),
+            Some(Event::Pressed(MAIN_QUIT)) => Transition::Quit,
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        draw_centered(d, font, "PONG", 70, 100, Color::YELLOW);
+        let category = Category {
+            mode: ctx.mode,
+            challenge: ctx.challenge,
+        };
+        let mode = if ctx.mutators.is_none() {
+            tr!("menu.mode", category.name())
+        } else {
+            tr!(
+                "menu.mode_mutators",
+                category.name(),
+                ctx.mutators.names().join(", ")
+            )
+        };
+        ui::hint(d, font, &mode, 190);
+        self.list.render(d, font);
+    }
+
+    fn music(&self, _ctx: &Context) -> Option<Track> {
+        Some(Track::Menu)
+    }
+}
+
+pub struct ModeSelect {
+    list: List,
+}
+
+impl ModeSelect {
+    pub fn new(current: GameMode) -> Self {
+        let mut list = List::new(
+            GameMode::ALL
+                .iter()
+                .map(|m| Item::button(m.name()))
+                .collect(),
+            300.0,
+        );
+        list.select(
+            GameMode::ALL
+                .iter()
+                .position(|m| *m == current)
+                .unwrap_or(0),
+        );
+        Self { list }
+    }
+}
+
+impl Scene for ModeSelect {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        match self.list.update(input) {
+            Some(Event::Pressed(index)) => {
+                ctx.mode = GameMode::ALL[index];
+                Transition::Pop
+            }
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("mode_select.title"), 120);
+        self.list.render(d, font);
+        ui::hint(
+            d,
+            font,
+            GameMode::ALL[self.list.selected()].description(),
+            600,
+        );
+    }
+}
+
+const CHALLENGE_MIRROR: usize = 0;
+const CHALLENGE_INVERTED: usize = 1;
+// One row per mutator follows the challenges.
+const CHALLENGE_MUTATORS: usize = 2;
+const CHALLENGE_BACK: usize = CHALLENGE_MUTATORS + Mutators::COUNT;
+const CHALLENGE_ROWS: usize = 6;
+const CHALLENGE_WIDTH: f32 = 720.0;
+
+pub struct ChallengeMenu {
+    list: List,
+}
+
+impl ChallengeMenu {
+    pub fn new(ctx: &Context) -> Self {
+        let mut ret = Self {
+            list: List::new(
+                (0..=CHALLENGE_BACK).map(|_| Item::button("")).collect(),
+                220.0,
+            )
+            .scrolling(CHALLENGE_ROWS)
+            .width(CHALLENGE_WIDTH),
+        };
+        ret.refresh(ctx.challenge, ctx.mutators);
+        ret
+    }
+
+    fn refresh(&mut self, challenge: Challenge, mutators: Mutators) {
+        self.list.set(
+            CHALLENGE_MIRROR,
+            tr!("challenges.mirror"),
+            Widget::Toggle(challenge.mirror),
+        );
+        self.list.set(
+            CHALLENGE_INVERTED,
+            tr!("challenges.inverted"),
+            Widget::Toggle(challenge.inverted),
+        );
+        for i in 0..Mutators::COUNT {
+            self.list.set(
+                CHALLENGE_MUTATORS + i,
+                Mutators::name(i),
+                Widget::Toggle(mutators.get(i)),
+            );
+        }
+        self.list
+            .set(CHALLENGE_BACK, tr!("menu.back"), Widget::Button);
+    }
+
+    fn toggle(ctx: &mut Context, row: usize) {
+        match row {
+            CHALLENGE_MIRROR => ctx.challenge.mirror = !ctx.challenge.mirror,
+            CHALLENGE_INVERTED => ctx.challenge.inverted = !ctx.challenge.inverted,
+            CHALLENGE_BACK => (),
+            _ => ctx.mutators.toggle(row - CHALLENGE_MUTATORS),
+        }
+    }
+}
+
+impl Scene for ChallengeMenu {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        match self.list.update(input) {
+            Some(Event::Pressed(CHALLENGE_BACK)) => return Transition::Pop,
+            Some(Event::Changed(row, _)) => Self::toggle(ctx, row),
+            _ => (),
+        }
+        self.refresh(ctx.challenge, ctx.mutators);
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("challenges.title"), 100);
+        self.list.render(d, font);
+        ui::hint(d, font, tr!("challenges.ranked_hint"), 600);
+        ui::hint(d, font, tr!("challenges.unranked_hint"), 630);
+    }
+}
+
+// Codes are ten characters or so; this leaves room for dashes and long seeds.
+const CODE_MAX_LENGTH: usize = 32;
+
+// Typing in a friend's run code to play the same setup.
+pub struct CodeEntry {
+    text: String,
+    code: Result<RunCode, String>,
+}
+
+impl CodeEntry {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            code: Err(String::new()),
+        }
+    }
+}
+
+impl Scene for CodeEntry {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        // Backspace is bound to Back too, but here it erases.
+        if input.pressed(Action::Back) && !input.erase {
+            return Transition::Pop;
+        }
+        self.text.extend(
+            input
+                .typed
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '-'),
+        );
+        self.text.truncate(CODE_MAX_LENGTH);
+        if input.erase {
+            self.text.pop();
+        }
+        self.code = self.text.parse();
+        let Ok(code) = self.code else {
+            return Transition::None;
+        };
+        if !input.submit {
+            return Transition::None;
+        }
+        ctx.mode = code.mode;
+        ctx.challenge = code.challenge;
+        ctx.mutators = code.mutators;
+        ctx.seed = Some(code.seed);
+        if ctx.config.settings.difficulty != code.difficulty {
+            ctx.config.settings.difficulty = code.difficulty;
+            ctx.config.save();
+        }
+        Transition::fade(Transition::Replace(Box::new(Game::new(ctx, 0))))
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("run_code.title"), 120);
+        draw_centered(d, font, &format!("{}_", self.text), 280, 50, Color::WHITE);
+        match &self.code {
+            Ok(code) => {
+                draw_centered(d, font, &code.describe(), 380, 24, Color::GREEN);
+                if code.difficulty != ctx.config.settings.difficulty {
+                    draw_centered(
+                        d,
+                        font,
+                        &tr!("run_code.difficulty", code.difficulty.name()),
+                        420,
+                        20,
+                        Color::GRAY,
+                    );
+                }
+            }
+            Err(e) if !self.text.is_empty() => {
+                draw_centered(d, font, e, 380, 24, Color::GRAY);
+            }
+            Err(_) => (),
+        }
+        ui::hint(d, font, tr!("run_code.hint"), 600);
+    }
+}
+
+const PAUSE_RESUME: usize = 0;
+const PAUSE_RESTART: usize = 1;
+const PAUSE_SETTINGS: usize = 2;
+const PAUSE_QUIT: usize = 3;
+
+pub struct PauseMenu {
+    level: usize,
+    list: List,
+    hints: Hints,
+}
+
+impl PauseMenu {
+    pub fn new(level: usize) -> Self {
+        let labels = Self::labels();
+        Self {
+            level,
+            list: List::new(labels.into_iter().map(Item::button).collect(), 280.0),
+            hints: Hints::new(vec![
+                (Control::Bound(&[Action::Up, Action::Down]), "hints.choose"),
+                (Control::Bound(&[Action::Confirm]), "hints.select"),
+                (Control::Bound(&[Action::Pause]), "hints.resume"),
+            ]),
+        }
+    }
+
+    fn labels() -> [&'static str; 4] {
+        [
+            tr!("pause.resume"),
+            tr!("pause.restart"),
+            tr!("menu.settings"),
+            tr!("pause.quit"),
+        ]
+    }
+}
+
+impl Scene for PauseMenu {
+    fn update(&mut self, ctx: &mut Context, input: &Input, duration: &Duration) -> Transition {
+        if input.pressed(Action::Pause) || input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        self.hints.update(input, duration.as_secs_f32());
+        self.list.set_labels(&Self::labels());
+        match self.list.update(input) {
+            Some(Event::Pressed(PAUSE_RESUME)) => Transition::Pop,
+            Some(Event::Pressed(PAUSE_RESTART)) => Transition::fade(Transition::Reset(vec![
+                Box::new(MainMenu::new()),
+                Box::new(Game::new(ctx, self.level)),
+            ])),
+            Some(Event::Pressed(PAUSE_SETTINGS)) => {
+                Transition::Push(Box::new(SettingsMenu::new(ctx, true)))
+            }
+            Some(Event::Pressed(PAUSE_QUIT)) if ctx.config.settings.confirm_quit => {
+                Transition::Push(Box::new(QuitPrompt::new(false)))
+            }
+            Some(Event::Pressed(PAUSE_QUIT)) => {
+                Transition::fade(Transition::Reset(vec![Box::new(MainMenu::new())]))
+            }
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        dim_background(d);
+        ui::heading(d, font, tr!("pause.title"), 160);
+        self.list.render(d, font);
+        self.hints
+            .render(d, font, WINDOW_HEIGHT as i32 - 80, ctx.theme().hint);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn ducks_music(&self) -> bool {
+        true
+    }
+}
+
+const QUIT_YES: usize = 0;
+const QUIT_NO: usize = 1;
+
+// Asks before a run in progress is thrown away, either back to the main menu
+// or, when the window was closed, out of the game.
+pub struct QuitPrompt {
+    exit: bool,
+    list: List,
+}
+
+impl QuitPrompt {
+    pub fn new(exit: bool) -> Self {
+        let labels = Self::labels();
+        let mut list = List::new(labels.into_iter().map(Item::button).collect(), 340.0);
+        // Answering without looking keeps playing.
+        list.select(QUIT_NO);
+        Self { exit, list }
+    }
+
+    fn labels() -> [&'static str; 2] {
+        [tr!("quit.yes"), tr!("quit.no")]
+    }
+}
+
+impl Scene for QuitPrompt {
+    fn update(&mut self, _ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        match self.list.update(input) {
+            Some(Event::Pressed(QUIT_YES)) if self.exit => Transition::Quit,
+            Some(Event::Pressed(QUIT_YES)) => {
+                Transition::fade(Transition::Reset(vec![Box::new(MainMenu::new())]))
+            }
+            Some(Event::Pressed(QUIT_NO)) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        dim_background(d);
+        ui::heading(d, font, tr!("quit.title"), 200);
+        draw_centered(d, font, tr!("quit.warning"), 280, 24, Color::LIGHTGRAY);
+        self.list.render(d, font);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn ducks_music(&self) -> bool {
+        true
+    }
+
+    fn asks_to_quit(&self) -> bool {
+        true
+    }
+}
+
+fn dim_background(d: &mut Canvas) {
+    d.draw_rectangle(
+        0,
+        0,
+        WINDOW_WIDTH as i32,
+        WINDOW_HEIGHT as i32,
+        Color::new(0, 0, 0, 180),
+    );
+}
+
+// The language goes first, where it can be found without reading the rest.
+const SETTINGS_LANGUAGE: usize = 0;
+const SETTINGS_VOLUME: usize = 1;
+const SETTINGS_MUSIC_VOLUME: usize = 2;
+const SETTINGS_SFX_VOLUME: usize = 3;
+const SETTINGS_BALL_TONE: usize = 4;
+const SETTINGS_INPUT: usize = 5;
+const SETTINGS_ONE_HANDED: usize = 6;
+const SETTINGS_DIFFICULTY: usize = 7;
+const SETTINGS_WINDOW: usize = 8;
+const SETTINGS_RESOLUTION: usize = 9;
+const SETTINGS_SHAKE: usize = 10;
+const SETTINGS_THEME: usize = 11;
+const SETTINGS_HIGH_CONTRAST: usize = 12;
+const SETTINGS_REDUCED_MOTION: usize = 13;
+const SETTINGS_GAME_SPEED: usize = 14;
+const SETTINGS_OVERSIZED: usize = 15;
+const SETTINGS_OVERSIZE_FACTOR: usize = 16;
+const SETTINGS_CRT: usize = 17;
+const SETTINGS_CONSTANT_SPEED: usize = 18;
+const SETTINGS_PADDLE_WEAR: usize = 19;
+const SETTINGS_DISCORD: usize = 20;
+const SETTINGS_SPEEDRUN_TIMER: usize = 21;
+const SETTINGS_CONFIRM_QUIT: usize = 22;
+const SETTINGS_BACK: usize = 23;
+const SETTINGS_ROWS: usize = 7;
+const SETTINGS_WIDTH: f32 = 820.0;
+
+pub struct SettingsMenu {
+    list: List,
+    overlay: bool,
+}
+
+impl SettingsMenu {
+    pub fn new(ctx: &Context, overlay: bool) -> Self {
+        let mut ret = Self {
+            list: List::new(
+                (0..=SETTINGS_BACK).map(|_| Item::button("")).collect(),
+                170.0,
+            )
+            .scrolling(SETTINGS_ROWS)
+            .width(SETTINGS_WIDTH),
+            overlay,
+        };
+        ret.refresh(&ctx.config.settings);
+        ret
+    }
+
+    fn refresh(&mut self, settings: &Settings) {
+        let audio = &settings.audio;
+        let visual = &settings.visual;
+        let language = lang::all()
+            .iter()
+            .find(|l| l.code == settings.language)
+            .unwrap_or(&lang::all()[0]);
+        let rows = [
+            (
+                SETTINGS_LANGUAGE,
+                tr!("settings.language"),
+                Widget::Choice(language.name.clone()),
+            ),
+            (
+                SETTINGS_VOLUME,
+                tr!("settings.master_volume"),
+                Widget::Slider(audio.master_volume),
+            ),
+            (
+                SETTINGS_MUSIC_VOLUME,
+                tr!("settings.music_volume"),
+                Widget::Slider(audio.music_volume),
+            ),
+            (
+                SETTINGS_SFX_VOLUME,
+                tr!("settings.sound_volume"),
+                Widget::Slider(audio.sfx_volume),
+            ),
+            (
+                SETTINGS_BALL_TONE,
+                tr!("settings.ball_tone"),
+                Widget::Toggle(audio.ball_tone),
+            ),
+            (
+                SETTINGS_INPUT,
+                tr!("settings.input"),
+                Widget::Choice(settings.input_mode.name().into()),
+            ),
+            (
+                SETTINGS_ONE_HANDED,
+                tr!("settings.one_handed"),
+                Widget::Toggle(settings.one_handed),
+            ),
+            (
+                SETTINGS_DIFFICULTY,
+                tr!("settings.difficulty"),
+                Widget::Choice(settings.difficulty.name().into()),
+            ),
+            (
+                SETTINGS_WINDOW,
+                tr!("settings.window"),
+                Widget::Choice(
+                    if settings.fullscreen {
+                        tr!("settings.fullscreen")
+                    } else {
+                        tr!("settings.windowed")
+                    }
+                    .into(),
+                ),
+            ),
+            (
+                SETTINGS_SHAKE,
+                tr!("settings.shake"),
+                Widget::Slider(visual.shake_intensity),
+            ),
+            (
+                SETTINGS_THEME,
+                tr!("settings.theme"),
+                Widget::Choice(settings.theme.clone()),
+            ),
+            (
+                SETTINGS_HIGH_CONTRAST,
+                tr!("settings.high_contrast"),
+                Widget::Toggle(visual.high_contrast),
+            ),
+            (
+                SETTINGS_REDUCED_MOTION,
+                tr!("settings.reduced_motion"),
+                Widget::Toggle(visual.reduced_motion),
+            ),
+            (
+                SETTINGS_GAME_SPEED,
+                tr!("settings.game_speed"),
+                Widget::Slider(settings.game_speed),
+            ),
+            (
+                SETTINGS_OVERSIZED,
+                tr!("settings.oversized"),
+                Widget::Toggle(visual.oversized),
+            ),
+            (
+                SETTINGS_OVERSIZE_FACTOR,
+                tr!("settings.oversize_factor"),
+                Widget::Choice(format!("{}%", visual.oversize_factor)),
+            ),
+            (
+                SETTINGS_CRT,
+                tr!("settings.crt"),
+                Widget::Toggle(visual.crt),
+            ),
+            (
+                SETTINGS_CONSTANT_SPEED,
+                tr!("settings.constant_speed"),
+                Widget::Toggle(settings.constant_ball_speed),
+            ),
+            (
+                SETTINGS_PADDLE_WEAR,
+                tr!("settings.paddle_wear"),
+                Widget::Toggle(settings.paddle_wear),
+            ),
+            (
+                SETTINGS_DISCORD,
+                tr!("settings.discord"),
+                Widget::Toggle(settings.discord_presence),
+            ),
+            (
+                SETTINGS_SPEEDRUN_TIMER,
+                tr!("settings.speedrun_timer"),
+                Widget::Toggle(settings.speedrun_timer),
+            ),
+            (
+                SETTINGS_CONFIRM_QUIT,
+                tr!("settings.confirm_quit"),
+                Widget::Toggle(settings.confirm_quit),
+            ),
+            (SETTINGS_BACK, tr!("menu.back"), Widget::Button),
+        ];
+        for (row, label, widget) in rows {
+            self.list.set(row, label, widget);
+        }
+        let (width, height) = if settings.fullscreen {
+            native_resolution()
+        } else {
+            settings.window_size
+        };
+        self.list.set(
+            SETTINGS_RESOLUTION,
+            tr!("settings.resolution"),
+            Widget::Choice(format!("{}x{}", width, height)),
+        );
+        // The resolution only applies to the window; fullscreen always
+        // uses the monitor's native one.
+        self.list
+            .set_enabled(SETTINGS_RESOLUTION, !settings.fullscreen);
+        // Reduced motion turns the shake off whatever its intensity.
+        self.list
+            .set_enabled(SETTINGS_SHAKE, !visual.reduced_motion);
+        self.list
+            .set_enabled(SETTINGS_OVERSIZE_FACTOR, visual.oversized);
+        self.list.set_enabled(
+            SETTINGS_PADDLE_WEAR,
+            settings.difficulty.allows_paddle_wear(),
+        );
+        self.list
+            .set_enabled(SETTINGS_DISCORD, cfg!(feature = "discord"));
+        for row in [
+            SETTINGS_VOLUME,
+            SETTINGS_MUSIC_VOLUME,
+            SETTINGS_SFX_VOLUME,
+            SETTINGS_BALL_TONE,
+        ] {
+            self.list.set_enabled(row, cfg!(feature = "audio"));
+        }
+    }
+
+    // Applies a left/right (-1/+1) adjustment to the given row.
+    fn adjust(settings: &mut Settings, themes: &[Theme], row: usize, delta: isize) -> bool {
+        match row {
+            SETTINGS_LANGUAGE => {
+                settings.cycle_language(lang::all(), delta);
+                lang::select(&settings.language);
+            }
+            SETTINGS_VOLUME => {
+                settings.audio.master_volume = step_volume(settings.audio.master_volume, delta)
+            }
+            SETTINGS_MUSIC_VOLUME => {
+                settings.audio.music_volume = step_volume(settings.audio.music_volume, delta)
+            }
+            SETTINGS_SFX_VOLUME => {
+                settings.audio.sfx_volume = step_volume(settings.audio.sfx_volume, delta)
+            }
+            SETTINGS_BALL_TONE => settings.audio.ball_tone = !settings.audio.ball_tone,
+            SETTINGS_INPUT => settings.cycle_input_mode(delta),
+            SETTINGS_ONE_HANDED => settings.one_handed = !settings.one_handed,
+            SETTINGS_DIFFICULTY => settings.cycle_difficulty(delta),
+            SETTINGS_WINDOW => settings.fullscreen = !settings.fullscreen,
+            SETTINGS_RESOLUTION => settings.cycle_window_size(delta),
+            SETTINGS_SHAKE => {
+                let step = settings.visual.shake_intensity as isize / 25 + delta;
+                settings.visual.shake_intensity = (step.rem_euclid(5) * 25) as u8
+            }
+            SETTINGS_THEME => settings.cycle_theme(themes, delta),
+            SETTINGS_HIGH_CONTRAST => {
+                settings.visual.high_contrast = !settings.visual.high_contrast
+            }
+            SETTINGS_REDUCED_MOTION => {
+                settings.visual.reduced_motion = !settings.visual.reduced_motion
+            }
+            SETTINGS_GAME_SPEED => {
+                settings.game_speed = step_game_speed(settings.game_speed, delta)
+            }
+            SETTINGS_OVERSIZED => settings.visual.oversized = !settings.visual.oversized,
+            SETTINGS_OVERSIZE_FACTOR => {
+                settings.visual.oversize_factor =
+                    step_oversize(settings.visual.oversize_factor, delta)
+            }
+            SETTINGS_CRT => settings.visual.crt = !settings.visual.crt,
+            SETTINGS_CONSTANT_SPEED => settings.constant_ball_speed = !settings.constant_ball_speed,
+            SETTINGS_PADDLE_WEAR => settings.paddle_wear = !settings.paddle_wear,
+            SETTINGS_DISCORD => settings.discord_presence = !settings.discord_presence,
+            SETTINGS_SPEEDRUN_TIMER => settings.speedrun_timer = !settings.speedrun_timer,
+            SETTINGS_CONFIRM_QUIT => settings.confirm_quit = !settings.confirm_quit,
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Scene for SettingsMenu {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+
+        let changed = match self.list.update(input) {
+            Some(Event::Pressed(SETTINGS_BACK)) => return Transition::Pop,
+            Some(Event::Changed(row, delta)) => {
+                Self::adjust(&mut ctx.config.settings, &ctx.themes, row, delta)
+            }
+            _ => false,
+        };
+
+        if changed {
+            ctx.config.save();
+        }
+        // Settings can also change outside this menu, e.g. with Alt+Enter.
+        self.refresh(&ctx.config.settings);
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        if self.overlay {
+            dim_background(d);
+        } else {
+            d.clear_background(Color::BLACK);
+        }
+        ui::heading(d, font, tr!("settings.title"), 50);
+        self.list.render(d, font);
+        ui::hint(d, font, tr!("settings.hint"), 640);
+    }
+
+    fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+}
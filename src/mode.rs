@@ -0,0 +1,98 @@
+use crate::lang::tr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameMode {
+    Classic,
+    TimeAttack,
+    Hardcore,
+    Zen,
+    Practice,
+    DualPaddle,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 6] = [
+        GameMode::Classic,
+        GameMode::TimeAttack,
+        GameMode::Hardcore,
+        GameMode::Zen,
+        GameMode::Practice,
+        GameMode::DualPaddle,
+    ];
+    // The modes that keep high scores.
+    pub const RANKED: [GameMode; 3] = [GameMode::Classic, GameMode::TimeAttack, GameMode::Hardcore];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => tr!("mode.classic"),
+            GameMode::TimeAttack => tr!("mode.time_attack"),
+            GameMode::Hardcore => tr!("mode.hardcore"),
+            GameMode::Zen => tr!("mode.zen"),
+            GameMode::Practice => tr!("mode.practice"),
+            GameMode::DualPaddle => tr!("mode.dual_paddle"),
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::TimeAttack => "time_attack",
+            GameMode::Hardcore => "hardcore",
+            GameMode::Zen => "zen",
+            GameMode::Practice => "practice",
+            GameMode::DualPaddle => "dual_paddle",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameMode::Classic => tr!("mode.classic_description"),
+            GameMode::TimeAttack => tr!("mode.time_attack_description"),
+            GameMode::Hardcore => tr!("mode.hardcore_description"),
+            GameMode::Zen => tr!("mode.zen_description"),
+            GameMode::Practice => tr!("mode.practice_description"),
+            GameMode::DualPaddle => tr!("mode.dual_paddle_description"),
+        }
+    }
+
+    // Spare balls at the start of a run, given what the difficulty allows.
+    pub fn spare_lives(&self, default: usize) -> usize {
+        match self {
+            GameMode::Hardcore => 0,
+            _ => default,
+        }
+    }
+
+    pub fn score_multiplier(&self) -> u32 {
+        match self {
+            GameMode::Hardcore => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn ranked(&self) -> bool {
+        Self::RANKED.contains(self)
+    }
+
+    // Modes where a lost ball costs nothing and the run never ends.
+    pub fn endless(&self) -> bool {
+        matches!(self, GameMode::Zen | GameMode::Practice)
+    }
+
+    // A mirrored paddle guards the top of the screen, which loses the ball
+    // like the bottom does, and the bricks sit in the middle.
+    pub fn dual_paddle(&self) -> bool {
+        *self == GameMode::DualPaddle
+    }
+
+    // Time attack races the clock and practice plays one level over, so
+    // neither stops for bonus stages.
+    pub fn bonus_stages(&self) -> bool {
+        !matches!(self, GameMode::TimeAttack | GameMode::Practice)
+    }
+
+    // Whether a lost run can be picked up again from the level it ended on.
+    pub fn continues(&self) -> bool {
+        *self != GameMode::Hardcore
+    }
+}
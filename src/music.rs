@@ -0,0 +1,187 @@
+// Background music. Built without the `audio` feature nothing is loaded or
+// played, though scenes still say which track they want.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Track {
+    Menu,
+    Gameplay,
+    Boss,
+}
+
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+impl Track {
+    const ALL: [Track; 3] = [Track::Menu, Track::Gameplay, Track::Boss];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Track::Menu => "menu",
+            Track::Gameplay => "game",
+            Track::Boss => "boss",
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod imp {
+    use super::Track;
+    use raylib::prelude::*;
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    const MUSIC_DIR: &str = "assets/music";
+    const CROSSFADE_TIME: f32 = 1.0;
+    // How loud music plays under the pause menu.
+    const DUCKED_VOLUME: f32 = 0.3;
+
+    struct Stream<'a> {
+        track: Track,
+        music: Music<'a>,
+        // Current loudness from 0.0 to 1.0 before the volume setting.
+        gain: f32,
+    }
+
+    // Streams the looping track the current scene asks for, cross-fading when it
+    // changes. Tracks are `menu`, `game` and `boss` in `assets/music/`, in any
+    // format raylib can stream; missing ones are just silent.
+    pub struct Jukebox<'a> {
+        audio: Option<&'a RaylibAudio>,
+        streams: Vec<Stream<'a>>,
+    }
+
+    impl<'a> Jukebox<'a> {
+        pub fn load(audio: Option<&'a RaylibAudio>) -> Self {
+            let Some(audio) = audio else {
+                return Self {
+                    audio,
+                    streams: Vec::new(),
+                };
+            };
+            let files: Vec<_> = fs::read_dir(MUSIC_DIR)
+                .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+                .unwrap_or_default();
+            let streams = Track::ALL
+                .into_iter()
+                .filter_map(|track| {
+                    let path = files
+                        .iter()
+                        .find(|p| p.file_stem().is_some_and(|s| s == track.key()))?;
+                    load(audio, path).map(|music| Stream {
+                        track,
+                        music,
+                        gain: 0.0,
+                    })
+                })
+                .collect();
+            Self {
+                audio: Some(audio),
+                streams,
+            }
+        }
+
+        // Loads `path` again if it is one of the tracks. A track that was playing
+        // starts over at the same volume.
+        pub fn reload(&mut self, path: &Path) {
+            let Some(audio) = self.audio else {
+                return;
+            };
+            if path.parent() != Some(Path::new(MUSIC_DIR)) {
+                return;
+            }
+            let Some(track) = Track::ALL
+                .into_iter()
+                .find(|track| path.file_stem().is_some_and(|s| s == track.key()))
+            else {
+                return;
+            };
+            let gain = self
+                .streams
+                .iter()
+                .find(|s| s.track == track)
+                .map_or(0.0, |s| s.gain);
+            self.streams.retain(|s| s.track != track);
+            if path.exists() {
+                if let Some(music) = load(audio, path) {
+                    self.streams.push(Stream { track, music, gain });
+                }
+            }
+        }
+
+        // Fades `track` in and everything else out. `volume` runs from 0.0 to 1.0.
+        pub fn update(
+            &mut self,
+            duration: &Duration,
+            track: Option<Track>,
+            ducked: bool,
+            volume: f32,
+        ) {
+            let step = duration.as_secs_f32() / CROSSFADE_TIME;
+            for stream in self.streams.iter_mut() {
+                let target = match Some(stream.track) == track {
+                    true if ducked => DUCKED_VOLUME,
+                    true => 1.0,
+                    false => 0.0,
+                };
+                stream.gain = if stream.gain < target {
+                    (stream.gain + step).min(target)
+                } else {
+                    (stream.gain - step).max(target)
+                };
+
+                let playing = stream.music.is_stream_playing();
+                if stream.gain > 0.0 {
+                    // Tracks start over rather than resuming where they faded out.
+                    if !playing {
+                        stream.music.play_stream();
+                    }
+                    stream.music.set_volume(stream.gain * volume);
+                    stream.music.update_stream();
+                } else if playing {
+                    stream.music.stop_stream();
+                }
+            }
+        }
+    }
+
+    fn load<'a>(audio: &'a RaylibAudio, path: &Path) -> Option<Music<'a>> {
+        match audio.new_music(&path.to_string_lossy()) {
+            Ok(music) => {
+                log::info!("loaded {}", path.display());
+                Some(music)
+            }
+            Err(e) => {
+                log::warn!("could not load {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod imp {
+    use super::Track;
+    use raylib::prelude::RaylibAudio;
+    use std::marker::PhantomData;
+    use std::path::Path;
+    use std::time::Duration;
+
+    pub struct Jukebox<'a>(PhantomData<&'a RaylibAudio>);
+
+    impl<'a> Jukebox<'a> {
+        pub fn load(_audio: Option<&'a RaylibAudio>) -> Self {
+            Self(PhantomData)
+        }
+
+        pub fn reload(&mut self, _path: &Path) {}
+
+        pub fn update(
+            &mut self,
+            _duration: &Duration,
+            _track: Option<Track>,
+            _ducked: bool,
+            _volume: f32,
+        ) {
+        }
+    }
+}
+
+pub use imp::Jukebox;
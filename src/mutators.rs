@@ -0,0 +1,233 @@
+use crate::lang::tr;
+use std::fmt;
+use std::str::FromStr;
+
+// How far a rotating arena turns either way, in radians, and how many seconds
+// it takes to sway there and back.
+const ARENA_SWAY: f32 = 0.21;
+const ARENA_SWAY_PERIOD: f32 = 24.0;
+
+// House rules picked before a run. Unlike the chat's mutators they last the
+// whole run, and any of them can be combined. The game asks these hooks
+// instead of checking the flags itself.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Mutators {
+    pub big_ball: bool,
+    pub tiny_paddle: bool,
+    pub double_speed: bool,
+    pub no_speed_ups: bool,
+    pub invisible_paddle: bool,
+    pub free_paddle: bool,
+    pub micro_ball: bool,
+    pub rotating_arena: bool,
+}
+
+impl Mutators {
+    pub const COUNT: usize = 8;
+    // The letter each one has in a seed string, in the same order.
+    const LETTERS: [char; Self::COUNT] = ['B', 'T', 'D', 'N', 'I', 'F', 'M', 'R'];
+
+    fn flags(&self) -> [bool; Self::COUNT] {
+        [
+            self.big_ball,
+            self.tiny_paddle,
+            self.double_speed,
+            self.no_speed_ups,
+            self.invisible_paddle,
+            self.free_paddle,
+            self.micro_ball,
+            self.rotating_arena,
+        ]
+    }
+
+    fn from_flags(flags: [bool; Self::COUNT]) -> Self {
+        let [big_ball, tiny_paddle, double_speed, no_speed_ups, invisible_paddle, free_paddle, micro_ball, rotating_arena] =
+            flags;
+        Self {
+            big_ball,
+            tiny_paddle,
+            double_speed,
+            no_speed_ups,
+            invisible_paddle,
+            free_paddle,
+            micro_ball,
+            rotating_arena,
+        }
+    }
+
+    // One bit per mutator, for run codes.
+    pub fn bits(&self) -> u8 {
+        self.flags()
+            .into_iter()
+            .enumerate()
+            .fold(0, |bits, (i, on)| bits | (on as u8) << i)
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self::from_flags(std::array::from_fn(|i| bits & 1 << i != 0))
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn name(index: usize) -> &'static str {
+        let names: [&'static str; Self::COUNT] = [
+            tr!("mutator.big_ball"),
+            tr!("mutator.tiny_paddle"),
+            tr!("mutator.double_speed"),
+            tr!("mutator.no_speed_ups"),
+            tr!("mutator.invisible_paddle"),
+            tr!("mutator.free_paddle"),
+            tr!("mutator.micro_ball"),
+            tr!("mutator.rotating_arena"),
+        ];
+        names[index]
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.flags()[index]
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        let mut flags = self.flags();
+        flags[index] = !flags[index];
+        *self = Self::from_flags(flags);
+    }
+
+    // The ones that are on, for showing in menus.
+    pub fn names(&self) -> Vec<&'static str> {
+        (0..Self::COUNT)
+            .filter(|i| self.get(*i))
+            .map(Self::name)
+            .collect()
+    }
+
+    pub fn ball_radius(&self, radius: f32) -> f32 {
+        let radius = if self.big_ball { radius * 2.0 } else { radius };
+        if self.micro_ball {
+            radius * 0.5
+        } else {
+            radius
+        }
+    }
+
+    // A micro ball is hard to hit, so it scores double.
+    pub fn points(&self, points: u32) -> u32 {
+        if self.micro_ball {
+            points * 2
+        } else {
+            points
+        }
+    }
+
+    pub fn paddle_width(&self, width: f32) -> f32 {
+        if self.tiny_paddle {
+            width * 0.5
+        } else {
+            width
+        }
+    }
+
+    pub fn ball_speed(&self, speed: f32) -> f32 {
+        if self.double_speed {
+            speed * 2.0
+        } else {
+            speed
+        }
+    }
+
+    // How much of the usual speed-up a bounce adds.
+    pub fn speed_gain(&self, gain: f32) -> f32 {
+        if self.no_speed_ups {
+            0.0
+        } else {
+            gain
+        }
+    }
+
+    // How far above its usual height the paddle can be moved.
+    pub fn paddle_band(&self, band: f32) -> f32 {
+        if self.free_paddle {
+            band
+        } else {
+            0.0
+        }
+    }
+
+    // How far the arena is turned about the middle of the screen, in
+    // radians, `seconds` into the level. It sways back and forth rather than
+    // spinning all the way round, so the bricks never come down to the
+    // paddle.
+    pub fn arena_angle(&self, seconds: f32) -> f32 {
+        if self.rotating_arena {
+            ARENA_SWAY * (seconds * std::f32::consts::TAU / ARENA_SWAY_PERIOD).sin()
+        } else {
+            0.0
+        }
+    }
+
+    // The paddle still shows during the countdown, so it can be lined up.
+    pub fn paddle_visible(&self, countdown: bool) -> bool {
+        !self.invisible_paddle || countdown
+    }
+}
+
+// The letters of the mutators that are on, e.g. `BD` for a big ball at double
+// speed.
+impl fmt::Display for Mutators {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (on, letter) in self.flags().into_iter().zip(Self::LETTERS) {
+            if on {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mutators {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut flags = [false; Self::COUNT];
+        for c in s.chars() {
+            let index = Self::LETTERS
+                .iter()
+                .position(|l| l.eq_ignore_ascii_case(&c))
+                .ok_or_else(|| format!("unknown mutator '{}'", c))?;
+            flags[index] = true;
+        }
+        Ok(Self::from_flags(flags))
+    }
+}
+
+// What `--seed` takes and the summary shows: the seed of the random effects,
+// then a dash and the mutators if any are on, e.g. `1234-BT`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Seed {
+    pub value: u64,
+    pub mutators: Mutators,
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)?;
+        if !self.mutators.is_none() {
+            write!(f, "-{}", self.mutators)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (value, mutators) = s.split_once('-').unwrap_or((s, ""));
+        Ok(Self {
+            value: value.parse().map_err(|_| format!("bad seed '{}'", value))?,
+            mutators: mutators.parse()?,
+        })
+    }
+}
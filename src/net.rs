@@ -0,0 +1,267 @@
+// Versus races. Built without the `net` feature `--host` and `--join` say
+// so and quit.
+
+// Every packet starts with this so stray traffic is ignored.
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+const PROTOCOL: &str = "pong-versus-2";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    // In the lobby, ready to start.
+    Waiting,
+    Playing,
+    Cleared,
+    // Out of lives.
+    Out,
+}
+
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+impl Phase {
+    const ALL: [Phase; 4] = [Phase::Waiting, Phase::Playing, Phase::Cleared, Phase::Out];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Phase::Waiting => "waiting",
+            Phase::Playing => "playing",
+            Phase::Cleared => "cleared",
+            Phase::Out => "out",
+        }
+    }
+}
+
+// What each player tells the other about their run, ten times a second.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Status {
+    pub phase: Phase,
+    pub level: usize,
+    pub score: u32,
+    pub bricks: usize,
+    pub lives: usize,
+    // The seed the race is played with, which the host picks.
+    pub seed: u64,
+}
+
+impl Status {
+    pub fn waiting(level: usize, seed: u64) -> Self {
+        Self {
+            phase: Phase::Waiting,
+            level,
+            score: 0,
+            bricks: 0,
+            lives: 0,
+            seed,
+        }
+    }
+
+    #[cfg_attr(not(feature = "net"), allow(dead_code))]
+    fn encode(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {}",
+            PROTOCOL,
+            self.phase.key(),
+            self.level,
+            self.score,
+            self.bricks,
+            self.lives,
+            self.seed
+        )
+    }
+
+    #[cfg_attr(not(feature = "net"), allow(dead_code))]
+    fn decode(text: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        if words.next()? != PROTOCOL {
+            return None;
+        }
+        let phase = words.next()?;
+        let mut number = || words.next()?.parse::<u64>().ok();
+        Some(Self {
+            phase: Phase::ALL.into_iter().find(|p| p.key() == phase)?,
+            level: number()? as usize,
+            score: number()? as u32,
+            bricks: number()? as usize,
+            lives: number()? as usize,
+            seed: number()?,
+        })
+    }
+}
+
+#[cfg(feature = "net")]
+mod imp {
+    use super::Status;
+    use std::io;
+    use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+    use std::time::{Duration, Instant};
+
+    const SEND_INTERVAL: Duration = Duration::from_millis(100);
+    // The other player counts as gone after this long without a packet.
+    const TIMEOUT: Duration = Duration::from_secs(3);
+
+    // A connection to one other player over UDP. The host waits on a port and
+    // talks to whoever sends it a packet first; the other player joins by
+    // address. The host's level and seed go along with its status, so both
+    // play the same level with the same random effects. Inputs aren't
+    // exchanged: each player runs their own game, and a lost packet just means
+    // a slightly older score on screen.
+    pub struct Peer {
+        socket: UdpSocket,
+        remote: Option<SocketAddr>,
+        // The level and seed the host picked for the race.
+        host_level: Option<usize>,
+        host_seed: Option<u64>,
+        last_sent: Option<Instant>,
+        last_heard: Option<Instant>,
+        opponent: Option<Status>,
+    }
+
+    impl Peer {
+        pub fn host(port: u16, level: usize, seed: u64) -> io::Result<Self> {
+            Self::new(
+                UdpSocket::bind(("0.0.0.0", port))?,
+                None,
+                Some((level, seed)),
+            )
+        }
+
+        pub fn join(address: &str) -> io::Result<Self> {
+            let remote = address
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such address"))?;
+            Self::new(UdpSocket::bind(("0.0.0.0", 0))?, Some(remote), None)
+        }
+
+        fn new(
+            socket: UdpSocket,
+            remote: Option<SocketAddr>,
+            hosting: Option<(usize, u64)>,
+        ) -> io::Result<Self> {
+            socket.set_nonblocking(true)?;
+            Ok(Self {
+                socket,
+                remote,
+                host_level: hosting.map(|(level, _)| level),
+                host_seed: hosting.map(|(_, seed)| seed),
+                last_sent: None,
+                last_heard: None,
+                opponent: None,
+            })
+        }
+
+        pub fn host_level(&self) -> Option<usize> {
+            self.host_level
+        }
+
+        pub fn host_seed(&self) -> Option<u64> {
+            self.host_seed
+        }
+
+        // Where the other player is, or for the host before anyone has joined,
+        // the local address being listened on.
+        pub fn describe(&self) -> String {
+            match (self.remote, self.socket.local_addr()) {
+                (Some(remote), _) => remote.to_string(),
+                (None, Ok(local)) => format!("port {}", local.port()),
+                (None, Err(_)) => "an unknown port".into(),
+            }
+        }
+
+        // Sends `status` every SEND_INTERVAL and takes in whatever has arrived.
+        pub fn update(&mut self, status: &Status) {
+            let mut buffer = [0; 256];
+            loop {
+                match self.socket.recv_from(&mut buffer) {
+                    Ok((len, from)) => {
+                        if self.remote.is_some_and(|r| r != from) {
+                            continue;
+                        }
+                        let Some(received) = std::str::from_utf8(&buffer[..len])
+                            .ok()
+                            .and_then(Status::decode)
+                        else {
+                            log::debug!("ignoring a bad packet from {}", from);
+                            continue;
+                        };
+                        if self.last_heard.is_none() {
+                            log::info!("racing {}", from);
+                        }
+                        self.remote = Some(from);
+                        self.opponent = Some(received);
+                        self.last_heard = Some(Instant::now());
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    // Includes the "connection refused" a host that isn't up yet
+                    // causes on some systems; just keep trying.
+                    Err(e) => {
+                        log::debug!("could not receive: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            let (Some(remote), due) = (
+                self.remote,
+                self.last_sent.is_none_or(|t| t.elapsed() >= SEND_INTERVAL),
+            ) else {
+                return;
+            };
+            if due {
+                if let Err(e) = self.socket.send_to(status.encode().as_bytes(), remote) {
+                    log::debug!("could not send to {}: {}", remote, e);
+                }
+                self.last_sent = Some(Instant::now());
+            }
+        }
+
+        // The other player's latest status, or None if they haven't been heard
+        // from recently.
+        pub fn opponent(&self) -> Option<&Status> {
+            match self.last_heard {
+                Some(t) if t.elapsed() < TIMEOUT => self.opponent.as_ref(),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "net"))]
+mod imp {
+    use super::Status;
+    use std::io;
+
+    pub struct Peer;
+
+    impl Peer {
+        pub fn host(_port: u16, _level: usize, _seed: u64) -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub fn join(_address: &str) -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub fn host_level(&self) -> Option<usize> {
+            None
+        }
+
+        pub fn host_seed(&self) -> Option<u64> {
+            None
+        }
+
+        pub fn describe(&self) -> String {
+            String::new()
+        }
+
+        pub fn update(&mut self, _status: &Status) {}
+
+        pub fn opponent(&self) -> Option<&Status> {
+            None
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "built without the net feature")
+    }
+}
+
+pub use imp::Peer;
@@ -0,0 +1,42 @@
+use crate::screen::Canvas;
+use crate::text::{Align, Text};
+use crate::WINDOW_WIDTH;
+use raylib::prelude::*;
+use std::time::Duration;
+
+const NOTICE_TIME: f32 = 2.0;
+const FONT_SIZE: i32 = 20;
+
+// A short message in the top right corner, e.g. for hotkeys that have no
+// screen of their own.
+pub struct Notice {
+    current: Option<(String, f32)>,
+}
+
+impl Notice {
+    pub fn new() -> Self {
+        Self { current: None }
+    }
+
+    pub fn show(&mut self, message: String) {
+        self.current = Some((message, NOTICE_TIME));
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        if let Some((_, time)) = &mut self.current {
+            *time -= duration.as_secs_f32();
+            if *time <= 0.0 {
+                self.current = None;
+            }
+        }
+    }
+
+    pub fn render(&self, d: &mut Canvas, font: &ffi::Font) {
+        if let Some((message, _)) = &self.current {
+            Text::new(message, FONT_SIZE, Color::WHITE)
+                .align(Align::Right)
+                .outline(Color::BLACK)
+                .draw(d, font, WINDOW_WIDTH as i32 - 16, 16);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// A local WebSocket server for stream overlays. Browser sources connect to
+// `ws://localhost:<port>` and get the state of the run as a JSON text message
+// whenever it changes, and once straight after connecting. Built without the
+// `net` feature `--overlay-server` says so and the game carries on.
+use crate::json;
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+pub struct OverlayState {
+    pub mode: &'static str,
+    // What the game is doing: "countdown", "playing", "cleared", "won" or
+    // "game_over".
+    pub phase: &'static str,
+    pub level: usize,
+    pub level_name: String,
+    pub score: u32,
+    pub lives: usize,
+    pub combo: u32,
+    pub bricks: usize,
+}
+
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+impl OverlayState {
+    fn to_json(&self) -> String {
+        format!(
+            r#"{{"mode":"{}","phase":"{}","level":{},"level_name":{},"score":{},"lives":{},"combo":{},"bricks":{}}}"#,
+            self.mode,
+            self.phase,
+            self.level + 1,
+            json::string(&self.level_name),
+            self.score,
+            self.lives,
+            self.combo,
+            self.bricks
+        )
+    }
+}
+
+#[cfg(feature = "net")]
+mod imp {
+    use super::OverlayState;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    // Appended to the client's key in the handshake, as RFC 6455 says.
+    const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    // A client that can't take a message this quickly is dropped rather than
+    // holding the game up.
+    const WRITE_TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[derive(Default)]
+    struct Shared {
+        clients: Vec<TcpStream>,
+        // The latest message, for clients that connect later.
+        last: Option<String>,
+    }
+
+    pub struct OverlayServer {
+        shared: Arc<Mutex<Shared>>,
+        last: Option<OverlayState>,
+    }
+
+    impl OverlayServer {
+        // Only listens on the loopback interface; overlays run on the same
+        // machine as the game.
+        pub fn start(port: u16) -> io::Result<Self> {
+            let listener = TcpListener::bind(("127.0.0.1", port))?;
+            let shared = Arc::new(Mutex::new(Shared::default()));
+            let accepted = Arc::clone(&shared);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let shared = Arc::clone(&accepted);
+                    // The handshake gets its own thread so a slow client can't
+                    // keep others from connecting.
+                    thread::spawn(move || match handshake(stream) {
+                        Ok(mut stream) => {
+                            log::info!("an overlay connected");
+                            let mut shared = shared.lock().unwrap();
+                            let greeting = shared.last.clone();
+                            if greeting.is_none_or(|m| send(&mut stream, &m).is_ok()) {
+                                shared.clients.push(stream);
+                            }
+                        }
+                        Err(e) => log::debug!("turned away an overlay: {}", e),
+                    });
+                }
+            });
+            Ok(Self { shared, last: None })
+        }
+
+        // Sends the state to every overlay if it changed.
+        pub fn publish(&mut self, state: OverlayState) {
+            if self.last.as_ref() == Some(&state) {
+                return;
+            }
+            let message = state.to_json();
+            self.last = Some(state);
+            let mut shared = self.shared.lock().unwrap();
+            shared.clients.retain_mut(|c| {
+                send(c, &message)
+                    .map_err(|e| log::debug!("an overlay went away: {}", e))
+                    .is_ok()
+            });
+            shared.last = Some(message);
+        }
+    }
+
+    // Reads the HTTP upgrade request and answers it. Anything the client sends
+    // afterwards is ignored.
+    fn handshake(stream: TcpStream) -> io::Result<TcpStream> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut key = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                    key = Some(value.trim().to_string());
+                }
+            }
+        }
+        let mut stream = stream;
+        let Some(key) = key else {
+            stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")?;
+            return Err(io::ErrorKind::InvalidData.into());
+        };
+        let accept = base64(&sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()));
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        )?;
+        Ok(stream)
+    }
+
+    // One unmasked text frame; servers never mask.
+    fn send(stream: &mut TcpStream, message: &str) -> io::Result<()> {
+        let payload = message.as_bytes();
+        let mut frame = vec![0x81];
+        match payload.len() {
+            n @ 0..=125 => frame.push(n as u8),
+            n @ 126..=0xffff => {
+                frame.push(126);
+                frame.extend((n as u16).to_be_bytes());
+            }
+            n => {
+                frame.push(127);
+                frame.extend((n as u64).to_be_bytes());
+            }
+        }
+        frame.extend(payload);
+        stream.write_all(&frame)
+    }
+
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+        let mut message = data.to_vec();
+        message.push(0x80);
+        while message.len() % 64 != 56 {
+            message.push(0);
+        }
+        message.extend((data.len() as u64 * 8).to_be_bytes());
+        for chunk in message.chunks(64) {
+            let mut w = [0u32; 80];
+            for (i, word) in chunk.chunks(4).enumerate() {
+                w[i] = u32::from_be_bytes(word.try_into().unwrap());
+            }
+            for i in 16..80 {
+                w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+            }
+            let [mut a, mut b, mut c, mut d, mut e] = h;
+            for (i, word) in w.iter().enumerate() {
+                let (f, k) = match i {
+                    0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                    20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                    40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                    _ => (b ^ c ^ d, 0xCA62C1D6),
+                };
+                let t = a
+                    .rotate_left(5)
+                    .wrapping_add(f)
+                    .wrapping_add(e)
+                    .wrapping_add(k)
+                    .wrapping_add(*word);
+                e = d;
+                d = c;
+                c = b.rotate_left(30);
+                b = a;
+                a = t;
+            }
+            for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+                *h = h.wrapping_add(v);
+            }
+        }
+        let mut out = [0; 20];
+        for (i, v) in h.iter().enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&v.to_be_bytes());
+        }
+        out
+    }
+
+    fn base64(data: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in data.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(not(feature = "net"))]
+mod imp {
+    use super::OverlayState;
+    use std::io;
+
+    pub struct OverlayServer;
+
+    impl OverlayServer {
+        pub fn start(_port: u16) -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "built without the net feature",
+            ))
+        }
+
+        pub fn publish(&mut self, _state: OverlayState) {}
+    }
+}
+
+pub use imp::OverlayServer;
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+// No step the game is simulated with is longer than this, however long the
+// frame really took.
+const MAX_STEP: Duration = Duration::from_millis(50);
+const SAMPLES: usize = 4;
+
+// Turns the measured frame times into the steps the game is simulated with.
+// A stall (dragging the window, a breakpoint, the OS getting busy) is clamped
+// so the ball can't jump through the bricks, and the last few frames are
+// averaged so a single slow one doesn't make everything lurch.
+pub struct FramePacer {
+    samples: [Duration; SAMPLES],
+    next: usize,
+    count: usize,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self {
+            samples: [Duration::ZERO; SAMPLES],
+            next: 0,
+            count: 0,
+        }
+    }
+
+    pub fn step(&mut self, frame: &Duration) -> Duration {
+        self.samples[self.next] = (*frame).min(MAX_STEP);
+        self.next = (self.next + 1) % SAMPLES;
+        self.count = (self.count + 1).min(SAMPLES);
+        self.samples[..self.count].iter().sum::<Duration>() / self.count as u32
+    }
+}
@@ -0,0 +1,86 @@
+use crate::pool::Pool;
+use crate::rng::Rng;
+use raylib::prelude::*;
+use std::time::Duration;
+
+const MAX_PARTICLES: usize = 1024;
+const GRAVITY: f32 = 900.0;
+
+#[derive(Default)]
+struct Particle {
+    position: Vector2,
+    velocity: Vector2,
+    color: Color,
+    size: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+// How a burst of particles looks; `color` is usually filled in per event.
+#[derive(Clone, Copy)]
+pub struct Burst {
+    pub count: usize,
+    pub speed: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    pub color: Color,
+}
+
+pub struct Particles {
+    pool: Pool<Particle>,
+    rng: Rng,
+}
+
+impl Particles {
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            pool: Pool::new(MAX_PARTICLES),
+            rng,
+        }
+    }
+
+    // Spawns a burst from random points inside `area`, flying in random directions.
+    pub fn burst(&mut self, area: impl Into<Rectangle>, burst: &Burst) {
+        let area = area.into();
+        for _ in 0..burst.count {
+            let angle = self.rng.range(0.0, std::f32::consts::TAU);
+            let speed = self.rng.range(0.3, 1.0) * burst.speed;
+            let particle = Particle {
+                position: Vector2::new(
+                    area.x + self.rng.range(0.0, area.width),
+                    area.y + self.rng.range(0.0, area.height),
+                ),
+                velocity: Vector2::new(angle.cos() * speed, angle.sin() * speed),
+                color: burst.color,
+                size: burst.size * self.rng.range(0.5, 1.0),
+                age: 0.0,
+                lifetime: burst.lifetime * self.rng.range(0.6, 1.0),
+            };
+            match self.pool.spawn() {
+                Some(slot) => *slot = particle,
+                None => return,
+            }
+        }
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        let dt = duration.as_secs_f32();
+        self.pool.retain(|p| {
+            p.age += dt;
+            p.velocity.y += GRAVITY * dt;
+            p.position += p.velocity * dt;
+            p.age < p.lifetime
+        });
+    }
+
+    pub fn render(&self, d: &mut impl RaylibDraw) {
+        for p in self.pool.iter() {
+            let fade = 1.0 - p.age / p.lifetime;
+            d.draw_rectangle_v(
+                p.position - Vector2::new(p.size / 2.0, p.size / 2.0),
+                Vector2::new(p.size, p.size),
+                p.color.alpha(fade),
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Once, OnceLock};
+
+const APP_DIR: &str = "pong";
+
+// Where the settings file goes: `$XDG_CONFIG_HOME/pong` on Linux,
+// `%APPDATA%\pong` on Windows and `~/Library/Application Support/pong` on
+// macOS. Falls back to the working directory if none of those can be found.
+pub fn config_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let base = if cfg!(test) {
+            Some(test_dir())
+        } else if cfg!(target_os = "linux") {
+            env_dir("XDG_CONFIG_HOME").or_else(|| home(".config"))
+        } else {
+            platform_dir()
+        };
+        create(base)
+    })
+}
+
+// Where progress, scores, replays and screenshots go: `$XDG_DATA_HOME/pong`
+// on Linux and the same place as the settings elsewhere.
+pub fn data_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let base = if cfg!(test) {
+            Some(test_dir())
+        } else if cfg!(target_os = "linux") {
+            env_dir("XDG_DATA_HOME").or_else(|| home(".local/share"))
+        } else {
+            platform_dir()
+        };
+        create(base)
+    })
+}
+
+// A file in `dir`. Older versions kept everything in the working directory,
+// so a file found there is moved over the first time.
+pub fn file(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    let old = Path::new(name);
+    if !cfg!(test) && !path.exists() && old.exists() && path != old {
+        if let Err(e) = fs::rename(old, &path).or_else(|_| fs::copy(old, &path).map(|_| ())) {
+            log::warn!("could not move {} to {}: {}", name, path.display(), e);
+            return old.to_path_buf();
+        }
+    }
+    path
+}
+
+// A directory in the data directory, created if needed.
+pub fn data_subdir(name: &str) -> PathBuf {
+    let path = data_dir().join(name);
+    if let Err(e) = fs::create_dir_all(&path) {
+        log::warn!("could not create {}: {}", path.display(), e);
+    }
+    path
+}
+
+// The tests save into a directory of their own, so they don't touch the
+// player's files. It is removed when the test run exits, passed or failed.
+fn test_dir() -> PathBuf {
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+    }
+    extern "C" fn remove() {
+        let _ = fs::remove_dir_all(test_base());
+    }
+    static CLEANUP: Once = Once::new();
+    CLEANUP.call_once(|| {
+        if unsafe { atexit(remove) } != 0 {
+            log::warn!("{} will be left behind", test_base().display());
+        }
+    });
+    test_base()
+}
+
+fn test_base() -> PathBuf {
+    env::temp_dir().join(format!("pong-test-{}", process::id()))
+}
+
+fn platform_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        home("Library/Application Support")
+    } else {
+        None
+    }
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+fn home(rest: &str) -> Option<PathBuf> {
+    env_dir("HOME").map(|h| h.join(rest))
+}
+
+fn create(base: Option<PathBuf>) -> PathBuf {
+    let Some(dir) = base.map(|b| b.join(APP_DIR)) else {
+        return PathBuf::from(".");
+    };
+    match fs::create_dir_all(&dir) {
+        Ok(()) => dir,
+        Err(e) => {
+            log::warn!("could not create {}: {}", dir.display(), e);
+            PathBuf::from(".")
+        }
+    }
+}
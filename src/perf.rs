@@ -0,0 +1,116 @@
+// The F3 frame stats. Built without the `devtools` feature F3 does nothing.
+
+#[cfg(feature = "devtools")]
+mod imp {
+    use crate::screen::Canvas;
+    use crate::WINDOW_HEIGHT;
+    use raylib::prelude::*;
+    use std::time::Duration;
+
+    const SAMPLES: usize = 60;
+    const FONT_SIZE: i32 = 20;
+
+    // Rolling average over the last SAMPLES values.
+    struct Average {
+        samples: [f32; SAMPLES],
+        next: usize,
+        count: usize,
+    }
+
+    impl Average {
+        fn new() -> Self {
+            Self {
+                samples: [0.0; SAMPLES],
+                next: 0,
+                count: 0,
+            }
+        }
+
+        fn push(&mut self, value: f32) {
+            self.samples[self.next] = value;
+            self.next = (self.next + 1) % SAMPLES;
+            self.count = (self.count + 1).min(SAMPLES);
+        }
+
+        fn get(&self) -> f32 {
+            if self.count == 0 {
+                return 0.0;
+            }
+            self.samples[..self.count].iter().sum::<f32>() / self.count as f32
+        }
+    }
+
+    // Frame timing shown in the bottom left corner, toggled with F3.
+    pub struct PerfOverlay {
+        pub visible: bool,
+        frame: Average,
+        update: Average,
+        render: Average,
+    }
+
+    impl PerfOverlay {
+        pub fn new() -> Self {
+            Self {
+                visible: false,
+                frame: Average::new(),
+                update: Average::new(),
+                render: Average::new(),
+            }
+        }
+
+        // Times are recorded in milliseconds: the whole frame, the scene update
+        // (input and physics) and drawing including the buffer swap.
+        pub fn record(&mut self, frame: &Duration, update: &Duration, render: &Duration) {
+            self.frame.push(frame.as_secs_f32() * 1000.0);
+            self.update.push(update.as_secs_f32() * 1000.0);
+            self.render.push(render.as_secs_f32() * 1000.0);
+        }
+
+        pub fn render(&self, d: &mut Canvas) {
+            if !self.visible {
+                return;
+            }
+            let frame = self.frame.get();
+            let fps = if frame > 0.0 { 1000.0 / frame } else { 0.0 };
+            let lines = [
+                format!("FPS {:.0} ({:.2} ms)", fps, frame),
+                format!("update {:.2} ms", self.update.get()),
+                format!("render {:.2} ms", self.render.get()),
+            ];
+            let height = lines.len() as i32 * (FONT_SIZE + 4) + 8;
+            let top = WINDOW_HEIGHT as i32 - height - 10;
+            d.draw_rectangle(10, top, 240, height, Color::BLACK.alpha(0.6));
+            for (i, line) in lines.iter().enumerate() {
+                d.draw_text(
+                    line,
+                    18,
+                    top + 6 + i as i32 * (FONT_SIZE + 4),
+                    FONT_SIZE,
+                    Color::LIME,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "devtools"))]
+mod imp {
+    use crate::screen::Canvas;
+    use std::time::Duration;
+
+    pub struct PerfOverlay {
+        pub visible: bool,
+    }
+
+    impl PerfOverlay {
+        pub fn new() -> Self {
+            Self { visible: false }
+        }
+
+        pub fn record(&mut self, _frame: &Duration, _update: &Duration, _render: &Duration) {}
+
+        pub fn render(&self, _d: &mut Canvas) {}
+    }
+}
+
+pub use imp::PerfOverlay;
@@ -0,0 +1,239 @@
+// The collision maths of the game, kept free of raylib calls (only its plain
+// Rectangle and Vector2 types) so the benchmarks can build it on its own.
+use raylib::ffi::Rectangle;
+use raylib::prelude::Vector2;
+
+// The area shared by `a` and `b`, or an empty rectangle if they don't touch.
+pub fn overlap(a: Rectangle, b: Rectangle) -> Rectangle {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    Rectangle {
+        x,
+        y,
+        width: ((a.x + a.width).min(b.x + b.width) - x).max(0.0),
+        height: ((a.y + a.height).min(b.y + b.height) - y).max(0.0),
+    }
+}
+
+pub struct BrickHit {
+    pub index: usize,
+    pub overlap: Rectangle,
+}
+
+impl BrickHit {
+    pub fn contact(&self) -> Vector2 {
+        Vector2::new(
+            self.overlap.x + self.overlap.width / 2.0,
+            self.overlap.y + self.overlap.height / 2.0,
+        )
+    }
+}
+
+// The first brick the ball overlaps. Only one brick is hit per step.
+pub fn first_hit(ball: Rectangle, bricks: impl IntoIterator<Item = Rectangle>) -> Option<BrickHit> {
+    bricks.into_iter().enumerate().find_map(|(index, brick)| {
+        let overlap = overlap(ball, brick);
+        (overlap.width * overlap.height > 0.0).then_some(BrickHit { index, overlap })
+    })
+}
+
+// Bounces `direction` off a brick centred on `centre`: a wide overlap means
+// the ball came through the top or bottom, a tall one through a side, and a
+// square one a corner, which reverses both. Returns the normal of the bounce,
+// pointing from the brick towards the ball at `ball`.
+pub fn reflect(ball: Vector2, centre: Vector2, hit: &BrickHit, direction: &mut Vector2) -> Vector2 {
+    let side = Vector2::new((ball.x - centre.x).signum(), (ball.y - centre.y).signum());
+    let overlap = hit.overlap;
+    if overlap.width > overlap.height {
+        direction.y *= -1.0;
+        Vector2::new(0.0, side.y)
+    } else if overlap.width < overlap.height {
+        direction.x *= -1.0;
+        Vector2::new(side.x, 0.0)
+    } else {
+        direction.y *= -1.0;
+        direction.x *= -1.0;
+        side.normalized()
+    }
+}
+
+// How far to move `ball` out of `brick` along `normal` as `reflect` returned
+// it, so the two only touch.
+pub fn separate(ball: Rectangle, brick: Rectangle, normal: Vector2) -> Vector2 {
+    let push = |n: f32, ball: f32, ball_size: f32, brick: f32, brick_size: f32| {
+        if n > 0.0 {
+            brick + brick_size - ball
+        } else if n < 0.0 {
+            brick - (ball + ball_size)
+        } else {
+            0.0
+        }
+    };
+    Vector2::new(
+        push(normal.x, ball.x, ball.width, brick.x, brick.width),
+        push(normal.y, ball.y, ball.height, brick.y, brick.height),
+    )
+}
+
+// The pushes and pulls on the ball over one step, added up first and then
+// applied together, so the order they are found in doesn't matter.
+#[derive(Default)]
+pub struct Forces {
+    total: Vector2,
+}
+
+impl Forces {
+    pub fn add(&mut self, force: Vector2) {
+        self.total += force;
+    }
+
+    // A pull towards `centre` from anywhere within `radius` of it, strongest
+    // close up and fading to nothing at the edge.
+    pub fn attract(&mut self, body: Vector2, centre: Vector2, radius: f32, strength: f32) {
+        let offset = centre - body;
+        let distance = offset.length();
+        if distance > 0.0 && distance < radius {
+            self.total += offset / distance * (strength * (1.0 - distance / radius));
+        }
+    }
+
+    // Turns `direction` by the forces over `seconds`, for a body moving at
+    // `speed`. Its length is kept, so forces bend the path without speeding
+    // the body up or stalling it.
+    pub fn steer(&self, direction: &mut Vector2, speed: f32, seconds: f32) {
+        let length = direction.length();
+        if length == 0.0 {
+            return;
+        }
+        let velocity = *direction / length * speed + self.total * seconds;
+        if velocity.length() > 0.0 {
+            *direction = velocity.normalized() * length;
+        }
+    }
+}
+
+// A rectangle turned `angle` radians clockwise about its centre.
+pub struct OrientedRect {
+    pub centre: Vector2,
+    pub half_size: Vector2,
+    pub angle: f32,
+}
+
+impl OrientedRect {
+    // From the rectangle's own axes to the screen's.
+    fn to_world(&self, v: Vector2) -> Vector2 {
+        let (sin, cos) = self.angle.sin_cos();
+        Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+    }
+
+    fn to_local(&self, v: Vector2) -> Vector2 {
+        let (sin, cos) = self.angle.sin_cos();
+        Vector2::new(v.x * cos + v.y * sin, -v.x * sin + v.y * cos)
+    }
+}
+
+pub struct Contact {
+    pub point: Vector2,
+    // Points out of the rectangle towards the circle.
+    pub normal: Vector2,
+}
+
+// Where a circle touches an oriented rectangle, found from the point of the
+// rectangle closest to the circle's centre. A centre already inside counts as
+// touching the top face, since that is the side the ball comes from.
+pub fn circle_contact(centre: Vector2, radius: f32, rect: &OrientedRect) -> Option<Contact> {
+    let local = rect.to_local(centre - rect.centre);
+    let (w, h) = (rect.half_size.x, rect.half_size.y);
+    let closest = Vector2::new(local.x.clamp(-w, w), local.y.clamp(-h, h));
+    let offset = local - closest;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        Vector2::new(0.0, -1.0)
+    };
+    Some(Contact {
+        point: rect.centre + rect.to_world(closest),
+        normal: rect.to_world(normal),
+    })
+}
+
+// A straight wall from `a` to `b`, which the ball bounces off from either
+// side.
+#[derive(Clone, Copy)]
+pub struct Segment {
+    pub a: Vector2,
+    pub b: Vector2,
+}
+
+// Where a circle touches a segment, found from the point of the segment
+// closest to its centre. The normal points from there towards the circle, so
+// walls can face any way.
+pub fn segment_contact(centre: Vector2, radius: f32, segment: &Segment) -> Option<Contact> {
+    let along = segment.b - segment.a;
+    let length = along.dot(along);
+    let t = if length > 0.0 {
+        ((centre - segment.a).dot(along) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let closest = segment.a + along * t;
+    let offset = centre - closest;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        // Dead on the line: face whichever side is up, to the left of a
+        // wall running left to right.
+        Vector2::new(along.y, -along.x).normalized()
+    };
+    Some(Contact {
+        point: closest,
+        normal,
+    })
+}
+
+// Where a circle at `a` touches one at `b`, on the surface of `b`; the normal
+// points from `b` towards `a`. Centres in the same place count as touching on
+// top, as in `circle_contact`.
+pub fn circles_contact(a: Vector2, a_radius: f32, b: Vector2, b_radius: f32) -> Option<Contact> {
+    let offset = a - b;
+    let distance = offset.length();
+    if distance >= a_radius + b_radius {
+        return None;
+    }
+    let normal = if distance > 0.0 {
+        offset / distance
+    } else {
+        Vector2::new(0.0, -1.0)
+    };
+    Some(Contact {
+        point: b + normal * b_radius,
+        normal,
+    })
+}
+
+// Mirrors `direction` about a surface with `normal`, unless it is already
+// moving away from it. Returns whether it bounced.
+pub fn bounce(direction: &mut Vector2, normal: Vector2) -> bool {
+    let along = direction.dot(normal);
+    if along >= 0.0 {
+        return false;
+    }
+    *direction -= normal * (2.0 * along);
+    true
+}
+
+// Turns `direction` upwards so it is at most `max_angle` radians off straight
+// up, keeping its length.
+pub fn clamp_upwards(direction: &mut Vector2, max_angle: f32) {
+    let length = direction.length();
+    let angle = direction.x.atan2(-direction.y).clamp(-max_angle, max_angle);
+    *direction = Vector2::new(angle.sin(), -angle.cos()) * length;
+}
@@ -0,0 +1,66 @@
+use crate::game::{draw_ghost, format_time, PROJ_RADIUS};
+use crate::ghost::Ghost;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::draw_centered;
+use crate::ui;
+use crate::WINDOW_HEIGHT;
+use raylib::prelude::*;
+use std::time::Duration;
+
+// A recorded clear, the paddle and ball of a ghost file, played back from
+// `--replay`. The ghost doesn't record the bricks, so the field is empty.
+// Confirm plays it again from the start and Back leaves.
+pub struct Playback {
+    name: String,
+    ghost: Ghost,
+    elapsed: Duration,
+}
+
+impl Playback {
+    pub fn new(name: String, ghost: Ghost) -> Self {
+        Self {
+            name,
+            ghost,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl Scene for Playback {
+    fn update(&mut self, _ctx: &mut Context, input: &Input, duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        if input.pressed(Action::Confirm) {
+            self.elapsed = Duration::ZERO;
+        }
+        self.elapsed = (self.elapsed + *duration).min(self.ghost.time());
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let theme = ctx.theme();
+        let font = ctx.font();
+        d.clear_background(theme.background);
+        ui::heading(d, font, &self.name, 60);
+        draw_centered(
+            d,
+            font,
+            &format!(
+                "{} / {}",
+                format_time(&self.elapsed),
+                format_time(&self.ghost.time())
+            ),
+            120,
+            30,
+            theme.text,
+        );
+        if let Some(frame) = self.ghost.at(&self.elapsed) {
+            draw_ghost(d, theme, &frame, PROJ_RADIUS, 1.0);
+        }
+        ui::hint(d, font, tr!("playback.hint"), WINDOW_HEIGHT as i32 - 30);
+    }
+}
@@ -0,0 +1,105 @@
+// The interface content packs are written against. A plugin is a dynamic
+// library from its own crate, which depends on this one as a library:
+//
+//     [lib]
+//     crate-type = ["dylib"]
+//
+//     [dependencies]
+//     pong = { git = "https://github.com/osamasalem/pong" }
+//
+// and exports its plugin with `pong::export_plugin!(MyPlugin)`. Builds of the
+// game with the `plugins` feature load every library in the plugins folder of
+// the data directory at start-up and let each one register its content.
+//
+// Plugins are Rust code running in the game's process, so they must be built
+// with the same compiler as the game and are trusted as much as the game is.
+// The API sticks to plain types so plugins don't link a second copy of raylib.
+
+// Bumped whenever the trait or the registry change; libraries built for
+// another version are skipped.
+pub const API_VERSION: u32 = 1;
+
+pub const MAX_BRICK_HP: usize = 5;
+
+// A brick with its own look and worth, placed in level grids by `symbol`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrickType {
+    pub symbol: char,
+    pub hp: usize,
+    pub points: u32,
+    // Red, green, blue and alpha.
+    pub color: [u8; 4],
+}
+
+// Everything the plugins have registered.
+#[derive(Default)]
+pub struct Registry {
+    brick_types: Vec<BrickType>,
+    levels: Vec<String>,
+    scripts: Vec<(String, String)>,
+}
+
+impl Registry {
+    // Digits, `.`, `#`, `@` and `*` already mean something in level files,
+    // and a symbol can only be taken once.
+    pub fn brick_type(&mut self, brick: BrickType) -> Result<(), String> {
+        if brick.symbol.is_ascii_digit()
+            || matches!(brick.symbol, '.' | '#' | '@' | '*')
+            || brick.symbol.is_whitespace()
+        {
+            return Err(format!("'{}' can't be a brick symbol", brick.symbol));
+        }
+        if !(1..=MAX_BRICK_HP).contains(&brick.hp) {
+            return Err(format!("brick hp goes from 1 to {}", MAX_BRICK_HP));
+        }
+        if self.brick_types.iter().any(|b| b.symbol == brick.symbol) {
+            return Err(format!("'{}' is already a brick type", brick.symbol));
+        }
+        self.brick_types.push(brick);
+        Ok(())
+    }
+
+    // A level in the `.lvl` format, played after the built-in ones.
+    pub fn level(&mut self, text: &str) {
+        self.levels.push(text.to_string());
+    }
+
+    // A mod script (see the README), for power-ups and other behaviour.
+    pub fn script(&mut self, name: &str, text: &str) {
+        self.scripts.push((name.to_string(), text.to_string()));
+    }
+
+    pub fn brick_types(&self) -> &[BrickType] {
+        &self.brick_types
+    }
+
+    pub fn levels(&self) -> &[String] {
+        &self.levels
+    }
+
+    pub fn scripts(&self) -> &[(String, String)] {
+        &self.scripts
+    }
+}
+
+pub trait PongPlugin {
+    fn name(&self) -> &str;
+    fn register(&self, registry: &mut Registry);
+}
+
+// The function the game looks up in a plugin library.
+pub type Entry = fn() -> Box<dyn PongPlugin>;
+
+// Exports the plugin `$plugin` builds, with the API version it was built for.
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:expr) => {
+        #[no_mangle]
+        pub static PONG_PLUGIN_API: u32 = $crate::plugin::API_VERSION;
+
+        #[no_mangle]
+        pub fn pong_plugin() -> Box<dyn $crate::plugin::PongPlugin> {
+            Box::new($plugin)
+        }
+    };
+}
@@ -0,0 +1,65 @@
+// Loads the plugins in the plugins folder of the data directory, in file name
+// order; see `pong::plugin` for writing one. Without the `plugins` feature
+// nothing is loaded.
+use pong::plugin::Registry;
+
+#[cfg(feature = "plugins")]
+pub fn load() -> Registry {
+    use crate::paths;
+    use std::fs;
+
+    let mut registry = Registry::default();
+    let Ok(entries) = fs::read_dir(paths::data_dir().join("plugins")) else {
+        return registry;
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    files.sort();
+    for path in files {
+        // Safety: loading a library runs its initialisers, and nothing can
+        // check that it really is a plugin for this build. Plugins are
+        // trusted like the game itself.
+        match unsafe { open(&path) } {
+            Ok(plugin) => {
+                plugin.register(&mut registry);
+                log::info!("loaded plugin {}", plugin.name());
+            }
+            Err(e) => log::warn!("could not load {}: {}", path.display(), e),
+        }
+    }
+    registry
+}
+
+// The library stays loaded for the rest of the program, since the plugin's
+// code lives in it.
+#[cfg(feature = "plugins")]
+unsafe fn open(path: &std::path::Path) -> Result<Box<dyn pong::plugin::PongPlugin>, String> {
+    use pong::plugin::{Entry, API_VERSION};
+
+    let library = libloading::Library::new(path).map_err(|e| e.to_string())?;
+    let version = *library
+        .get::<*const u32>(b"PONG_PLUGIN_API\0")
+        .map_err(|_| "not a pong plugin")?;
+    if *version != API_VERSION {
+        return Err(format!(
+            "built for plugin API {}, this game has {}",
+            *version, API_VERSION
+        ));
+    }
+    let entry = *library
+        .get::<Entry>(b"pong_plugin\0")
+        .map_err(|e| e.to_string())?;
+    let plugin = entry();
+    std::mem::forget(library);
+    Ok(plugin)
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn load() -> Registry {
+    Registry::default()
+}
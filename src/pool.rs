@@ -0,0 +1,60 @@
+// A fixed-capacity pool for short-lived things like particles and popups.
+// Live items are kept packed at the front; dead ones are swapped behind them
+// and handed out again by `spawn`, so once the pool has grown to its busiest
+// size nothing is allocated or freed while playing. Items that own memory,
+// such as a popup's text, keep it between uses.
+pub struct Pool<T> {
+    items: Vec<T>,
+    live: usize,
+    // The most items there can be. The Vec may have been given room for
+    // more than asked, so its own capacity doesn't say.
+    capacity: usize,
+}
+
+impl<T: Default> Pool<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+            live: 0,
+            capacity,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.live == self.capacity
+    }
+
+    // A slot for a new item, still holding whatever its last user left in it,
+    // or None when the pool is full. Callers overwrite every field.
+    pub fn spawn(&mut self) -> Option<&mut T> {
+        if self.is_full() {
+            return None;
+        }
+        if self.live == self.items.len() {
+            self.items.push(T::default());
+        }
+        self.live += 1;
+        Some(&mut self.items[self.live - 1])
+    }
+
+    // Keeps the items `f` returns true for. Order is not preserved.
+    pub fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut i = 0;
+        while i < self.live {
+            if f(&mut self.items[i]) {
+                i += 1;
+            } else {
+                self.live -= 1;
+                self.items.swap(i, self.live);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.live = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items[..self.live].iter()
+    }
+}
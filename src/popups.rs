@@ -0,0 +1,74 @@
+use crate::pool::Pool;
+use crate::text::{Align, Text};
+use raylib::prelude::*;
+use std::fmt::{self, Write};
+use std::time::Duration;
+
+const LIFETIME: f32 = 0.8;
+const RISE_SPEED: f32 = 70.0;
+const MAX_POPUPS: usize = 64;
+
+#[derive(Default)]
+struct Popup {
+    text: String,
+    position: Vector2,
+    size: i32,
+    color: Color,
+    age: f32,
+}
+
+// Short-lived text in world space, such as the points for a destroyed brick.
+// Each popup drifts upwards and fades out.
+pub struct Popups {
+    pool: Pool<Popup>,
+}
+
+impl Popups {
+    pub fn new() -> Self {
+        Self {
+            pool: Pool::new(MAX_POPUPS),
+        }
+    }
+
+    // `position` is where the centre of the text starts. The text is written
+    // into the buffer of an old popup, so spawning doesn't allocate.
+    pub fn spawn(&mut self, text: fmt::Arguments, position: Vector2, size: i32, color: Color) {
+        let Some(popup) = self.pool.spawn() else {
+            return;
+        };
+        popup.text.clear();
+        let _ = popup.text.write_fmt(text);
+        popup.position = position;
+        popup.size = size;
+        popup.color = color;
+        popup.age = 0.0;
+    }
+
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        let dt = duration.as_secs_f32();
+        self.pool.retain(|popup| {
+            popup.age += dt;
+            popup.position.y -= RISE_SPEED * dt;
+            popup.age < LIFETIME
+        });
+    }
+
+    pub fn render(&self, d: &mut impl RaylibDraw, font: &ffi::Font) {
+        for popup in self.pool.iter() {
+            let alpha = 1.0 - popup.age / LIFETIME;
+            Text::new(&popup.text, popup.size, popup.color.alpha(alpha))
+                .align(Align::Center)
+                .outline(Color::BLACK.alpha(alpha))
+                .draw(
+                    d,
+                    font,
+                    popup.position.x as i32,
+                    (popup.position.y - popup.size as f32 / 2.0) as i32,
+                );
+        }
+    }
+}
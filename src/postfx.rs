@@ -0,0 +1,128 @@
+use crate::error::Error;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effect {
+    Bloom,
+    Chromatic,
+    Crt,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 3] = [Effect::Bloom, Effect::Chromatic, Effect::Crt];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Effect::Bloom => "bloom",
+            Effect::Chromatic => "chromatic",
+            Effect::Crt => "crt",
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Effect::Bloom => include_str!("../shaders/bloom.fs"),
+            Effect::Chromatic => include_str!("../shaders/chromatic.fs"),
+            Effect::Crt => include_str!("../shaders/crt.fs"),
+        }
+    }
+}
+
+// Browsers only run GLSL ES 1.00, so for the web build the desktop shaders
+// are rewritten into it.
+fn shader_source(effect: &Effect) -> String {
+    let source = effect.source();
+    if !cfg!(target_os = "emscripten") {
+        return source.to_string();
+    }
+    source
+        .replace("#version 330", "#version 100\nprecision mediump float;")
+        .replace("in vec", "varying vec")
+        .replace("out vec4 finalColor;", "")
+        .replace("finalColor", "gl_FragColor")
+        .replace("texture(", "texture2D(")
+}
+
+// Runs the finished frame through a chain of full screen shaders. Every
+// effect but the last renders into one of two scratch textures in turn; the
+// last is applied while scaling into the window, so effects that bend the
+// picture such as the CRT work on the visible area.
+pub struct PostFx {
+    shaders: Vec<Shader>,
+    buffers: [RenderTexture2D; 2],
+}
+
+impl PostFx {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<Self, Error> {
+        let mut buffer = || {
+            let target = rl
+                .load_render_texture(thread, WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32)
+                .map_err(|_| Error::RenderTexture("post-processing"))?;
+            target.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+            Ok(target)
+        };
+        let buffers = [buffer()?, buffer()?];
+        Ok(Self {
+            shaders: Effect::ALL
+                .iter()
+                .map(|e| rl.load_shader_from_memory(thread, None, Some(&shader_source(e))))
+                .collect(),
+            buffers,
+        })
+    }
+
+    fn shader(&self, effect: Effect) -> &Shader {
+        &self.shaders[effect as usize]
+    }
+
+    pub fn present(
+        &mut self,
+        d: &mut RaylibDrawHandle,
+        thread: &RaylibThread,
+        frame: &RenderTexture2D,
+        effects: &[Effect],
+        viewport: Rectangle,
+    ) {
+        let Some((last, passes)) = effects.split_last() else {
+            d.clear_background(Color::BLACK);
+            blit(d, frame, viewport);
+            return;
+        };
+
+        let logical = Rectangle::new(0.0, 0.0, WINDOW_WIDTH, WINDOW_HEIGHT);
+        let mut current = None;
+        for (i, effect) in passes.iter().enumerate() {
+            let [first, second] = &mut self.buffers;
+            let (target, previous) = if i % 2 == 0 {
+                (first, &*second)
+            } else {
+                (second, &*first)
+            };
+            let source = if current.is_some() { previous } else { frame };
+            let shader = &self.shaders[*effect as usize];
+            let mut d = d.begin_texture_mode(thread, target);
+            d.clear_background(Color::BLACK);
+            let mut d = d.begin_shader_mode(shader);
+            blit(&mut d, source, logical);
+            current = Some(i % 2);
+        }
+
+        let source = current.map_or(frame, |i| &self.buffers[i]);
+        d.clear_background(Color::BLACK);
+        let mut d = d.begin_shader_mode(self.shader(*last));
+        blit(&mut d, source, viewport);
+    }
+}
+
+// Render textures are stored upside down, hence the negative source height.
+fn blit(d: &mut impl RaylibDraw, texture: &RenderTexture2D, dest: Rectangle) {
+    d.draw_texture_pro(
+        texture,
+        Rectangle::new(0.0, 0.0, WINDOW_WIDTH, -WINDOW_HEIGHT),
+        dest,
+        Vector2::zero(),
+        0.0,
+        Color::WHITE,
+    );
+}
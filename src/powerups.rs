@@ -0,0 +1,364 @@
+use crate::config::{Table, Value, Writer};
+use crate::console::{arg, Commands};
+use crate::lang::tr;
+use crate::physics::{self, OrientedRect};
+use crate::pool::Pool;
+use crate::rng::Rng;
+use crate::text::{Align, Text};
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+
+// One brick in this many drops a capsule when it breaks.
+const DROP_CHANCE: f32 = 0.12;
+const FALL_SPEED: f32 = 160.0;
+// How quickly a capsule blown off course by the wind goes back to falling
+// straight down, per second.
+const CAPSULE_DRAG: f32 = 2.0;
+const CAPSULE_WIDTH: f32 = 40.0;
+const CAPSULE_HEIGHT: f32 = 18.0;
+const CAPSULE_FONT_SIZE: i32 = 16;
+// More capsules than this at once and new ones aren't dropped.
+const MAX_CAPSULES: usize = 64;
+// How long a collected power-up lasts, in seconds.
+const EFFECT_TIME: f32 = 12.0;
+// A heavy ball's hit points per brick, and how much of its bounce off the
+// bricks it breaks it keeps.
+const HEAVY_DAMAGE: usize = 3;
+const HEAVY_DEFLECTION: f32 = 0.15;
+const ACTIVE_FONT_SIZE: i32 = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUp {
+    // A ball that is easy to hit.
+    Mega,
+    // A ball that is hard to hit, but worth double.
+    Micro,
+    // A ball that smashes through bricks.
+    Heavy,
+}
+
+impl PowerUp {
+    const ALL: [PowerUp; 3] = [PowerUp::Mega, PowerUp::Micro, PowerUp::Heavy];
+
+    // What it is called in save files and console commands.
+    pub fn key(&self) -> &'static str {
+        match self {
+            PowerUp::Mega => "mega",
+            PowerUp::Micro => "micro",
+            PowerUp::Heavy => "heavy",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.key() == key)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUp::Mega => tr!("powerup.mega"),
+            PowerUp::Micro => tr!("powerup.micro"),
+            PowerUp::Heavy => tr!("powerup.heavy"),
+        }
+    }
+
+    fn letter(&self) -> &'static str {
+        match self {
+            PowerUp::Mega => "M",
+            PowerUp::Micro => "m",
+            PowerUp::Heavy => "H",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUp::Mega => Color::new(90, 200, 255, 255),
+            PowerUp::Micro => Color::new(255, 200, 60, 255),
+            PowerUp::Heavy => Color::new(150, 150, 165, 255),
+        }
+    }
+
+    // Power-ups that can't be active at the same time as this one.
+    fn cancels(&self, other: PowerUp) -> bool {
+        matches!(
+            (self, other),
+            (PowerUp::Mega, PowerUp::Micro) | (PowerUp::Micro, PowerUp::Mega)
+        )
+    }
+}
+
+// A power-up falling from a broken brick, caught with the paddle.
+pub struct Capsule {
+    pub kind: PowerUp,
+    pub position: Vector2,
+    pub velocity: Vector2,
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self {
+            kind: PowerUp::Mega,
+            position: Vector2::zero(),
+            velocity: Vector2::zero(),
+        }
+    }
+}
+
+// The capsules on screen and the effects running, with the random numbers
+// that decide the drops.
+pub struct PowerUps {
+    falling: Pool<Capsule>,
+    active: Vec<(PowerUp, f32)>,
+    rng: Rng,
+}
+
+impl PowerUps {
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            falling: Pool::new(MAX_CAPSULES),
+            active: Vec::new(),
+            rng,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.falling.clear();
+        self.active.clear();
+    }
+
+    // The drop generator goes in `[powerups]`, then each falling capsule in
+    // `[capsule1]`, `[capsule2]`, ... and each running effect in `[effect1]`,
+    // `[effect2]`, ...
+    pub fn write(&self, writer: &mut Writer) {
+        writer
+            .section("powerups")
+            .value("rng", Value::Int(self.rng.state() as i64));
+        for (i, capsule) in self.falling.iter().enumerate() {
+            writer
+                .section(&format!("capsule{}", i + 1))
+                .value("kind", Value::Str(capsule.kind.key().into()))
+                .value("x", Value::Float(capsule.position.x as f64))
+                .value("y", Value::Float(capsule.position.y as f64))
+                .value("velocity_x", Value::Float(capsule.velocity.x as f64))
+                .value("velocity_y", Value::Float(capsule.velocity.y as f64));
+        }
+        for (i, (kind, left)) in self.active.iter().enumerate() {
+            writer
+                .section(&format!("effect{}", i + 1))
+                .value("kind", Value::Str(kind.key().into()))
+                .value("left", Value::Float(*left as f64));
+        }
+    }
+
+    // What `write` wrote, with `rng` for files that don't say where the
+    // generator was.
+    pub fn from_table(table: &Table, rng: Rng) -> Result<Self, String> {
+        let float = |key: String| {
+            table
+                .get_float(&key)
+                .map(|x| x as f32)
+                .ok_or_else(|| format!("snapshot has no {}", key))
+        };
+        let kind = |section: &str| {
+            let key = table.get_str(&format!("{}.kind", section))?;
+            Some(
+                PowerUp::from_key(key)
+                    .ok_or_else(|| format!("{} has no power-up called '{}'", section, key)),
+            )
+        };
+        let mut falling = Pool::new(MAX_CAPSULES);
+        for i in 1.. {
+            let section = format!("capsule{}", i);
+            let Some(kind) = kind(&section) else {
+                break;
+            };
+            let capsule = Capsule {
+                kind: kind?,
+                position: Vector2::new(
+                    float(format!("{}.x", section))?,
+                    float(format!("{}.y", section))?,
+                ),
+                velocity: Vector2::new(
+                    float(format!("{}.velocity_x", section))?,
+                    float(format!("{}.velocity_y", section))?,
+                ),
+            };
+            // Like a drop, one that doesn't fit is left out.
+            if let Some(slot) = falling.spawn() {
+                *slot = capsule;
+            }
+        }
+        let mut active = Vec::new();
+        loop {
+            let section = format!("effect{}", active.len() + 1);
+            let Some(kind) = kind(&section) else {
+                break;
+            };
+            active.push((kind?, float(format!("{}.left", section))?));
+        }
+        Ok(Self {
+            falling,
+            active,
+            rng: table
+                .get_int("powerups.rng")
+                .map_or(rng, |state| Rng::resume(state as u64)),
+        })
+    }
+
+    // Maybe drops a capsule where a brick broke.
+    pub fn drop_at(&mut self, position: Vector2) {
+        if self.rng.next_f32() >= DROP_CHANCE {
+            return;
+        }
+        self.send(position);
+    }
+
+    // Drops a capsule of a random power-up at `position`, whatever the chance.
+    pub fn send(&mut self, position: Vector2) {
+        // The kind is picked even when there is no room, so the drops
+        // after it come out the same either way.
+        let index = (self.rng.next_f32() * PowerUp::ALL.len() as f32) as usize;
+        if let Some(capsule) = self.falling.spawn() {
+            *capsule = Capsule {
+                kind: PowerUp::ALL[index.min(PowerUp::ALL.len() - 1)],
+                position,
+                velocity: Vector2::new(0.0, FALL_SPEED),
+            };
+        }
+    }
+
+    // Moves the capsules, pushed by whatever `wind` blows where they are, and
+    // counts down the effects. Returns the power-ups the paddle caught, which
+    // are active from now on.
+    pub fn update(
+        &mut self,
+        seconds: f32,
+        paddle: &OrientedRect,
+        wind: impl Fn(Vector2) -> Vector2,
+    ) -> Vec<PowerUp> {
+        for effect in &mut self.active {
+            effect.1 -= seconds;
+        }
+        self.active.retain(|(_, left)| *left > 0.0);
+
+        let mut caught = Vec::new();
+        self.falling.retain(|capsule| {
+            let fall = Vector2::new(0.0, FALL_SPEED);
+            capsule.velocity +=
+                (wind(capsule.position) + (fall - capsule.velocity) * CAPSULE_DRAG) * seconds;
+            capsule.position += capsule.velocity * seconds;
+            capsule.position.x = capsule
+                .position
+                .x
+                .clamp(CAPSULE_WIDTH / 2.0, WINDOW_WIDTH - CAPSULE_WIDTH / 2.0);
+            let hit = physics::circle_contact(capsule.position, CAPSULE_HEIGHT / 2.0, paddle);
+            if hit.is_some() {
+                caught.push(capsule.kind);
+            }
+            hit.is_none()
+                && (-CAPSULE_HEIGHT..WINDOW_HEIGHT + CAPSULE_HEIGHT).contains(&capsule.position.y)
+        });
+        for kind in &caught {
+            self.activate(*kind);
+        }
+        caught
+    }
+
+    // Starts the effect over and ends the ones it can't run alongside.
+    // The console commands for power-ups.
+    pub fn commands() -> Commands<PowerUps> {
+        Commands::<PowerUps>::new().register(
+            "give",
+            "give <mega|micro|heavy>",
+            |powerups, _, args| {
+                let key: String = arg(args, 0, "power-up")?;
+                let kind = PowerUp::from_key(&key).ok_or(format!("bad power-up '{}'", key))?;
+                powerups.activate(kind);
+                Ok(format!("gave {}", kind.name()))
+            },
+        )
+    }
+
+    pub fn activate(&mut self, kind: PowerUp) {
+        self.active
+            .retain(|(active, _)| *active != kind && !kind.cancels(*active));
+        self.active.push((kind, EFFECT_TIME));
+    }
+
+    fn is_active(&self, kind: PowerUp) -> bool {
+        self.active.iter().any(|(active, _)| *active == kind)
+    }
+
+    pub fn ball_radius(&self, radius: f32) -> f32 {
+        if self.is_active(PowerUp::Mega) {
+            radius * 1.75
+        } else if self.is_active(PowerUp::Micro) {
+            radius * 0.5
+        } else {
+            radius
+        }
+    }
+
+    pub fn ball_damage(&self, damage: usize) -> usize {
+        if self.is_active(PowerUp::Heavy) {
+            damage * HEAVY_DAMAGE
+        } else {
+            damage
+        }
+    }
+
+    pub fn ball_deflection(&self, deflection: f32) -> f32 {
+        if self.is_active(PowerUp::Heavy) {
+            deflection * HEAVY_DEFLECTION
+        } else {
+            deflection
+        }
+    }
+
+    pub fn points(&self, points: u32) -> u32 {
+        if self.is_active(PowerUp::Micro) {
+            points * 2
+        } else {
+            points
+        }
+    }
+
+    // The falling capsules, drawn in the playfield.
+    pub fn render(&self, d: &mut impl RaylibDraw, font: &ffi::Font) {
+        for capsule in self.falling.iter() {
+            let rect = Rectangle {
+                x: capsule.position.x - CAPSULE_WIDTH / 2.0,
+                y: capsule.position.y - CAPSULE_HEIGHT / 2.0,
+                width: CAPSULE_WIDTH,
+                height: CAPSULE_HEIGHT,
+            };
+            d.draw_rectangle_rounded(rect, 1.0, 8, capsule.kind.color());
+            Text::new(capsule.kind.letter(), CAPSULE_FONT_SIZE, Color::BLACK)
+                .align(Align::Center)
+                .draw(
+                    d,
+                    font,
+                    capsule.position.x as i32,
+                    (capsule.position.y - CAPSULE_FONT_SIZE as f32 / 2.0) as i32,
+                );
+        }
+    }
+
+    // The effects running and the seconds they have left, in the bottom
+    // right corner.
+    pub fn render_active(&self, d: &mut impl RaylibDraw, font: &ffi::Font) {
+        for (i, (kind, left)) in self.active.iter().enumerate() {
+            Text::new(
+                &tr!("powerup.left", kind.name(), left.ceil()),
+                ACTIVE_FONT_SIZE,
+                kind.color(),
+            )
+            .align(Align::Right)
+            .draw(
+                d,
+                font,
+                WINDOW_WIDTH as i32 - 16,
+                WINDOW_HEIGHT as i32 - 36 - i as i32 * (ACTIVE_FONT_SIZE + 4),
+            );
+        }
+    }
+}
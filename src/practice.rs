@@ -0,0 +1,42 @@
+use crate::level::LEVELS_DIR;
+use crate::paths;
+use crate::watch::Watcher;
+use raylib::prelude::Vector2;
+use std::time::Duration;
+
+// Where the layouts exported from practice go, in the data directory.
+pub const LAYOUTS_DIR: &str = "layouts";
+// How far the ball moves each time it is stepped while frozen.
+pub const STEP: Duration = Duration::from_micros(16_667);
+
+// Where the ball comes back after it is lost, and how it is moving.
+#[derive(Clone, Copy)]
+pub struct Spawn {
+    pub position: Vector2,
+    pub direction: Vector2,
+    pub speed: f32,
+}
+
+// The tools practice mode adds on top of a normal game: freezing and stepping
+// the ball, a marked respawn point, and levels in the data directory reloading
+// when their files change.
+pub struct Practice {
+    pub frozen: bool,
+    pub spawn: Option<Spawn>,
+    watcher: Option<Watcher>,
+}
+
+impl Practice {
+    pub fn new() -> Self {
+        Self {
+            frozen: false,
+            spawn: None,
+            watcher: (!cfg!(target_os = "emscripten"))
+                .then(|| Watcher::spawn(paths::data_dir().join(LEVELS_DIR))),
+        }
+    }
+
+    pub fn levels_changed(&self) -> bool {
+        self.watcher.iter().any(|w| !w.changed().is_empty())
+    }
+}
@@ -0,0 +1,257 @@
+// What the player is doing, shown on their Discord profile. Built with
+// `--features discord`, the activity is sent to the Discord client over its
+// local IPC socket; without it, or with the setting switched off, nothing is
+// sent. The application id Discord shows the activity under is taken from
+// `PONG_DISCORD_APP_ID` at build time.
+use crate::lang::tr;
+use std::time::Duration;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(not(feature = "discord"), allow(dead_code))]
+pub struct Activity {
+    // The first line, which restarts the elapsed time whenever it changes.
+    pub details: String,
+    pub state: String,
+    // How long the activity has been going on already.
+    pub elapsed: Duration,
+}
+
+impl Activity {
+    pub fn menus() -> Self {
+        Self {
+            details: tr!("presence.menus").into(),
+            state: String::new(),
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(feature = "discord")]
+mod imp {
+    use super::Activity;
+    use crate::json;
+    use std::io::{self, Read, Write};
+    use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+    use std::thread;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    const APPLICATION_ID: Option<&str> = option_env!("PONG_DISCORD_APP_ID");
+    // Discord drops updates sent faster than about one every four seconds.
+    const MIN_INTERVAL: Duration = Duration::from_secs(4);
+    // How often to look for a Discord client that wasn't running.
+    const RETRY: Duration = Duration::from_secs(15);
+
+    const OP_HANDSHAKE: u32 = 0;
+    const OP_FRAME: u32 = 1;
+
+    // What the worker shows, with the elapsed time turned into a start time.
+    #[derive(Clone, PartialEq)]
+    struct Shown {
+        details: String,
+        state: String,
+        start: u64,
+    }
+
+    pub struct Presence {
+        sender: Option<Sender<Option<Shown>>>,
+        last: Option<Shown>,
+    }
+
+    impl Presence {
+        pub fn new() -> Self {
+            let Some(application_id) = APPLICATION_ID else {
+                log::warn!("built without PONG_DISCORD_APP_ID, Discord status is off");
+                return Self {
+                    sender: None,
+                    last: None,
+                };
+            };
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || run(application_id, receiver));
+            Self {
+                sender: Some(sender),
+                last: None,
+            }
+        }
+
+        // None clears the activity. Only changes are passed on.
+        pub fn update(&mut self, activity: Option<Activity>) {
+            let Some(sender) = &self.sender else {
+                return;
+            };
+            let shown = activity.map(|activity| {
+                let start = match &self.last {
+                    Some(last) if last.details == activity.details => last.start,
+                    _ => SystemTime::now()
+                        .checked_sub(activity.elapsed)
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map_or(0, |d| d.as_secs()),
+                };
+                Shown {
+                    details: activity.details,
+                    state: activity.state,
+                    start,
+                }
+            });
+            if shown != self.last {
+                let _ = sender.send(shown.clone());
+                self.last = shown;
+            }
+        }
+    }
+
+    // The worker keeps only the newest activity, so a burst of score changes
+    // turns into one update.
+    fn run(application_id: &str, receiver: Receiver<Option<Shown>>) {
+        let mut connection: Option<Connection> = None;
+        let mut last_attempt: Option<Instant> = None;
+        let mut wanted = None;
+        let mut sent = None;
+        loop {
+            match receiver.recv_timeout(RETRY) {
+                Ok(shown) => wanted = Some(shown),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            if let Some(newest) = receiver.try_iter().last() {
+                wanted = Some(newest);
+            }
+            if wanted.is_none() || wanted == sent {
+                continue;
+            }
+            if connection.is_none() && last_attempt.is_none_or(|t| t.elapsed() >= RETRY) {
+                last_attempt = Some(Instant::now());
+                connection = Connection::open(application_id)
+                    .map_err(|e| log::debug!("could not reach Discord: {}", e))
+                    .ok();
+            }
+            let Some(conn) = &mut connection else {
+                continue;
+            };
+            match conn.set_activity(wanted.as_ref().unwrap().as_ref()) {
+                Ok(()) => sent = wanted.clone(),
+                Err(e) => {
+                    log::debug!("lost Discord: {}", e);
+                    connection = None;
+                    sent = None;
+                }
+            }
+            thread::sleep(MIN_INTERVAL);
+        }
+    }
+
+    trait Pipe: Read + Write + Send {}
+    impl<T: Read + Write + Send> Pipe for T {}
+
+    struct Connection {
+        pipe: Box<dyn Pipe>,
+        nonce: u64,
+    }
+
+    impl Connection {
+        fn open(application_id: &str) -> io::Result<Self> {
+            let mut conn = Self {
+                pipe: connect()?,
+                nonce: 0,
+            };
+            conn.send(
+                OP_HANDSHAKE,
+                &format!(r#"{{"v":1,"client_id":{}}}"#, json::string(application_id)),
+            )?;
+            Ok(conn)
+        }
+
+        fn set_activity(&mut self, shown: Option<&Shown>) -> io::Result<()> {
+            let activity = match shown {
+                None => "null".to_string(),
+                Some(shown) if shown.state.is_empty() => format!(
+                    r#"{{"details":{},"timestamps":{{"start":{}}}}}"#,
+                    json::string(&shown.details),
+                    shown.start
+                ),
+                Some(shown) => format!(
+                    r#"{{"details":{},"state":{},"timestamps":{{"start":{}}}}}"#,
+                    json::string(&shown.details),
+                    json::string(&shown.state),
+                    shown.start
+                ),
+            };
+            self.nonce += 1;
+            let payload = format!(
+                r#"{{"cmd":"SET_ACTIVITY","args":{{"pid":{},"activity":{}}},"nonce":"{}"}}"#,
+                std::process::id(),
+                activity,
+                self.nonce
+            );
+            self.send(OP_FRAME, &payload)
+        }
+
+        // Every message gets an answer, which has to be read so the socket
+        // doesn't fill up. Its contents don't matter.
+        fn send(&mut self, op: u32, payload: &str) -> io::Result<()> {
+            let mut frame = Vec::with_capacity(8 + payload.len());
+            frame.extend(op.to_le_bytes());
+            frame.extend((payload.len() as u32).to_le_bytes());
+            frame.extend(payload.as_bytes());
+            self.pipe.write_all(&frame)?;
+
+            let mut header = [0; 8];
+            self.pipe.read_exact(&mut header)?;
+            let length = u32::from_le_bytes(header[4..].try_into().unwrap());
+            io::copy(&mut (&mut self.pipe).take(length as u64), &mut io::sink())?;
+            Ok(())
+        }
+    }
+
+    // Discord listens on the first free one of `discord-ipc-0` to `-9`.
+    #[cfg(unix)]
+    fn connect() -> io::Result<Box<dyn Pipe>> {
+        use std::os::unix::net::UnixStream;
+        use std::path::PathBuf;
+        let dir = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+            .iter()
+            .find_map(std::env::var_os)
+            .map_or(PathBuf::from("/tmp"), PathBuf::from);
+        for i in 0..10 {
+            if let Ok(stream) = UnixStream::connect(dir.join(format!("discord-ipc-{}", i))) {
+                stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+                return Ok(Box::new(stream));
+            }
+        }
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    #[cfg(windows)]
+    fn connect() -> io::Result<Box<dyn Pipe>> {
+        use std::fs::OpenOptions;
+        for i in 0..10 {
+            let path = format!(r"\\?\pipe\discord-ipc-{}", i);
+            if let Ok(pipe) = OpenOptions::new().read(true).write(true).open(path) {
+                return Ok(Box::new(pipe));
+            }
+        }
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn connect() -> io::Result<Box<dyn Pipe>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+#[cfg(not(feature = "discord"))]
+mod imp {
+    use super::Activity;
+
+    pub struct Presence;
+
+    impl Presence {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn update(&mut self, _activity: Option<Activity>) {}
+    }
+}
+
+pub use imp::Presence;
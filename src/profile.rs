@@ -0,0 +1,90 @@
+// Timing of the main phases of a frame, for finding out where a slow frame
+// went. Built with `--features profiling`, every `scope` records a span and
+// `finish` writes them all to `trace.json` in the Chrome trace format, which
+// chrome://tracing and https://ui.perfetto.dev can open. Without the feature
+// everything here compiles to nothing.
+
+#[cfg(feature = "profiling")]
+mod imp {
+    use crate::paths;
+    use std::fs;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Instant;
+
+    // About half an hour of frames; later spans are dropped.
+    const MAX_SPANS: usize = 1_000_000;
+
+    struct Span {
+        name: &'static str,
+        start: f64,
+        duration: f64,
+    }
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    static SPANS: Mutex<Vec<Span>> = Mutex::new(Vec::new());
+
+    fn micros(instant: Instant) -> f64 {
+        (instant - *START.get_or_init(Instant::now)).as_secs_f64() * 1e6
+    }
+
+    pub struct Scope {
+        name: &'static str,
+        start: Instant,
+    }
+
+    pub fn scope(name: &'static str) -> Scope {
+        START.get_or_init(Instant::now);
+        Scope {
+            name,
+            start: Instant::now(),
+        }
+    }
+
+    impl Drop for Scope {
+        fn drop(&mut self) {
+            let mut spans = SPANS.lock().unwrap();
+            if spans.len() < MAX_SPANS {
+                spans.push(Span {
+                    name: self.name,
+                    start: micros(self.start),
+                    duration: self.start.elapsed().as_secs_f64() * 1e6,
+                });
+            }
+        }
+    }
+
+    pub fn finish() {
+        let spans = SPANS.lock().unwrap();
+        let events: Vec<String> = spans
+            .iter()
+            .map(|s| {
+                format!(
+                    r#"{{"name":"{}","ph":"X","ts":{:.1},"dur":{:.1},"pid":1,"tid":1}}"#,
+                    s.name, s.start, s.duration
+                )
+            })
+            .collect();
+        let path = paths::data_dir().join("trace.json");
+        let json = format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"));
+        match fs::write(&path, json) {
+            Ok(()) => eprintln!("profile written to {}", path.display()),
+            Err(e) => log::error!("could not write {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+mod imp {
+    pub struct Scope;
+
+    #[inline(always)]
+    pub fn scope(_name: &'static str) -> Scope {
+        Scope
+    }
+
+    pub fn finish() {}
+}
+
+// Times everything until the returned guard is dropped, so bind it to a
+// named variable: `let _scope = profile::scope("physics");`.
+pub use imp::{finish, scope};
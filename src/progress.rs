@@ -0,0 +1,80 @@
+use crate::config::{Table, Value, Writer};
+use crate::paths;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const PROGRESS_FILE: &str = "progress.toml";
+
+#[derive(Clone, Default)]
+pub struct LevelRecord {
+    pub cleared: bool,
+    pub best_score: Option<u32>,
+    pub best_time: Option<Duration>,
+}
+
+pub struct Progress {
+    path: PathBuf,
+    levels: Vec<LevelRecord>,
+}
+
+impl Progress {
+    pub fn load(level_count: usize) -> Self {
+        let path = paths::file(paths::data_dir(), PROGRESS_FILE);
+        let table = fs::read_to_string(&path)
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let levels = (0..level_count)
+            .map(|i| {
+                let section = format!("level{}", i + 1);
+                LevelRecord {
+                    cleared: table
+                        .get_bool(&format!("{}.cleared", section))
+                        .unwrap_or(false),
+                    best_score: table
+                        .get_int(&format!("{}.best_score", section))
+                        .map(|s| s as u32),
+                    best_time: table
+                        .get_int(&format!("{}.best_time_ms", section))
+                        .map(|ms| Duration::from_millis(ms as u64)),
+                }
+            })
+            .collect();
+        Self { path, levels }
+    }
+
+    pub fn get(&self, level: usize) -> &LevelRecord {
+        &self.levels[level]
+    }
+
+    // The first level is always open; every other one needs its predecessor cleared.
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level == 0 || self.levels[level - 1].cleared
+    }
+
+    pub fn record(&mut self, level: usize, score: u32, time: Duration) {
+        let record = &mut self.levels[level];
+        record.cleared = true;
+        record.best_score = Some(record.best_score.map_or(score, |s| s.max(score)));
+        record.best_time = Some(record.best_time.map_or(time, |t| t.min(time)));
+        self.save();
+    }
+
+    fn save(&self) {
+        let mut writer = Writer::default();
+        for (i, record) in self.levels.iter().enumerate() {
+            writer
+                .section(&format!("level{}", i + 1))
+                .value("cleared", Value::Bool(record.cleared));
+            if let Some(score) = record.best_score {
+                writer.value("best_score", Value::Int(score as i64));
+            }
+            if let Some(time) = record.best_time {
+                writer.value("best_time_ms", Value::Int(time.as_millis() as i64));
+            }
+        }
+        if let Err(e) = fs::write(&self.path, writer.finish()) {
+            log::error!("could not write {}: {}", self.path.display(), e);
+        }
+    }
+}
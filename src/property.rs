@@ -0,0 +1,52 @@
+// Property tests without a framework: a property is tried against many cases
+// drawn from the game's own generator, and a failing case is reported with
+// the seed that made it, so `PONG_SEED=<seed> cargo test <name>` runs just
+// that case again.
+use crate::rng::Rng;
+use raylib::prelude::Vector2;
+use std::env;
+use std::f32::consts::TAU;
+
+mod physics;
+
+const CASES: u64 = 256;
+
+// Fails the property with a message unless `cond` holds.
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)+) => {
+        let holds: bool = $cond;
+        if !holds {
+            return Err(format!($($arg)+));
+        }
+    };
+}
+pub(crate) use ensure;
+
+// Runs `property` once per case, each with a generator of its own.
+pub fn check(name: &str, property: impl Fn(&mut Rng) -> Result<(), String>) {
+    let seeds = match env::var("PONG_SEED").ok().and_then(|s| s.parse().ok()) {
+        Some(seed) => seed..seed + 1,
+        None => 0..CASES,
+    };
+    for seed in seeds {
+        if let Err(e) = property(&mut Rng::new(seed)) {
+            panic!("{} failed with PONG_SEED={}: {}", name, seed, e);
+        }
+    }
+}
+
+// A direction of `length` pointing anywhere.
+pub fn direction(rng: &mut Rng, length: f32) -> Vector2 {
+    let angle = rng.range(0.0, TAU);
+    Vector2::new(angle.cos(), angle.sin()) * length
+}
+
+// A whole number from `0` up to but not including `n`.
+pub fn below(rng: &mut Rng, n: usize) -> usize {
+    (rng.next_u64() % n as u64) as usize
+}
+
+// Whether `a` and `b` are equal but for rounding.
+pub fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= 1e-4 * a.abs().max(b.abs()).max(1.0)
+}
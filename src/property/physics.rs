@@ -0,0 +1,172 @@
+use crate::physics::*;
+use crate::property::{self, below, close, ensure};
+use crate::rng::Rng;
+use raylib::ffi::Rectangle;
+use raylib::prelude::Vector2;
+
+// A brick somewhere on the screen, from a sliver to a wide slab.
+fn brick(rng: &mut Rng) -> Rectangle {
+    Rectangle {
+        x: rng.range(0.0, 1200.0),
+        y: rng.range(0.0, 600.0),
+        width: rng.range(20.0, 200.0),
+        height: rng.range(10.0, 40.0),
+    }
+}
+
+fn centre(rect: Rectangle) -> Vector2 {
+    Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0)
+}
+
+#[test]
+fn bouncing_keeps_the_speed() {
+    property::check("bouncing_keeps_the_speed", |rng| {
+        let length = rng.range(0.1, 4.0);
+        let mut direction = property::direction(rng, length);
+        let normal = property::direction(rng, 1.0);
+        let before = direction;
+        let bounced = bounce(&mut direction, normal);
+        ensure!(
+            close(direction.length(), before.length()),
+            "{:?} off {:?} became {:?}",
+            before,
+            normal,
+            direction
+        );
+        ensure!(
+            bounced == (before.dot(normal) < 0.0),
+            "{:?} off {:?} bounced: {}",
+            before,
+            normal,
+            bounced
+        );
+        ensure!(
+            direction.dot(normal) >= -1e-4,
+            "{:?} off {:?} still heads into the surface as {:?}",
+            before,
+            normal,
+            direction
+        );
+        Ok(())
+    });
+}
+
+#[test]
+fn clamping_upwards_keeps_the_speed() {
+    property::check("clamping_upwards_keeps_the_speed", |rng| {
+        let length = rng.range(0.1, 4.0);
+        let mut direction = property::direction(rng, length);
+        let max_angle = rng.range(0.1, 1.5);
+        let before = direction;
+        clamp_upwards(&mut direction, max_angle);
+        ensure!(
+            close(direction.length(), before.length()),
+            "{:?} became {:?}",
+            before,
+            direction
+        );
+        let angle = direction.x.atan2(-direction.y);
+        ensure!(
+            angle.abs() <= max_angle + 1e-4,
+            "{:?} is {} radians off straight up, more than {}",
+            direction,
+            angle,
+            max_angle
+        );
+        Ok(())
+    });
+}
+
+#[test]
+fn forces_keep_the_speed() {
+    property::check("forces_keep_the_speed", |rng| {
+        let mut forces = Forces::default();
+        let body = Vector2::new(rng.range(0.0, 1280.0), rng.range(0.0, 720.0));
+        for _ in 0..below(rng, 4) {
+            let strength = rng.range(0.0, 400.0);
+            forces.add(property::direction(rng, strength));
+        }
+        for _ in 0..below(rng, 4) {
+            let distance = rng.range(0.0, 200.0);
+            let centre = body + property::direction(rng, distance);
+            forces.attract(body, centre, 160.0, rng.range(0.0, 1000.0));
+        }
+        let mut direction = property::direction(rng, 2.0f32.sqrt());
+        let before = direction;
+        forces.steer(&mut direction, rng.range(100.0, 1500.0), 1.0 / 60.0);
+        ensure!(
+            close(direction.length(), before.length()),
+            "{:?} became {:?}",
+            before,
+            direction
+        );
+        Ok(())
+    });
+}
+
+// A ball whose box overlaps `brick`, from grazing it to inside it.
+fn ball_in(rng: &mut Rng, brick: Rectangle) -> (Rectangle, BrickHit) {
+    let size = rng.range(8.0, 64.0);
+    loop {
+        let ball = Rectangle {
+            x: rng.range(brick.x - size, brick.x + brick.width),
+            y: rng.range(brick.y - size, brick.y + brick.height),
+            width: size,
+            height: size,
+        };
+        if let Some(hit) = first_hit(ball, [brick]) {
+            return (ball, hit);
+        }
+    }
+}
+
+#[test]
+fn reflecting_off_a_brick_keeps_the_speed() {
+    property::check("reflecting_off_a_brick_keeps_the_speed", |rng| {
+        let brick = brick(rng);
+        let (ball, hit) = ball_in(rng, brick);
+        let length = rng.range(0.1, 4.0);
+        let mut direction = property::direction(rng, length);
+        let before = direction;
+        let normal = reflect(centre(ball), centre(brick), &hit, &mut direction);
+        ensure!(
+            close(direction.length(), before.length()),
+            "{:?} off {:?} became {:?}",
+            before,
+            normal,
+            direction
+        );
+        ensure!(
+            close(normal.length(), 1.0),
+            "the normal {:?} is not a unit vector",
+            normal
+        );
+        Ok(())
+    });
+}
+
+#[test]
+fn a_ball_that_hit_a_brick_ends_outside_it() {
+    property::check("a_ball_that_hit_a_brick_ends_outside_it", |rng| {
+        let brick = brick(rng);
+        let (ball, hit) = ball_in(rng, brick);
+        let mut direction = property::direction(rng, 2.0f32.sqrt());
+        let normal = reflect(centre(ball), centre(brick), &hit, &mut direction);
+        let out = separate(ball, brick, normal);
+        let moved = Rectangle {
+            x: ball.x + out.x,
+            y: ball.y + out.y,
+            ..ball
+        };
+        let left = overlap(moved, brick);
+        ensure!(
+            left.width * left.height <= 1e-3,
+            "{:?} in {:?} was moved by {:?} and still overlaps by {:?}",
+            ball,
+            brick,
+            out,
+            left
+        );
+        Ok(())
+    });
+}
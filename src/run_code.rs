@@ -0,0 +1,131 @@
+use crate::challenge::Challenge;
+use crate::lang::tr;
+use crate::mode::GameMode;
+use crate::mutators::{Mutators, Seed};
+use crate::settings::Difficulty;
+use std::fmt;
+use std::str::FromStr;
+
+// Crockford's base32: no I, L, O or U, so codes read back the way they were
+// written down.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const GROUP: usize = 5;
+// Bits of the setup below the seed.
+const SETUP_BITS: u32 = 15;
+
+// Everything a run is started with, written as a short code a friend can
+// type into the menu to play the same run. The seed goes in last and its
+// leading zeros are dropped, so codes for the game's own 32 bit seeds are
+// eleven characters, the last one a checksum. The mutators get a byte, one
+// bit each.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RunCode {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub challenge: Challenge,
+    pub mutators: Mutators,
+    pub difficulty: Difficulty,
+}
+
+impl RunCode {
+    pub fn seed_string(&self) -> Seed {
+        Seed {
+            value: self.seed,
+            mutators: self.mutators,
+        }
+    }
+
+    // What the code sets up, for showing before it is played.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.mode.name(), self.difficulty.name()];
+        if !self.challenge.is_none() {
+            parts.push(self.challenge.name());
+        }
+        parts.extend(self.mutators.names());
+        parts.join(", ")
+    }
+
+    fn pack(&self) -> u128 {
+        let index = |i: Option<usize>| i.unwrap_or(0) as u128;
+        let difficulty = index(Difficulty::ALL.iter().position(|d| *d == self.difficulty));
+        let mode = index(GameMode::ALL.iter().position(|m| *m == self.mode));
+        let challenge = index(Challenge::ALL.iter().position(|c| *c == self.challenge));
+        difficulty
+            | mode << 2
+            | challenge << 5
+            | (self.mutators.bits() as u128) << 7
+            | (self.seed as u128) << SETUP_BITS
+    }
+
+    fn unpack(value: u128) -> Option<Self> {
+        let field = |shift: u32, bits: u32| (value >> shift & ((1 << bits) - 1)) as usize;
+        Some(Self {
+            difficulty: *Difficulty::ALL.get(field(0, 2))?,
+            mode: *GameMode::ALL.get(field(2, 3))?,
+            challenge: *Challenge::ALL.get(field(5, 2))?,
+            mutators: Mutators::from_bits(field(7, 8) as u8),
+            seed: u64::try_from(value >> SETUP_BITS).ok()?,
+        })
+    }
+}
+
+// The sum of the digits, which catches nearly every single mistyped
+// character.
+fn checksum(digits: &[u8]) -> u8 {
+    (digits.iter().map(|d| *d as u32).sum::<u32>() % 31) as u8
+}
+
+impl fmt::Display for RunCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut value = self.pack();
+        let mut digits = Vec::new();
+        while value > 0 || digits.len() * 5 < SETUP_BITS as usize {
+            digits.push((value & 31) as u8);
+            value >>= 5;
+        }
+        digits.push(checksum(&digits));
+        for (i, digit) in digits.iter().enumerate() {
+            if i > 0 && i % GROUP == 0 {
+                write!(f, "-")?;
+            }
+            write!(f, "{}", ALPHABET[*digit as usize] as char)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RunCode {
+    type Err = String;
+
+    // Case, dashes and spaces don't matter, and the letters that look like
+    // digits are read as them.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut digits = Vec::new();
+        for c in s.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+            let c = match c.to_ascii_uppercase() {
+                'O' => '0',
+                'I' | 'L' => '1',
+                c => c,
+            };
+            let digit = ALPHABET
+                .iter()
+                .position(|a| *a as char == c)
+                .ok_or_else(|| tr!("run_code.bad_character", c))?;
+            digits.push(digit as u8);
+        }
+        let Some(check) = digits.pop() else {
+            return Err(tr!("run_code.empty").into());
+        };
+        if digits.len() * 5 < SETUP_BITS as usize || digits.len() * 5 > 128 {
+            return Err(tr!("run_code.wrong_length").into());
+        }
+        if checksum(&digits) != check {
+            return Err(tr!("run_code.typo").into());
+        }
+        let value = digits
+            .iter()
+            .rev()
+            .fold(0u128, |value, d| value << 5 | *d as u128);
+        Self::unpack(value).ok_or_else(|| tr!("run_code.other_version").into())
+    }
+}
@@ -0,0 +1,328 @@
+use crate::achievements::Achievements;
+use crate::assets::Assets;
+use crate::challenge::Challenge;
+use crate::config::Config;
+use crate::highscores::HighScores;
+use crate::input::Input;
+use crate::leaderboard::Leaderboard;
+use crate::level::Level;
+use crate::menu::QuitPrompt;
+use crate::mode::GameMode;
+use crate::music::Track;
+use crate::mutators::Mutators;
+use crate::net::Peer;
+use crate::overlay::OverlayServer;
+use crate::postfx::Effect;
+use crate::presence::Activity;
+use crate::progress::Progress;
+use crate::screen::Canvas;
+use crate::script::Script;
+use crate::sonify::BallTone;
+use crate::sounds::Cue;
+use crate::stats::LifetimeStats;
+use crate::theme::Theme;
+use crate::tween::Tween;
+use crate::twitch::Chat;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use pong::plugin::Registry;
+use raylib::prelude::*;
+use std::time::Duration;
+
+pub struct Context {
+    pub mode: GameMode,
+    // Picked in the challenges menu for the runs that follow.
+    pub challenge: Challenge,
+    // Picked there too, or read from the `--seed` string.
+    pub mutators: Mutators,
+    pub config: Config,
+    pub levels: Vec<Level>,
+    pub progress: Progress,
+    pub high_scores: HighScores,
+    pub achievements: Achievements,
+    pub leaderboard: Leaderboard,
+    pub lifetime: LifetimeStats,
+    pub assets: Assets,
+    pub themes: Vec<Theme>,
+    pub high_contrast_theme: Theme,
+    // Set by `--debug`; unlocks the hitbox overlay and the console.
+    pub debug: bool,
+    pub debug_overlay: bool,
+    // Set by `--seed` to make the random effects repeatable.
+    pub seed: Option<u64>,
+    // Set when something worth flashing the taskbar for happens.
+    pub attention: bool,
+    // Set once the control hints have been shown at the first serve, so the
+    // serves after it go without.
+    pub hints_shown: bool,
+    // Sounds to play this frame; main plays them and empties the queue.
+    pub sounds: Vec<Cue>,
+    // The other player in a versus race.
+    pub peer: Option<Peer>,
+    // Viewers voting in streamer mode.
+    pub chat: Option<Chat>,
+    // The mods found in the mods folder.
+    pub scripts: Vec<Script>,
+    // What the plugins added, for levels loaded after startup.
+    pub registry: Registry,
+    // Set by `--overlay-server`.
+    pub overlay: Option<OverlayServer>,
+}
+
+impl Context {
+    // The theme picked in the settings, or Classic if it no longer exists.
+    pub fn theme(&self) -> &Theme {
+        if self.config.settings.visual.high_contrast {
+            return &self.high_contrast_theme;
+        }
+        self.themes
+            .iter()
+            .find(|t| t.name == self.config.settings.theme)
+            .unwrap_or(&self.themes[0])
+    }
+
+    // The theme's effects, then the CRT if it is switched on. The CRT always
+    // goes last since it bends the picture.
+    pub fn effects(&self) -> Vec<Effect> {
+        let mut effects = self.theme().effects.clone();
+        effects.retain(|e| *e != Effect::Crt);
+        if self.config.settings.visual.crt {
+            effects.push(Effect::Crt);
+        }
+        effects
+    }
+
+    pub fn font(&self) -> &ffi::Font {
+        self.assets.font(self.theme().font.as_deref())
+    }
+}
+
+pub enum Transition {
+    None,
+    Push(Box<dyn Scene>),
+    Pop,
+    Replace(Box<dyn Scene>),
+    Reset(Vec<Box<dyn Scene>>),
+    Quit,
+    // Fades to black, applies the inner transition, then fades back in.
+    Fade(Box<Transition>),
+}
+
+impl Transition {
+    pub fn fade(transition: Transition) -> Self {
+        Transition::Fade(Box::new(transition))
+    }
+}
+
+const FADE_TIME: f32 = 0.25;
+
+enum Fade {
+    Idle,
+    Out(Tween, Transition),
+    In(Tween),
+}
+
+pub trait Scene {
+    // What the scene is called in the log.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    fn update(&mut self, ctx: &mut Context, input: &Input, duration: &Duration) -> Transition;
+    fn render(&self, ctx: &Context, d: &mut Canvas);
+
+    // Overlays are drawn on top of the scene below them instead of replacing it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    // What the window title should say while this scene is showing.
+    fn title(&self, _ctx: &Context) -> Option<String> {
+        None
+    }
+
+    // What the Discord status should say while this scene is showing.
+    fn activity(&self, _ctx: &Context) -> Option<Activity> {
+        None
+    }
+
+    // The music to play while this scene is showing.
+    fn music(&self, _ctx: &Context) -> Option<Track> {
+        None
+    }
+
+    // Whether the music of the scenes below plays quieter under this one.
+    fn ducks_music(&self) -> bool {
+        false
+    }
+
+    // Whether quitting now would lose a run in progress.
+    fn loses_progress(&self) -> bool {
+        false
+    }
+
+    // Where the ball is, for the ball tone to follow.
+    fn ball_tone(&self, _ctx: &Context) -> Option<BallTone> {
+        None
+    }
+
+    // Whether this scene is the one asking whether to quit, so closing the
+    // window again quits for real.
+    fn asks_to_quit(&self) -> bool {
+        false
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    quit: bool,
+    fade: Fade,
+}
+
+impl SceneStack {
+    pub fn new(root: Box<dyn Scene>) -> Self {
+        Self {
+            scenes: vec![root],
+            quit: false,
+            fade: Fade::Idle,
+        }
+    }
+
+    pub fn push(&mut self, scene: Box<dyn Scene>) {
+        log::debug!("pushed {}", scene.name());
+        self.scenes.push(scene);
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit || self.scenes.is_empty()
+    }
+
+    // The title of the topmost scene that has one, so overlays keep the title
+    // of the scene underneath.
+    pub fn title(&self, ctx: &Context) -> Option<String> {
+        self.scenes.iter().rev().find_map(|s| s.title(ctx))
+    }
+
+    // The activity of the topmost scene that has one, like `title`.
+    pub fn activity(&self, ctx: &Context) -> Option<Activity> {
+        self.scenes.iter().rev().find_map(|s| s.activity(ctx))
+    }
+
+    // The track of the topmost scene that picks one, and whether any scene
+    // above it ducks it.
+    pub fn music(&self, ctx: &Context) -> (Option<Track>, bool) {
+        let Some((index, track)) = self
+            .scenes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, s)| s.music(ctx).map(|t| (i, t)))
+        else {
+            return (None, false);
+        };
+        let ducked = self.scenes[index + 1..].iter().any(|s| s.ducks_music());
+        (Some(track), ducked)
+    }
+
+    // Only the scene on top is heard, so the tone stops while the game is
+    // paused.
+    pub fn ball_tone(&self, ctx: &Context) -> Option<BallTone> {
+        self.scenes.last().and_then(|s| s.ball_tone(ctx))
+    }
+
+    // Scenes are not updated while fading out, so nothing can be clicked twice.
+    pub fn update(&mut self, ctx: &mut Context, input: &Input, duration: &Duration) {
+        // Closing the window mid-run asks first, unless that is turned off in
+        // the settings.
+        if input.close {
+            let asking = self.scenes.last().is_some_and(|s| s.asks_to_quit());
+            if ctx.config.settings.confirm_quit
+                && !asking
+                && self.scenes.iter().any(|s| s.loses_progress())
+            {
+                self.scenes.push(Box::new(QuitPrompt::new(true)));
+            } else {
+                self.quit = true;
+            }
+            return;
+        }
+
+        match std::mem::replace(&mut self.fade, Fade::Idle) {
+            Fade::Idle => (),
+            Fade::Out(mut tween, pending) => {
+                tween.update(duration);
+                if tween.finished() {
+                    self.apply(pending);
+                    self.fade = Fade::In(Tween::new(FADE_TIME));
+                } else {
+                    self.fade = Fade::Out(tween, pending);
+                }
+                return;
+            }
+            Fade::In(mut tween) => {
+                tween.update(duration);
+                if !tween.finished() {
+                    self.fade = Fade::In(tween);
+                }
+            }
+        }
+
+        let Some(top) = self.scenes.last_mut() else {
+            return;
+        };
+        let transition = top.update(ctx, input, duration);
+        self.apply(transition);
+    }
+
+    fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::None => (),
+            Transition::Push(scene) => self.push(scene),
+            Transition::Pop => {
+                if let Some(scene) = self.scenes.pop() {
+                    log::debug!("popped {}", scene.name());
+                }
+            }
+            Transition::Replace(scene) => {
+                if let Some(old) = self.scenes.pop() {
+                    log::debug!("replaced {} with {}", old.name(), scene.name());
+                }
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scenes) => {
+                let names: Vec<_> = scenes.iter().map(|s| s.name()).collect();
+                log::debug!("reset the scenes to {}", names.join(", "));
+                self.scenes = scenes;
+            }
+            Transition::Quit => {
+                log::info!("quitting");
+                self.quit = true;
+            }
+            Transition::Fade(inner) => self.fade = Fade::Out(Tween::new(FADE_TIME), *inner),
+        }
+    }
+
+    pub fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let base = self
+            .scenes
+            .iter()
+            .rposition(|s| !s.is_overlay())
+            .unwrap_or(0);
+        for scene in &self.scenes[base..] {
+            scene.render(ctx, d);
+        }
+
+        let darkness = match &self.fade {
+            Fade::Idle => return,
+            Fade::Out(tween, _) => tween.linear(),
+            Fade::In(tween) => 1.0 - tween.linear(),
+        };
+        d.draw_rectangle(
+            0,
+            0,
+            WINDOW_WIDTH as i32,
+            WINDOW_HEIGHT as i32,
+            Color::BLACK.alpha(darkness),
+        );
+    }
+}
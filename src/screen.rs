@@ -0,0 +1,111 @@
+use crate::error::Error;
+use crate::postfx::{Effect, PostFx};
+use crate::settings::Settings;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+
+// Everything is drawn at the logical WINDOW_WIDTH x WINDOW_HEIGHT resolution
+// into an offscreen texture, which is then scaled into the real window.
+pub type Canvas<'a, 'b> = RaylibTextureMode<'a, RaylibDrawHandle<'b>>;
+
+pub struct Screen {
+    target: RenderTexture2D,
+    applied: Option<(bool, (i32, i32))>,
+    postfx: PostFx,
+}
+
+impl Screen {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<Self, Error> {
+        let target = rl
+            .load_render_texture(thread, WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32)
+            .map_err(|_| Error::RenderTexture("render"))?;
+        target.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+        Ok(Self {
+            target,
+            applied: None,
+            postfx: PostFx::new(rl, thread)?,
+        })
+    }
+
+    // Applies the window settings when they have changed since the last call,
+    // so a window the player resized by hand is left alone otherwise.
+    // Fullscreen uses the monitor's own resolution rather than switching the
+    // monitor to the window size.
+    pub fn apply(&mut self, rl: &mut RaylibHandle, settings: &Settings) {
+        let wanted = (settings.fullscreen, settings.window_size);
+        if self.applied == Some(wanted) {
+            return;
+        }
+        self.applied = Some(wanted);
+
+        if settings.fullscreen {
+            if !rl.is_window_fullscreen() {
+                let (width, height) = native_resolution();
+                rl.set_window_size(width, height);
+                rl.toggle_fullscreen();
+            }
+            return;
+        }
+        if rl.is_window_fullscreen() {
+            rl.toggle_fullscreen();
+        }
+        let (width, height) = settings.window_size;
+        rl.set_window_size(width, height);
+        let monitor = get_current_monitor();
+        rl.set_window_position(
+            (get_monitor_width(monitor) - width) / 2,
+            (get_monitor_height(monitor) - height) / 2,
+        );
+    }
+
+    // The largest rectangle with the logical aspect ratio that fits the
+    // window, centred so the leftover space becomes black bars.
+    fn viewport(rl: &RaylibHandle) -> Rectangle {
+        let (width, height) = (rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        let scale = (width / WINDOW_WIDTH).min(height / WINDOW_HEIGHT);
+        Rectangle {
+            x: ((width - WINDOW_WIDTH * scale) / 2.0).floor(),
+            y: ((height - WINDOW_HEIGHT * scale) / 2.0).floor(),
+            width: WINDOW_WIDTH * scale,
+            height: WINDOW_HEIGHT * scale,
+        }
+    }
+
+    // Maps the mouse into logical coordinates; call before polling input.
+    pub fn update_mouse(&self, rl: &mut RaylibHandle) {
+        let viewport = Self::viewport(rl);
+        rl.set_mouse_offset(Vector2::new(-viewport.x, -viewport.y));
+        rl.set_mouse_scale(
+            WINDOW_WIDTH / viewport.width,
+            WINDOW_HEIGHT / viewport.height,
+        );
+    }
+
+    // The last frame drawn, at the logical resolution and before any
+    // post-processing.
+    pub fn frame(&self) -> &RenderTexture2D {
+        &self.target
+    }
+
+    pub fn draw(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        effects: &[Effect],
+        render: impl FnOnce(&mut Canvas),
+    ) {
+        let viewport = Self::viewport(rl);
+        let mut d = rl.begin_drawing(thread);
+        {
+            let mut canvas = d.begin_texture_mode(thread, &mut self.target);
+            render(&mut canvas);
+        }
+        self.postfx
+            .present(&mut d, thread, &self.target, effects, viewport);
+    }
+}
+
+pub fn native_resolution() -> (i32, i32) {
+    let monitor = get_current_monitor();
+    (get_monitor_width(monitor), get_monitor_height(monitor))
+}
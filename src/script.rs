@@ -0,0 +1,365 @@
+// Mods: small scripts in the mods folder of the data directory that run at
+// hook points in the game. A script is a list of hooks, each followed by the
+// commands to run there:
+//
+//     on_brick_destroyed
+//         add broken 1
+//         if $broken >= 10
+//             addscore 500
+//             popup TEN IN A ROW
+//             set broken 0
+//         end
+//
+// `$name` is replaced by a value the game provides (`score`, `lives`, `level`
+// and so on) or one of the script's own variables, which `set` and `add`
+// change and which start at 0. Everything else is a command from the game's
+// script API. There are no loops, so a hook always finishes.
+//
+// Built without the `mods` feature no scripts are loaded, and any that are
+// there are reported.
+
+const MODS_DIR: &str = "mods";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Hook {
+    LevelStart,
+    BrickDestroyed,
+    Tick,
+}
+
+#[cfg_attr(not(feature = "mods"), allow(dead_code))]
+impl Hook {
+    const ALL: [Hook; 3] = [Hook::LevelStart, Hook::BrickDestroyed, Hook::Tick];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::LevelStart => "on_level_start",
+            Hook::BrickDestroyed => "on_brick_destroyed",
+            Hook::Tick => "on_tick",
+        }
+    }
+}
+
+#[cfg(feature = "mods")]
+mod imp {
+    use super::{Hook, MODS_DIR};
+    use crate::paths;
+    use pong::plugin::Registry;
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::fs;
+
+    #[derive(Clone, Copy, Debug)]
+    enum Comparison {
+        Equal,
+        NotEqual,
+        Less,
+        LessOrEqual,
+        Greater,
+        GreaterOrEqual,
+    }
+
+    impl Comparison {
+        fn parse(op: &str) -> Option<Self> {
+            Some(match op {
+                "==" => Comparison::Equal,
+                "!=" => Comparison::NotEqual,
+                "<" => Comparison::Less,
+                "<=" => Comparison::LessOrEqual,
+                ">" => Comparison::Greater,
+                ">=" => Comparison::GreaterOrEqual,
+                _ => return None,
+            })
+        }
+
+        fn test(&self, a: f64, b: f64) -> bool {
+            match self {
+                Comparison::Equal => a == b,
+                Comparison::NotEqual => a != b,
+                Comparison::Less => a < b,
+                Comparison::LessOrEqual => a <= b,
+                Comparison::Greater => a > b,
+                Comparison::GreaterOrEqual => a >= b,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    enum Statement {
+        // A line for the script API, with `$` words still in it.
+        Command(String),
+        Set(String, String),
+        Add(String, String),
+        If {
+            left: String,
+            comparison: Comparison,
+            right: String,
+            body: Vec<Statement>,
+        },
+    }
+
+    #[derive(Clone)]
+    pub struct Script {
+        pub name: String,
+        hooks: Vec<(Hook, Vec<Statement>)>,
+        vars: HashMap<String, f64>,
+        // A script that went wrong once is switched off for the rest of the run
+        // rather than reporting the same error every frame.
+        failed: bool,
+    }
+
+    impl Script {
+        pub fn parse(name: &str, text: &str) -> Result<Script, String> {
+            let mut hooks: Vec<(Hook, Vec<Statement>)> = Vec::new();
+            // The statement lists being filled, innermost last, with the `if`
+            // each nested one belongs to.
+            let mut open: Vec<(Vec<Statement>, Option<Statement>)> = Vec::new();
+            let mut hook = None;
+            for (number, line) in text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let error = |message: &str| format!("line {}: {}", number + 1, message);
+                if let Some(next) = Hook::ALL.into_iter().find(|h| h.name() == line) {
+                    if open.len() > 1 {
+                        return Err(error("missing end before the next hook"));
+                    }
+                    if let (Some(done), Some((body, _))) = (hook, open.pop()) {
+                        hooks.push((done, body));
+                    }
+                    hook = Some(next);
+                    open.push((Vec::new(), None));
+                    continue;
+                }
+                if hook.is_none() {
+                    return Err(error("expected a hook such as on_tick"));
+                }
+                let words: Vec<&str> = line.split_whitespace().collect();
+                match words.as_slice() {
+                    ["end"] => {
+                        if open.len() < 2 {
+                            return Err(error("end without if"));
+                        }
+                        let (body, statement) = open.pop().unwrap();
+                        let Some(Statement::If {
+                            left,
+                            comparison,
+                            right,
+                            ..
+                        }) = statement
+                        else {
+                            unreachable!("only if opens a block");
+                        };
+                        open.last_mut().unwrap().0.push(Statement::If {
+                            left,
+                            comparison,
+                            right,
+                            body,
+                        });
+                    }
+                    ["if", left, op, right] => {
+                        let comparison =
+                            Comparison::parse(op).ok_or_else(|| error("unknown comparison"))?;
+                        open.push((
+                            Vec::new(),
+                            Some(Statement::If {
+                                left: left.to_string(),
+                                comparison,
+                                right: right.to_string(),
+                                body: Vec::new(),
+                            }),
+                        ));
+                    }
+                    ["if", ..] => return Err(error("if takes <value> <comparison> <value>")),
+                    [verb @ ("set" | "add"), var, value] => {
+                        let (var, value) = (var.to_string(), value.to_string());
+                        open.last_mut().unwrap().0.push(if *verb == "set" {
+                            Statement::Set(var, value)
+                        } else {
+                            Statement::Add(var, value)
+                        });
+                    }
+                    ["set" | "add", ..] => return Err(error("set and add take <name> <value>")),
+                    _ => open
+                        .last_mut()
+                        .unwrap()
+                        .0
+                        .push(Statement::Command(line.to_string())),
+                }
+            }
+            if open.len() > 1 {
+                return Err("missing end at the end of the file".into());
+            }
+            if let (Some(done), Some((body, _))) = (hook, open.pop()) {
+                hooks.push((done, body));
+            }
+            Ok(Script {
+                name: name.to_string(),
+                hooks,
+                vars: HashMap::new(),
+                failed: false,
+            })
+        }
+
+        // Runs the statements of `hook`, passing API commands to `execute`.
+        // `values` are what `$` words can read besides the script's variables.
+        pub fn run(
+            &mut self,
+            hook: Hook,
+            values: &[(&str, f64)],
+            execute: &mut dyn FnMut(&str) -> Result<String, String>,
+        ) {
+            if self.failed {
+                return;
+            }
+            let hooks = std::mem::take(&mut self.hooks);
+            let result = hooks
+                .iter()
+                .filter(|(h, _)| *h == hook)
+                .try_for_each(|(_, body)| self.block(body, values, execute));
+            self.hooks = hooks;
+            if let Err(e) = result {
+                log::warn!("mod {} stopped: {}", self.name, e);
+                self.failed = true;
+            }
+        }
+
+        fn block(
+            &mut self,
+            body: &[Statement],
+            values: &[(&str, f64)],
+            execute: &mut dyn FnMut(&str) -> Result<String, String>,
+        ) -> Result<(), String> {
+            for statement in body {
+                match statement {
+                    Statement::Command(line) => {
+                        let line = line
+                            .split_whitespace()
+                            .map(|word| self.word(word, values))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        execute(&line)?;
+                    }
+                    Statement::Set(var, value) => {
+                        let value = self.number(value, values)?;
+                        self.vars.insert(var.clone(), value);
+                    }
+                    Statement::Add(var, value) => {
+                        let value = self.number(value, values)?;
+                        *self.vars.entry(var.clone()).or_default() += value;
+                    }
+                    Statement::If {
+                        left,
+                        comparison,
+                        right,
+                        body,
+                    } => {
+                        let (left, right) =
+                            (self.number(left, values)?, self.number(right, values)?);
+                        if comparison.test(left, right) {
+                            self.block(body, values, execute)?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // A word with `$name` replaced by its value.
+        fn word<'a>(&self, word: &'a str, values: &[(&str, f64)]) -> Cow<'a, str> {
+            let Some(name) = word.strip_prefix('$') else {
+                return word.into();
+            };
+            let value = values
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| *v)
+                .or_else(|| self.vars.get(name).copied())
+                .unwrap_or(0.0);
+            // Whole numbers are passed without a decimal point so commands
+            // taking integers accept them.
+            if value.fract() == 0.0 {
+                format!("{}", value as i64).into()
+            } else {
+                value.to_string().into()
+            }
+        }
+
+        fn number(&self, word: &str, values: &[(&str, f64)]) -> Result<f64, String> {
+            let text = self.word(word, values);
+            text.parse()
+                .map_err(|_| format!("'{}' is not a number", text))
+        }
+    }
+
+    // The scripts plugins added, then every `.script` file in the mods folder in
+    // file name order. Broken scripts are reported and skipped.
+    pub fn load_all(registry: &Registry) -> Vec<Script> {
+        let mut scripts: Vec<Script> = registry
+            .scripts()
+            .iter()
+            .filter_map(|(name, text)| {
+                Script::parse(name, text)
+                    .map_err(|e| log::warn!("could not load the plugin mod {}: {}", name, e))
+                    .ok()
+            })
+            .collect();
+        let Ok(entries) = fs::read_dir(paths::data_dir().join(MODS_DIR)) else {
+            return scripts;
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "script"))
+            .collect();
+        files.sort();
+        scripts.extend(files.into_iter().filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Script::parse(&name, &text))
+            {
+                Ok(script) => {
+                    log::info!("loaded mod {}", name);
+                    Some(script)
+                }
+                Err(e) => {
+                    log::warn!("could not load {}: {}", path.display(), e);
+                    None
+                }
+            }
+        }));
+        scripts
+    }
+}
+
+#[cfg(not(feature = "mods"))]
+mod imp {
+    use super::{Hook, MODS_DIR};
+    use crate::paths;
+    use pong::plugin::Registry;
+
+    // There are never any scripts to run.
+    #[derive(Clone)]
+    pub enum Script {}
+
+    impl Script {
+        pub fn run(
+            &mut self,
+            _hook: Hook,
+            _values: &[(&str, f64)],
+            _execute: &mut dyn FnMut(&str) -> Result<String, String>,
+        ) {
+            match *self {}
+        }
+    }
+
+    pub fn load_all(registry: &Registry) -> Vec<Script> {
+        if !registry.scripts().is_empty() || paths::data_dir().join(MODS_DIR).is_dir() {
+            log::warn!("built without the mods feature, mods are off");
+        }
+        Vec::new()
+    }
+}
+
+pub use imp::{load_all, Script};
@@ -0,0 +1,410 @@
+use crate::config::{Table, Value, Writer};
+use crate::lang::{tr, Language};
+use crate::theme::Theme;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputMode {
+    Keyboard,
+    Mouse,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl InputMode {
+    const ALL: [InputMode; 2] = [InputMode::Keyboard, InputMode::Mouse];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputMode::Keyboard => tr!("settings.keyboard"),
+            InputMode::Mouse => tr!("settings.mouse"),
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            InputMode::Keyboard => "keyboard",
+            InputMode::Mouse => "mouse",
+        }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => tr!("difficulty.easy"),
+            Difficulty::Normal => tr!("difficulty.normal"),
+            Difficulty::Hard => tr!("difficulty.hard"),
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    // Paddle wear would be too harsh on Easy, so it only applies above it.
+    pub fn allows_paddle_wear(&self) -> bool {
+        *self != Difficulty::Easy
+    }
+
+    pub fn ball_speed_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+}
+
+pub const WINDOW_SIZES: [(i32, i32); 6] = [
+    (960, 540),
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+
+// Steps through `all` by `delta`, wrapping around at both ends.
+pub fn cycle<T: Copy + PartialEq>(all: &[T], current: T, delta: isize) -> T {
+    let index = all.iter().position(|v| *v == current).unwrap_or(0) as isize;
+    all[(index + delta).rem_euclid(all.len() as isize) as usize]
+}
+
+// Everything that changes how the game looks rather than plays. Renderers ask
+// this instead of reading the individual flags, so e.g. reduced motion can
+// switch several effects off in one place.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisualSettings {
+    pub shake_intensity: u8,
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+    pub crt: bool,
+    // Draws and plays the ball and paddle bigger, by `oversize_factor`
+    // percent, for anyone who finds them hard to see.
+    pub oversized: bool,
+    pub oversize_factor: u8,
+}
+
+impl VisualSettings {
+    // Screen shake strength from 0.0 (off) to 1.0.
+    pub fn shake(&self) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            self.shake_intensity as f32 / 100.0
+        }
+    }
+
+    pub fn particles(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    pub fn trails(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    pub fn animated_background(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    // The camera zooming in on the blow that clears a level.
+    pub fn zoom(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    // Things sliding into place; fades are kept either way.
+    pub fn slides(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    // What the ball's radius and the paddle's width are multiplied by.
+    pub fn oversize(&self) -> f32 {
+        if self.oversized {
+            self.oversize_factor as f32 / 100.0
+        } else {
+            1.0
+        }
+    }
+}
+
+// Volumes in percent. Master applies to everything on top of the other two.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub master_volume: u8,
+    pub music_volume: u8,
+    pub sfx_volume: u8,
+    // A tone that follows the ball, panned with it and higher the further
+    // up it is, at the sound effects' volume.
+    pub ball_tone: bool,
+}
+
+impl AudioSettings {
+    pub fn master(&self) -> f32 {
+        self.master_volume as f32 / 100.0
+    }
+
+    pub fn music(&self) -> f32 {
+        self.music_volume as f32 / 100.0
+    }
+
+    pub fn sfx(&self) -> f32 {
+        self.sfx_volume as f32 / 100.0
+    }
+}
+
+// Volumes move in steps of 10%.
+pub fn step_volume(volume: u8, delta: isize) -> u8 {
+    (volume as isize + delta * 10).clamp(0, 100) as u8
+}
+
+// The ball and paddle can be made from a quarter to twice as big again.
+const MIN_OVERSIZE: u8 = 125;
+const MAX_OVERSIZE: u8 = 200;
+
+pub fn step_oversize(factor: u8, delta: isize) -> u8 {
+    let steps = (MAX_OVERSIZE - MIN_OVERSIZE) as isize / 25 + 1;
+    let step = (factor.clamp(MIN_OVERSIZE, MAX_OVERSIZE) - MIN_OVERSIZE) as isize / 25 + delta;
+    MIN_OVERSIZE + (step.rem_euclid(steps) * 25) as u8
+}
+
+// The slowest the game can be set to run, in percent of full speed.
+pub const MIN_GAME_SPEED: u8 = 50;
+
+// The game speed moves in steps of 10% too, from half speed to full.
+pub fn step_game_speed(speed: u8, delta: isize) -> u8 {
+    (speed as isize / 10 * 10 + delta * 10).clamp(MIN_GAME_SPEED as isize, 100) as u8
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    // The code of the language the text is in.
+    pub language: String,
+    pub audio: AudioSettings,
+    pub input_mode: InputMode,
+    // Moving, serving and pausing all within reach of one hand.
+    pub one_handed: bool,
+    pub difficulty: Difficulty,
+    pub fullscreen: bool,
+    pub window_size: (i32, i32),
+    pub theme: String,
+    pub visual: VisualSettings,
+    pub constant_ball_speed: bool,
+    // How fast everything in a run moves, in percent. Scores played slower
+    // than full speed are marked.
+    pub game_speed: u8,
+    // The paddle shrinks with every ball lost until the level is cleared.
+    pub paddle_wear: bool,
+    // Only has an effect in builds with the `discord` feature.
+    pub discord_presence: bool,
+    pub speedrun_timer: bool,
+    // Escape or closing the window mid-run asks before the run is lost.
+    pub confirm_quit: bool,
+    // The name the last high score went in under, offered for the next one.
+    pub player_name: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: "en".into(),
+            audio: AudioSettings {
+                master_volume: 80,
+                music_volume: 70,
+                sfx_volume: 100,
+                ball_tone: false,
+            },
+            input_mode: InputMode::Keyboard,
+            one_handed: false,
+            difficulty: Difficulty::Normal,
+            fullscreen: false,
+            window_size: (1280, 720),
+            theme: "Classic".into(),
+            visual: VisualSettings {
+                shake_intensity: 100,
+                high_contrast: false,
+                reduced_motion: false,
+                crt: false,
+                oversized: false,
+                oversize_factor: 150,
+            },
+            constant_ball_speed: false,
+            game_speed: 100,
+            paddle_wear: false,
+            discord_presence: true,
+            speedrun_timer: false,
+            confirm_quit: true,
+            player_name: "AAA".into(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_table(table: &Table) -> Self {
+        let default = Self::default();
+        Self {
+            language: table
+                .get_str("settings.language")
+                .map_or(default.language, str::to_string),
+            audio: AudioSettings {
+                master_volume: table
+                    .get_int("settings.volume")
+                    .map_or(default.audio.master_volume, |v| v.clamp(0, 100) as u8),
+                music_volume: table
+                    .get_int("settings.music_volume")
+                    .map_or(default.audio.music_volume, |v| v.clamp(0, 100) as u8),
+                sfx_volume: table
+                    .get_int("settings.sfx_volume")
+                    .map_or(default.audio.sfx_volume, |v| v.clamp(0, 100) as u8),
+                ball_tone: table
+                    .get_bool("settings.ball_tone")
+                    .unwrap_or(default.audio.ball_tone),
+            },
+            input_mode: table
+                .get_str("settings.input_mode")
+                .and_then(|s| InputMode::ALL.into_iter().find(|m| m.key() == s))
+                .unwrap_or(default.input_mode),
+            one_handed: table
+                .get_bool("settings.one_handed")
+                .unwrap_or(default.one_handed),
+            difficulty: table
+                .get_str("settings.difficulty")
+                .and_then(|s| Difficulty::ALL.into_iter().find(|d| d.key() == s))
+                .unwrap_or(default.difficulty),
+            fullscreen: table
+                .get_bool("settings.fullscreen")
+                .unwrap_or(default.fullscreen),
+            window_size: table
+                .get_int("settings.window_width")
+                .zip(table.get_int("settings.window_height"))
+                .filter(|(w, h)| *w > 0 && *h > 0)
+                .map_or(default.window_size, |(w, h)| (w as i32, h as i32)),
+            theme: table
+                .get_str("settings.theme")
+                .map_or(default.theme, str::to_string),
+            visual: VisualSettings {
+                shake_intensity: table
+                    .get_int("settings.shake_intensity")
+                    .map_or(default.visual.shake_intensity, |v| v.clamp(0, 100) as u8),
+                high_contrast: table
+                    .get_bool("settings.high_contrast")
+                    .unwrap_or(default.visual.high_contrast),
+                reduced_motion: table
+                    .get_bool("settings.reduced_motion")
+                    .unwrap_or(default.visual.reduced_motion),
+                crt: table.get_bool("settings.crt").unwrap_or(default.visual.crt),
+                oversized: table
+                    .get_bool("settings.oversized")
+                    .unwrap_or(default.visual.oversized),
+                oversize_factor: table
+                    .get_int("settings.oversize_factor")
+                    .map_or(default.visual.oversize_factor, |v| {
+                        step_oversize(v.clamp(0, 255) as u8, 0)
+                    }),
+            },
+            constant_ball_speed: table
+                .get_bool("settings.constant_ball_speed")
+                .unwrap_or(default.constant_ball_speed),
+            game_speed: table
+                .get_int("settings.game_speed")
+                .map_or(default.game_speed, |v| {
+                    step_game_speed(v.clamp(0, 100) as u8, 0)
+                }),
+            paddle_wear: table
+                .get_bool("settings.paddle_wear")
+                .unwrap_or(default.paddle_wear),
+            discord_presence: table
+                .get_bool("settings.discord_presence")
+                .unwrap_or(default.discord_presence),
+            speedrun_timer: table
+                .get_bool("settings.speedrun_timer")
+                .unwrap_or(default.speedrun_timer),
+            confirm_quit: table
+                .get_bool("settings.confirm_quit")
+                .unwrap_or(default.confirm_quit),
+            player_name: table
+                .get_str("settings.player_name")
+                .map_or(default.player_name, str::to_string),
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer) {
+        writer
+            .section("settings")
+            .value("language", Value::Str(self.language.clone()))
+            .value("volume", Value::Int(self.audio.master_volume as i64))
+            .value("music_volume", Value::Int(self.audio.music_volume as i64))
+            .value("sfx_volume", Value::Int(self.audio.sfx_volume as i64))
+            .value("ball_tone", Value::Bool(self.audio.ball_tone))
+            .value("input_mode", Value::Str(self.input_mode.key().into()))
+            .value("one_handed", Value::Bool(self.one_handed))
+            .value("difficulty", Value::Str(self.difficulty.key().into()))
+            .value("fullscreen", Value::Bool(self.fullscreen))
+            .value("window_width", Value::Int(self.window_size.0 as i64))
+            .value("window_height", Value::Int(self.window_size.1 as i64))
+            .value(
+                "shake_intensity",
+                Value::Int(self.visual.shake_intensity as i64),
+            )
+            .value("theme", Value::Str(self.theme.clone()))
+            .value("high_contrast", Value::Bool(self.visual.high_contrast))
+            .value("reduced_motion", Value::Bool(self.visual.reduced_motion))
+            .value("crt", Value::Bool(self.visual.crt))
+            .value("oversized", Value::Bool(self.visual.oversized))
+            .value(
+                "oversize_factor",
+                Value::Int(self.visual.oversize_factor as i64),
+            )
+            .value("constant_ball_speed", Value::Bool(self.constant_ball_speed))
+            .value("game_speed", Value::Int(self.game_speed as i64))
+            .value("paddle_wear", Value::Bool(self.paddle_wear))
+            .value("discord_presence", Value::Bool(self.discord_presence))
+            .value("speedrun_timer", Value::Bool(self.speedrun_timer))
+            .value("confirm_quit", Value::Bool(self.confirm_quit))
+            .value("player_name", Value::Str(self.player_name.clone()));
+    }
+
+    pub fn cycle_input_mode(&mut self, delta: isize) {
+        self.input_mode = cycle(&InputMode::ALL, self.input_mode, delta);
+    }
+
+    pub fn cycle_window_size(&mut self, delta: isize) {
+        self.window_size = cycle(&WINDOW_SIZES, self.window_size, delta);
+    }
+
+    // An unknown theme name counts as the first theme.
+    pub fn cycle_theme(&mut self, themes: &[Theme], delta: isize) {
+        let index = themes
+            .iter()
+            .position(|t| t.name == self.theme)
+            .unwrap_or(0) as isize;
+        self.theme = themes[(index + delta).rem_euclid(themes.len() as isize) as usize]
+            .name
+            .clone();
+    }
+
+    // An unknown language counts as the first one, English.
+    pub fn cycle_language(&mut self, languages: &[Language], delta: isize) {
+        let index = languages
+            .iter()
+            .position(|l| l.code == self.language)
+            .unwrap_or(0) as isize;
+        self.language = languages[(index + delta).rem_euclid(languages.len() as isize) as usize]
+            .code
+            .clone();
+    }
+
+    pub fn cycle_difficulty(&mut self, delta: isize) {
+        self.difficulty = cycle(&Difficulty::ALL, self.difficulty, delta);
+    }
+}
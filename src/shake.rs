@@ -0,0 +1,43 @@
+use crate::rng::Rng;
+use raylib::prelude::*;
+use std::time::Duration;
+
+const MAX_OFFSET: f32 = 24.0;
+const DECAY_PER_SECOND: f32 = 1.6;
+
+// Trauma-based screen shake: impacts add trauma in 0..=1, which decays over
+// time, and the camera is offset by an amount growing with trauma squared so
+// small knocks stay subtle while big ones really jolt.
+pub struct Shake {
+    trauma: f32,
+    offset: Vector2,
+    rng: Rng,
+}
+
+impl Shake {
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            trauma: 0.0,
+            offset: Vector2::zero(),
+            rng,
+        }
+    }
+
+    pub fn add(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+
+    // `intensity` scales the offset, 0.0 turning the shake off entirely.
+    pub fn update(&mut self, duration: &Duration, intensity: f32) {
+        self.trauma = (self.trauma - DECAY_PER_SECOND * duration.as_secs_f32()).max(0.0);
+        let amount = MAX_OFFSET * intensity * self.trauma * self.trauma;
+        self.offset = Vector2::new(
+            self.rng.range(-1.0, 1.0) * amount,
+            self.rng.range(-1.0, 1.0) * amount,
+        );
+    }
+
+    pub fn offset(&self) -> Vector2 {
+        self.offset
+    }
+}
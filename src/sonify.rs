@@ -0,0 +1,134 @@
+// The ball tone. Built without the `audio` feature it stays silent.
+
+// Where the ball is, for following it by ear: `pan` from -1.0 (left) to 1.0
+// (right) and `height` from 0.0 (bottom) to 1.0 (top).
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub struct BallTone {
+    pub pan: f32,
+    pub height: f32,
+}
+
+#[cfg(feature = "audio")]
+mod imp {
+    use super::BallTone;
+    use raylib::prelude::*;
+    use std::f32::consts::TAU;
+    use std::marker::PhantomData;
+
+    const SAMPLE_RATE: u32 = 44100;
+    // Frames handed to raylib at a time: short enough for the tone to keep up
+    // with the ball, long enough not to run dry between two frames.
+    const CHUNK: usize = 2048;
+    // The tone spans two octaves, low at the bottom of the screen and high at
+    // the top.
+    const LOW: f32 = 220.0;
+    const HIGH: f32 = 880.0;
+    // How loud the tone is next to the sound effects.
+    const LEVEL: f32 = 0.35;
+    // The share of the second harmonic, which makes it less piercing than a
+    // bare sine.
+    const OVERTONE: f32 = 0.25;
+
+    // A steady tone generated on the fly that follows the ball around. The
+    // pitch and volume glide from one chunk to the next so changes don't click.
+    pub struct Sonifier<'a> {
+        stream: Option<ffi::AudioStream>,
+        buffer: Vec<i16>,
+        phase: f32,
+        frequency: f32,
+        amplitude: f32,
+        // The stream lives on the audio device.
+        _audio: PhantomData<&'a RaylibAudio>,
+    }
+
+    impl<'a> Sonifier<'a> {
+        pub fn load(audio: Option<&'a RaylibAudio>) -> Self {
+            // The wrapper's `AudioStream::update` passes a byte count where
+            // raylib wants frames, so the stream is driven through ffi directly.
+            let stream = audio.and_then(|_| unsafe {
+                // Music streams take the default size too, so it is put back.
+                ffi::SetAudioStreamBufferSizeDefault(CHUNK as i32);
+                let stream = ffi::LoadAudioStream(SAMPLE_RATE, 16, 1);
+                ffi::SetAudioStreamBufferSizeDefault(0);
+                if !ffi::IsAudioStreamReady(stream) {
+                    log::warn!("could not open an audio stream for the ball tone");
+                    return None;
+                }
+                ffi::PlayAudioStream(stream);
+                Some(stream)
+            });
+            Self {
+                stream,
+                buffer: vec![0; CHUNK],
+                phase: 0.0,
+                frequency: LOW,
+                amplitude: 0.0,
+                _audio: PhantomData,
+            }
+        }
+
+        // Keeps the stream fed, sounding `tone` or fading out without one.
+        // `volume` runs from 0.0 to 1.0.
+        pub fn update(&mut self, tone: Option<BallTone>, volume: f32) {
+            let Some(stream) = self.stream else {
+                return;
+            };
+            let mut frequency = self.frequency;
+            let mut amplitude = 0.0;
+            if let Some(tone) = tone {
+                frequency = LOW * (HIGH / LOW).powf(tone.height.clamp(0.0, 1.0));
+                amplitude = volume * LEVEL;
+                // raylib pans from 1.0 (left) to 0.0 (right).
+                unsafe { ffi::SetAudioStreamPan(stream, 0.5 - tone.pan.clamp(-1.0, 1.0) / 2.0) };
+            }
+            while unsafe { ffi::IsAudioStreamProcessed(stream) } {
+                self.fill(frequency, amplitude);
+                unsafe {
+                    ffi::UpdateAudioStream(stream, self.buffer.as_ptr().cast(), CHUNK as i32);
+                }
+            }
+        }
+
+        fn fill(&mut self, frequency: f32, amplitude: f32) {
+            for (i, sample) in self.buffer.iter_mut().enumerate() {
+                let t = i as f32 / CHUNK as f32;
+                let f = self.frequency + (frequency - self.frequency) * t;
+                let a = self.amplitude + (amplitude - self.amplitude) * t;
+                self.phase = (self.phase + TAU * f / SAMPLE_RATE as f32) % TAU;
+                let wave =
+                    (self.phase.sin() + OVERTONE * (2.0 * self.phase).sin()) / (1.0 + OVERTONE);
+                *sample = (wave * a * i16::MAX as f32) as i16;
+            }
+            self.frequency = frequency;
+            self.amplitude = amplitude;
+        }
+    }
+
+    impl Drop for Sonifier<'_> {
+        fn drop(&mut self) {
+            if let Some(stream) = self.stream {
+                unsafe { ffi::UnloadAudioStream(stream) };
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod imp {
+    use super::BallTone;
+    use raylib::prelude::RaylibAudio;
+    use std::marker::PhantomData;
+
+    pub struct Sonifier<'a>(PhantomData<&'a RaylibAudio>);
+
+    impl<'a> Sonifier<'a> {
+        pub fn load(_audio: Option<&'a RaylibAudio>) -> Self {
+            Self(PhantomData)
+        }
+
+        pub fn update(&mut self, _tone: Option<BallTone>, _volume: f32) {}
+    }
+}
+
+pub use imp::Sonifier;
@@ -0,0 +1,203 @@
+// Sound effects. Built without the `audio` feature the cues the game queues
+// up are dropped.
+use crate::events::GameEvent;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sfx {
+    WallHit,
+    PaddleHit,
+    BrickHit,
+    BrickDestroyed,
+    LifeLost,
+    PowerUp,
+    // A heavy ball hitting a brick.
+    Thud,
+    Portal,
+    Bumper,
+}
+
+impl Sfx {
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    const ALL: [Sfx; 9] = [
+        Sfx::WallHit,
+        Sfx::PaddleHit,
+        Sfx::BrickHit,
+        Sfx::BrickDestroyed,
+        Sfx::LifeLost,
+        Sfx::PowerUp,
+        Sfx::Thud,
+        Sfx::Portal,
+        Sfx::Bumper,
+    ];
+
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    fn key(&self) -> &'static str {
+        match self {
+            Sfx::WallHit => "wall",
+            Sfx::PaddleHit => "paddle",
+            Sfx::BrickHit => "brick",
+            Sfx::BrickDestroyed => "break",
+            Sfx::LifeLost => "lose",
+            Sfx::PowerUp => "powerup",
+            Sfx::Thud => "thud",
+            Sfx::Portal => "portal",
+            Sfx::Bumper => "bumper",
+        }
+    }
+
+    pub fn for_event(event: &GameEvent) -> Option<Sfx> {
+        Some(match event {
+            GameEvent::WallHit { .. } => Sfx::WallHit,
+            GameEvent::PaddleHit { .. } => Sfx::PaddleHit,
+            GameEvent::BrickHit { .. } => Sfx::BrickHit,
+            GameEvent::BrickDestroyed { .. } => Sfx::BrickDestroyed,
+            GameEvent::LifeLost => Sfx::LifeLost,
+            GameEvent::Teleported { .. } => Sfx::Portal,
+            GameEvent::BumperHit { .. } => Sfx::Bumper,
+            GameEvent::PowerUpCaught { .. } | GameEvent::GemCaught { .. } => Sfx::PowerUp,
+            GameEvent::ChatVote { .. } => return None,
+        })
+    }
+}
+
+// A request to play a sound, at a pitch where 1.0 is the recording as is and
+// panned from -1.0 (left) to 1.0 (right).
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub struct Cue {
+    pub sfx: Sfx,
+    pub pitch: f32,
+    pub pan: f32,
+}
+
+impl Cue {
+    pub fn new(sfx: Sfx) -> Self {
+        Self {
+            sfx,
+            pitch: 1.0,
+            pan: 0.0,
+        }
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.pitch = pitch;
+        self
+    }
+
+    pub fn pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+}
+
+#[cfg(feature = "audio")]
+mod imp {
+    use super::{Cue, Sfx};
+    use raylib::prelude::*;
+    use std::fs;
+    use std::path::Path;
+
+    const SOUNDS_DIR: &str = "assets/sounds";
+
+    // Sound effects from `assets/sounds/`, named after `Sfx::key` in any format
+    // raylib can load. Missing ones are silent.
+    pub struct SoundBank<'a> {
+        audio: Option<&'a RaylibAudio>,
+        sounds: Vec<(Sfx, Sound<'a>)>,
+    }
+
+    impl<'a> SoundBank<'a> {
+        pub fn load(audio: Option<&'a RaylibAudio>) -> Self {
+            let Some(audio) = audio else {
+                return Self {
+                    audio,
+                    sounds: Vec::new(),
+                };
+            };
+            let files: Vec<_> = fs::read_dir(SOUNDS_DIR)
+                .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+                .unwrap_or_default();
+            let sounds = Sfx::ALL
+                .into_iter()
+                .filter_map(|sfx| {
+                    let path = files
+                        .iter()
+                        .find(|p| p.file_stem().is_some_and(|s| s == sfx.key()))?;
+                    load(audio, path).map(|sound| (sfx, sound))
+                })
+                .collect();
+            Self {
+                audio: Some(audio),
+                sounds,
+            }
+        }
+
+        // Loads `path` again if it is one of the sound effects.
+        pub fn reload(&mut self, path: &Path) {
+            let Some(audio) = self.audio else {
+                return;
+            };
+            if path.parent() != Some(Path::new(SOUNDS_DIR)) {
+                return;
+            }
+            let Some(sfx) = Sfx::ALL
+                .into_iter()
+                .find(|sfx| path.file_stem().is_some_and(|s| s == sfx.key()))
+            else {
+                return;
+            };
+            self.sounds.retain(|(s, _)| *s != sfx);
+            if path.exists() {
+                if let Some(sound) = load(audio, path) {
+                    self.sounds.push((sfx, sound));
+                }
+            }
+        }
+
+        // `volume` runs from 0.0 to 1.0.
+        pub fn play(&mut self, cue: &Cue, volume: f32) {
+            if let Some((_, sound)) = self.sounds.iter_mut().find(|(s, _)| *s == cue.sfx) {
+                sound.set_volume(volume);
+                sound.set_pitch(cue.pitch);
+                // raylib pans from 1.0 (left) to 0.0 (right).
+                sound.set_pan(0.5 - cue.pan / 2.0);
+                sound.play();
+            }
+        }
+    }
+
+    fn load<'a>(audio: &'a RaylibAudio, path: &Path) -> Option<Sound<'a>> {
+        match audio.new_sound(&path.to_string_lossy()) {
+            Ok(sound) => {
+                log::info!("loaded {}", path.display());
+                Some(sound)
+            }
+            Err(e) => {
+                log::warn!("could not load {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod imp {
+    use super::Cue;
+    use raylib::prelude::RaylibAudio;
+    use std::marker::PhantomData;
+    use std::path::Path;
+
+    pub struct SoundBank<'a>(PhantomData<&'a RaylibAudio>);
+
+    impl<'a> SoundBank<'a> {
+        pub fn load(_audio: Option<&'a RaylibAudio>) -> Self {
+            Self(PhantomData)
+        }
+
+        pub fn reload(&mut self, _path: &Path) {}
+
+        pub fn play(&mut self, _cue: &Cue, _volume: f32) {}
+    }
+}
+
+pub use imp::SoundBank;
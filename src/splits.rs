@@ -0,0 +1,197 @@
+// The speedrun timer's splits: the run time at the end of every level. Runs
+// that start on the first level are compared against the fastest full run of
+// the same category, kept in `splits.toml` and exported for LiveSplit as
+// `pong-<category>.lss` whenever it is beaten.
+use crate::challenge::Category;
+use crate::config::{Table, Value, Writer};
+use crate::game::format_time;
+use crate::level::Level;
+use crate::paths;
+use crate::text::Text;
+use raylib::prelude::*;
+use std::fs;
+use std::time::Duration;
+
+const SPLITS_FILE: &str = "splits.toml";
+// Splits shown above the current level's.
+const SHOWN_SPLITS: usize = 3;
+const FONT_SIZE: i32 = 18;
+const LINE_HEIGHT: i32 = 22;
+
+pub struct Splits {
+    category: Category,
+    first_level: usize,
+    times: Vec<Duration>,
+    // The personal best's splits, when there is one to compare against.
+    best: Vec<Duration>,
+}
+
+impl Splits {
+    pub fn new(category: Category, first_level: usize, level_count: usize) -> Self {
+        let table = fs::read_to_string(paths::data_dir().join(SPLITS_FILE))
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let best: Vec<Duration> = (1..)
+            .map_while(|i| table.get_int(&format!("{}.level{}_ms", category.key(), i)))
+            .map(|ms| Duration::from_millis(ms as u64))
+            .collect();
+        Self {
+            category,
+            first_level,
+            times: Vec::new(),
+            best: if first_level == 0 && best.len() == level_count {
+                best
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    pub fn split(&mut self, time: Duration) {
+        self.times.push(time);
+    }
+
+    // How far behind the personal best a run is at `time` into the `i`th level
+    // of the run, in seconds; negative when ahead.
+    fn delta(&self, i: usize, time: Duration) -> Option<f32> {
+        let best = self.best.get(i)?;
+        Some(time.as_secs_f32() - best.as_secs_f32())
+    }
+
+    // Keeps the run as the personal best if it cleared every level faster.
+    pub fn finish(&mut self, levels: &[Level]) {
+        if self.first_level != 0 || self.times.len() != levels.len() {
+            return;
+        }
+        if self
+            .best
+            .last()
+            .is_some_and(|best| best <= self.times.last().unwrap())
+        {
+            return;
+        }
+        self.best = self.times.clone();
+
+        let path = paths::data_dir().join(SPLITS_FILE);
+        let table = fs::read_to_string(&path)
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let mut writer = Writer::default();
+        for category in Category::all() {
+            let key = category.key();
+            let times: Vec<i64> = if category == self.category {
+                self.best.iter().map(|t| t.as_millis() as i64).collect()
+            } else {
+                (1..)
+                    .map_while(|i| table.get_int(&format!("{}.level{}_ms", key, i)))
+                    .collect()
+            };
+            if times.is_empty() {
+                continue;
+            }
+            writer.section(&key);
+            for (i, ms) in times.into_iter().enumerate() {
+                writer.value(&format!("level{}_ms", i + 1), Value::Int(ms));
+            }
+        }
+        if let Err(e) = fs::write(&path, writer.finish()) {
+            log::error!("could not write {}: {}", path.display(), e);
+        }
+
+        let path = paths::data_dir().join(format!("pong-{}.lss", self.category.key()));
+        if let Err(e) = fs::write(&path, self.livesplit(levels)) {
+            log::error!("could not write {}: {}", path.display(), e);
+        }
+    }
+
+    // The personal best as a LiveSplit splits file. The game only keeps game
+    // time, so it is given as the real time too.
+    fn livesplit(&self, levels: &[Level]) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml += "<Run version=\"1.7.0\">\n";
+        xml += "  <GameIcon />\n  <GameName>Pong</GameName>\n";
+        xml += &format!("  <CategoryName>{}</CategoryName>\n", self.category.name());
+        xml += "  <Offset>00:00:00</Offset>\n  <AttemptCount>0</AttemptCount>\n";
+        xml += "  <AttemptHistory />\n  <Segments>\n";
+        let mut previous = Duration::ZERO;
+        for (i, (level, time)) in levels.iter().zip(&self.best).enumerate() {
+            xml += "    <Segment>\n";
+            xml += &format!(
+                "      <Name>{}</Name>\n      <Icon />\n",
+                escape(&format!("{}. {}", i + 1, level.name))
+            );
+            xml += "      <SplitTimes>\n        <SplitTime name=\"Personal Best\">\n";
+            xml += &lss_times("          ", *time);
+            xml += "        </SplitTime>\n      </SplitTimes>\n";
+            xml += "      <BestSegmentTime>\n";
+            xml += &lss_times("        ", time.saturating_sub(previous));
+            xml += "      </BestSegmentTime>\n      <SegmentHistory />\n";
+            xml += "    </Segment>\n";
+            previous = *time;
+        }
+        xml += "  </Segments>\n  <AutoSplitterSettings />\n</Run>\n";
+        xml
+    }
+
+    // The last few splits, then the level being played with the run's time.
+    pub fn render(
+        &self,
+        d: &mut impl RaylibDraw,
+        font: &ffi::Font,
+        levels: &[Level],
+        time: Duration,
+        (x, y): (i32, i32),
+    ) {
+        let current = self.times.len();
+        let line = |d: &mut _, row: i32, i: usize, time: Duration, color: Color| {
+            let name = levels
+                .get(self.first_level + i)
+                .map_or("", |l| l.name.as_str());
+            let y = y + row * LINE_HEIGHT;
+            Text::new(name, FONT_SIZE, color).draw(d, font, x, y);
+            Text::new(&format_time(&time), FONT_SIZE, color).draw(d, font, x + 150, y);
+            if let Some(delta) = self.delta(i, time) {
+                let (sign, color) = if delta < 0.0 {
+                    ('-', Color::GREEN)
+                } else {
+                    ('+', Color::RED)
+                };
+                let delta = format!(
+                    "{}{}",
+                    sign,
+                    format_time(&Duration::from_secs_f32(delta.abs()))
+                );
+                Text::new(&delta, FONT_SIZE, color).draw(d, font, x + 240, y);
+            }
+        };
+        let first = current.saturating_sub(SHOWN_SPLITS);
+        for (row, i) in (first..current).enumerate() {
+            line(d, row as i32, i, self.times[i], Color::LIGHTGRAY);
+        }
+        let row = (current - first) as i32;
+        if self.first_level + current < levels.len() {
+            line(d, row, current, time, Color::WHITE);
+        }
+    }
+}
+
+fn lss_times(indent: &str, time: Duration) -> String {
+    let secs = time.as_secs();
+    let stamp = format!(
+        "{:02}:{:02}:{:02}.{:07}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_nanos() / 100
+    );
+    format!(
+        "{0}<RealTime>{1}</RealTime>\n{0}<GameTime>{1}</GameTime>\n",
+        indent, stamp
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
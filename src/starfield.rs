@@ -0,0 +1,48 @@
+use crate::rng::Rng;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::time::Duration;
+
+struct Star {
+    position: Vector2,
+    // 0..1, where nearer (higher) stars are bigger, brighter and faster.
+    depth: f32,
+}
+
+// A field of stars drifting down the screen at different depths for a parallax look.
+pub struct Starfield {
+    stars: Vec<Star>,
+    speed: f32,
+}
+
+impl Starfield {
+    pub fn new(count: usize, speed: f32) -> Self {
+        let mut rng = Rng::from_time();
+        let stars = (0..count)
+            .map(|_| Star {
+                position: Vector2::new(rng.range(0.0, WINDOW_WIDTH), rng.range(0.0, WINDOW_HEIGHT)),
+                depth: rng.range(0.2, 1.0),
+            })
+            .collect();
+        Self { stars, speed }
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        for star in self.stars.iter_mut() {
+            star.position.y += self.speed * star.depth * duration.as_secs_f32();
+            if star.position.y > WINDOW_HEIGHT {
+                star.position.y -= WINDOW_HEIGHT;
+            }
+        }
+    }
+
+    pub fn render(&self, d: &mut impl RaylibDraw, color: Color) {
+        for star in &self.stars {
+            d.draw_circle_v(
+                star.position,
+                0.5 + 1.5 * star.depth,
+                color.alpha(0.2 + 0.6 * star.depth),
+            );
+        }
+    }
+}
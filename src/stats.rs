@@ -0,0 +1,398 @@
+use crate::config::{Table, Value, Writer};
+use crate::game::format_time;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::mode::GameMode;
+use crate::music::Track;
+use crate::paths;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::Text;
+use crate::ui::{self, Bar};
+use crate::window::group_thousands;
+use crate::WINDOW_WIDTH;
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const LIFETIME_FILE: &str = "lifetime.toml";
+
+// Counters collected over a whole run, shown on the summary screen once it ends.
+#[derive(Clone, Default)]
+pub struct RunStats {
+    pub score: u32,
+    pub bricks_destroyed: u32,
+    pub longest_combo: u32,
+    pub max_ball_speed: f32,
+    pub time_played: Duration,
+    pub powerups_collected: u32,
+    pub balls_lost: u32,
+    pub fastest_clear: Option<Duration>,
+    pub levels_cleared: u32,
+    // Levels cleared without losing a ball.
+    pub flawless_clears: u32,
+    combo: u32,
+    level_start_balls_lost: u32,
+}
+
+impl RunStats {
+    // A combo is the number of brick hits in a row without the ball touching the racket.
+    pub fn brick_hit(&mut self) {
+        self.combo += 1;
+        self.longest_combo = self.longest_combo.max(self.combo);
+    }
+
+    pub fn brick_destroyed(&mut self, points: u32) {
+        self.bricks_destroyed += 1;
+        self.score += points;
+    }
+
+    // Bumpers score without breaking the combo or counting as bricks.
+    pub fn bumper_hit(&mut self, points: u32) {
+        self.score += points;
+    }
+
+    // Gems caught in a bonus stage only add to the score.
+    pub fn gem_caught(&mut self, points: u32) {
+        self.score += points;
+    }
+
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn break_combo(&mut self) {
+        self.combo = 0;
+    }
+
+    pub fn ball_speed(&mut self, speed: f32) {
+        self.max_ball_speed = self.max_ball_speed.max(speed);
+    }
+
+    pub fn level_cleared(&mut self, time: Duration) {
+        self.fastest_clear = Some(self.fastest_clear.map_or(time, |t| t.min(time)));
+        self.levels_cleared += 1;
+        if self.balls_lost == self.level_start_balls_lost {
+            self.flawless_clears += 1;
+        }
+        self.level_start_balls_lost = self.balls_lost;
+    }
+
+    pub fn from_table(table: &Table, section: &str) -> Self {
+        let int = |key: &str| table.get_int(&format!("{}.{}", section, key)).unwrap_or(0);
+        Self {
+            score: int("score") as u32,
+            bricks_destroyed: int("bricks_destroyed") as u32,
+            longest_combo: int("longest_combo") as u32,
+            max_ball_speed: table
+                .get_float(&format!("{}.max_ball_speed", section))
+                .unwrap_or(0.0) as f32,
+            time_played: Duration::from_millis(int("time_played_ms") as u64),
+            powerups_collected: int("powerups_collected") as u32,
+            balls_lost: int("balls_lost") as u32,
+            fastest_clear: table
+                .get_int(&format!("{}.fastest_clear_ms", section))
+                .map(|ms| Duration::from_millis(ms as u64)),
+            levels_cleared: int("levels_cleared") as u32,
+            flawless_clears: int("flawless_clears") as u32,
+            combo: int("combo") as u32,
+            level_start_balls_lost: int("level_start_balls_lost") as u32,
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer, section: &str) {
+        writer
+            .section(section)
+            .value("score", Value::Int(self.score as i64))
+            .value("bricks_destroyed", Value::Int(self.bricks_destroyed as i64))
+            .value("longest_combo", Value::Int(self.longest_combo as i64))
+            .value("max_ball_speed", Value::Float(self.max_ball_speed as f64))
+            .value(
+                "time_played_ms",
+                Value::Int(self.time_played.as_millis() as i64),
+            )
+            .value(
+                "powerups_collected",
+                Value::Int(self.powerups_collected as i64),
+            )
+            .value("balls_lost", Value::Int(self.balls_lost as i64))
+            .value("levels_cleared", Value::Int(self.levels_cleared as i64))
+            .value("flawless_clears", Value::Int(self.flawless_clears as i64))
+            .value("combo", Value::Int(self.combo as i64))
+            .value(
+                "level_start_balls_lost",
+                Value::Int(self.level_start_balls_lost as i64),
+            );
+        if let Some(time) = self.fastest_clear {
+            writer.value("fastest_clear_ms", Value::Int(time.as_millis() as i64));
+        }
+    }
+}
+
+// One mode's share of the lifetime totals.
+#[derive(Clone, Default)]
+pub struct ModeTotals {
+    pub runs: u32,
+    pub bricks_broken: u64,
+    pub playtime: Duration,
+    pub best_score: u32,
+}
+
+// Totals over every run ever played, kept in `lifetime.toml`, with a
+// `[lifetime.<mode>]` section for each mode played.
+pub struct LifetimeStats {
+    path: PathBuf,
+    pub runs: u32,
+    pub bricks_broken: u64,
+    pub balls_lost: u64,
+    pub playtime: Duration,
+    pub highest_combo: u32,
+    pub fastest_clear: Option<Duration>,
+    pub best_score: u32,
+    pub modes: HashMap<GameMode, ModeTotals>,
+}
+
+impl LifetimeStats {
+    pub fn load() -> Self {
+        let path = paths::file(paths::data_dir(), LIFETIME_FILE);
+        let table = fs::read_to_string(&path)
+            .map(|text| Table::parse(&text))
+            .unwrap_or_default();
+        let int = |key: &str| table.get_int(&format!("lifetime.{}", key));
+        Self {
+            runs: int("runs").unwrap_or(0) as u32,
+            bricks_broken: int("bricks_broken").unwrap_or(0) as u64,
+            balls_lost: int("balls_lost").unwrap_or(0) as u64,
+            playtime: Duration::from_millis(int("playtime_ms").unwrap_or(0) as u64),
+            highest_combo: int("highest_combo").unwrap_or(0) as u32,
+            fastest_clear: int("fastest_clear_ms").map(|ms| Duration::from_millis(ms as u64)),
+            best_score: int("best_score").unwrap_or(0) as u32,
+            modes: GameMode::ALL
+                .iter()
+                .filter_map(|mode| {
+                    let int =
+                        |key: &str| table.get_int(&format!("lifetime.{}.{}", mode.key(), key));
+                    let totals = ModeTotals {
+                        runs: int("runs")? as u32,
+                        bricks_broken: int("bricks_broken").unwrap_or(0) as u64,
+                        playtime: Duration::from_millis(int("playtime_ms").unwrap_or(0) as u64),
+                        best_score: int("best_score").unwrap_or(0) as u32,
+                    };
+                    Some((*mode, totals))
+                })
+                .collect(),
+            path,
+        }
+    }
+
+    // Adds a finished run to the totals and saves them.
+    pub fn record(&mut self, mode: GameMode, run: &RunStats) {
+        self.runs += 1;
+        self.bricks_broken += run.bricks_destroyed as u64;
+        self.balls_lost += run.balls_lost as u64;
+        self.playtime += run.time_played;
+        self.highest_combo = self.highest_combo.max(run.longest_combo);
+        if let Some(time) = run.fastest_clear {
+            self.fastest_clear = Some(self.fastest_clear.map_or(time, |t| t.min(time)));
+        }
+        self.best_score = self.best_score.max(run.score);
+        let totals = self.modes.entry(mode).or_default();
+        totals.runs += 1;
+        totals.bricks_broken += run.bricks_destroyed as u64;
+        totals.playtime += run.time_played;
+        totals.best_score = totals.best_score.max(run.score);
+        self.save();
+    }
+
+    pub fn mode(&self, mode: GameMode) -> ModeTotals {
+        self.modes.get(&mode).cloned().unwrap_or_default()
+    }
+
+    fn save(&self) {
+        let mut writer = Writer::default();
+        writer
+            .section("lifetime")
+            .value("runs", Value::Int(self.runs as i64))
+            .value("bricks_broken", Value::Int(self.bricks_broken as i64))
+            .value("balls_lost", Value::Int(self.balls_lost as i64))
+            .value("playtime_ms", Value::Int(self.playtime.as_millis() as i64))
+            .value("highest_combo", Value::Int(self.highest_combo as i64))
+            .value("best_score", Value::Int(self.best_score as i64));
+        if let Some(time) = self.fastest_clear {
+            writer.value("fastest_clear_ms", Value::Int(time.as_millis() as i64));
+        }
+        for mode in GameMode::ALL {
+            let Some(totals) = self.modes.get(&mode) else {
+                continue;
+            };
+            writer
+                .section(&format!("lifetime.{}", mode.key()))
+                .value("runs", Value::Int(totals.runs as i64))
+                .value("bricks_broken", Value::Int(totals.bricks_broken as i64))
+                .value(
+                    "playtime_ms",
+                    Value::Int(totals.playtime.as_millis() as i64),
+                )
+                .value("best_score", Value::Int(totals.best_score as i64));
+        }
+        if let Err(e) = fs::write(&self.path, writer.finish()) {
+            log::error!("could not write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+// Hours and minutes, for play time too long for `format_time`.
+pub fn format_playtime(duration: &Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+// What the bars of the per-mode chart measure; left and right switch
+// between them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Chart {
+    Runs,
+    Playtime,
+    Bricks,
+    BestScore,
+}
+
+impl Chart {
+    const ALL: [Chart; 4] = [
+        Chart::Runs,
+        Chart::Playtime,
+        Chart::Bricks,
+        Chart::BestScore,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Chart::Runs => tr!("stats.runs"),
+            Chart::Playtime => tr!("stats.playtime"),
+            Chart::Bricks => tr!("stats.bricks"),
+            Chart::BestScore => tr!("stats.best_score"),
+        }
+    }
+
+    fn bar(&self, mode: GameMode, totals: &ModeTotals) -> Bar {
+        let (value, text) = match self {
+            Chart::Runs => (totals.runs as f32, totals.runs.to_string()),
+            Chart::Playtime => (
+                totals.playtime.as_secs_f32(),
+                format_playtime(&totals.playtime),
+            ),
+            Chart::Bricks => (
+                totals.bricks_broken as f32,
+                totals.bricks_broken.to_string(),
+            ),
+            Chart::BestScore => (totals.best_score as f32, group_thousands(totals.best_score)),
+        };
+        Bar {
+            label: mode.name().into(),
+            value,
+            text,
+        }
+    }
+}
+
+// The lifetime totals and bests on the left, and a chart of how they split
+// between the modes on the right.
+pub struct StatsScreen {
+    chart: usize,
+}
+
+impl StatsScreen {
+    pub fn new() -> Self {
+        Self { chart: 0 }
+    }
+}
+
+impl Scene for StatsScreen {
+    fn update(&mut self, _ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) || input.pressed(Action::Confirm) {
+            return Transition::Pop;
+        }
+        let delta = input.pressed(Action::Right) as isize - input.pressed(Action::Left) as isize;
+        self.chart = (self.chart as isize + delta).rem_euclid(Chart::ALL.len() as isize) as usize;
+        Transition::None
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        let stats = &ctx.lifetime;
+        d.clear_background(Color::BLACK);
+        ui::heading(d, font, tr!("stats.title"), 60);
+
+        let left = WINDOW_WIDTH as i32 / 2 - 580;
+        let rows = [
+            (tr!("stats.totals"), None),
+            (tr!("stats.runs"), Some(stats.runs.to_string())),
+            (
+                tr!("stats.playtime"),
+                Some(format_playtime(&stats.playtime)),
+            ),
+            (tr!("stats.bricks"), Some(stats.bricks_broken.to_string())),
+            (tr!("stats.balls_lost"), Some(stats.balls_lost.to_string())),
+            (tr!("stats.bests"), None),
+            (
+                tr!("stats.best_score"),
+                Some(group_thousands(stats.best_score)),
+            ),
+            (
+                tr!("stats.highest_combo"),
+                Some(stats.highest_combo.to_string()),
+            ),
+            (
+                tr!("stats.fastest_clear"),
+                Some(stats.fastest_clear.as_ref().map_or("-".into(), format_time)),
+            ),
+        ];
+        let mut y = 180;
+        for (label, value) in rows {
+            match value {
+                // Headings get a gap above them, except the first.
+                None => {
+                    if y > 180 {
+                        y += 16;
+                    }
+                    Text::new(label, 24, Color::GRAY).draw(d, font, left, y);
+                }
+                Some(value) => {
+                    Text::new(label, 28, Color::WHITE).draw(d, font, left, y);
+                    Text::new(&value, 28, Color::YELLOW).draw(d, font, left + 300, y);
+                }
+            }
+            y += 38;
+        }
+
+        let chart = Chart::ALL[self.chart];
+        let x = WINDOW_WIDTH / 2.0 - 20.0;
+        ui::hint(d, font, &format!("< {} >", chart.name()), 150);
+        let bars: Vec<Bar> = GameMode::ALL
+            .iter()
+            .map(|mode| chart.bar(*mode, &stats.mode(*mode)))
+            .collect();
+        ui::bar_chart(
+            d,
+            font,
+            Rectangle {
+                x,
+                y: 190.0,
+                width: 600.0,
+                height: 360.0,
+            },
+            &bars,
+            Color::SKYBLUE,
+        );
+        if stats.runs == 0 {
+            ui::hint(d, font, tr!("stats.empty"), 580);
+        }
+        ui::hint(d, font, tr!("stats.hint"), 650);
+    }
+
+    fn music(&self, _ctx: &Context) -> Option<Track> {
+        Some(Track::Menu)
+    }
+}
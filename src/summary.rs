@@ -0,0 +1,140 @@
+use crate::game::{format_time, Game};
+use crate::input::Input;
+use crate::lang::tr;
+use crate::run_code::RunCode;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::stats::RunStats;
+use crate::text::{draw_centered, Align, Text};
+use crate::ui::{Event, Item, List};
+use crate::versus::Lobby;
+use crate::WINDOW_WIDTH;
+use raylib::prelude::*;
+use std::time::Duration;
+
+const SUMMARY_PLAY_AGAIN: usize = 0;
+const SUMMARY_MENU: usize = 1;
+
+pub struct RunSummary {
+    stats: RunStats,
+    won: bool,
+    restart_level: usize,
+    rank: Option<usize>,
+    // After a versus race, playing again goes back to the lobby.
+    rematch: bool,
+    // Shown so the run can be played again from the menu or with `--seed`.
+    code: Option<RunCode>,
+    list: List,
+}
+
+impl RunSummary {
+    pub fn new(stats: RunStats, won: bool, restart_level: usize, rank: Option<usize>) -> Self {
+        Self {
+            stats,
+            won,
+            restart_level,
+            rank,
+            rematch: false,
+            code: None,
+            list: List::new(
+                vec![
+                    Item::button(tr!("summary.play_again")),
+                    Item::button(tr!("summary.main_menu")),
+                ],
+                520.0,
+            ),
+        }
+    }
+}
+
+impl RunSummary {
+    pub fn rematch(mut self, rematch: bool) -> Self {
+        self.rematch = rematch;
+        self
+    }
+
+    pub fn code(mut self, code: RunCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+}
+
+impl Scene for RunSummary {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        match self.list.update(input) {
+            Some(Event::Pressed(SUMMARY_PLAY_AGAIN)) if self.rematch => {
+                Transition::fade(Transition::Replace(Box::new(Lobby::new())))
+            }
+            Some(Event::Pressed(SUMMARY_PLAY_AGAIN)) => Transition::fade(Transition::Replace(
+                Box::new(Game::new(ctx, self.restart_level)),
+            )),
+            Some(Event::Pressed(SUMMARY_MENU)) => Transition::fade(Transition::Pop),
+            _ => Transition::None,
+        }
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        let (title, color) = if self.won {
+            (tr!("game.won"), Color::YELLOW)
+        } else {
+            (tr!("game.over"), Color::RED)
+        };
+        draw_centered(d, font, title, 50, 70, color);
+        if let Some(rank) = self.rank {
+            draw_centered(
+                d,
+                font,
+                &tr!("summary.high_score", rank + 1),
+                130,
+                24,
+                Color::GREEN,
+            );
+        }
+
+        let rows = [
+            (tr!("summary.score"), self.stats.score.to_string()),
+            (
+                tr!("summary.bricks_destroyed"),
+                self.stats.bricks_destroyed.to_string(),
+            ),
+            (
+                tr!("summary.longest_combo"),
+                self.stats.longest_combo.to_string(),
+            ),
+            (
+                tr!("summary.max_ball_speed"),
+                format!("{:.0}", self.stats.max_ball_speed),
+            ),
+            (
+                tr!("summary.time_played"),
+                format_time(&self.stats.time_played),
+            ),
+            (
+                tr!("summary.powerups_collected"),
+                self.stats.powerups_collected.to_string(),
+            ),
+        ];
+        let left = WINDOW_WIDTH as i32 / 2 - 260;
+        for (i, (label, value)) in rows.iter().enumerate() {
+            let y = 190 + i as i32 * 50;
+            Text::new(label, 30, Color::LIGHTGRAY).draw(d, font, left, y);
+            Text::new(value, 30, Color::WHITE)
+                .align(Align::Right)
+                .draw(d, font, left + 520, y);
+        }
+
+        if let Some(code) = self.code {
+            draw_centered(
+                d,
+                font,
+                &tr!("summary.code", code, code.seed_string()),
+                480,
+                20,
+                Color::GRAY,
+            );
+        }
+        self.list.render(d, font);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use raylib::prelude::*;
+use std::ffi::CString;
+
+const BANNER_SIZE: i32 = 50;
+
+#[derive(Clone, Copy)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// Lets a borrowed raw font go where raylib wants `impl AsRef<ffi::Font>`, so
+// loaded fonts and raylib's built-in one can be handled the same way.
+struct FontRef<'a>(&'a ffi::Font);
+
+impl AsRef<ffi::Font> for FontRef<'_> {
+    fn as_ref(&self) -> &ffi::Font {
+        self.0
+    }
+}
+
+// A line of text to draw. `x` is the left edge, centre or right edge depending
+// on the alignment; `y` is always the top.
+pub struct Text<'a> {
+    text: &'a str,
+    size: f32,
+    color: Color,
+    align: Align,
+    outline: Option<Color>,
+}
+
+impl<'a> Text<'a> {
+    pub fn new(text: &'a str, size: i32, color: Color) -> Self {
+        Self {
+            text,
+            size: size as f32,
+            color,
+            align: Align::Left,
+            outline: None,
+        }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn outline(mut self, color: Color) -> Self {
+        self.outline = Some(color);
+        self
+    }
+
+    // The same spacing raylib's own draw_text uses.
+    fn spacing(&self) -> f32 {
+        self.size / 10.0
+    }
+
+    pub fn width(&self, font: &ffi::Font) -> f32 {
+        let Ok(text) = CString::new(self.text) else {
+            return 0.0;
+        };
+        unsafe { ffi::MeasureTextEx(*font, text.as_ptr(), self.size, self.spacing()).x }
+    }
+
+    pub fn draw(&self, d: &mut impl RaylibDraw, font: &ffi::Font, x: i32, y: i32) {
+        let left = match self.align {
+            Align::Left => x as f32,
+            Align::Center => x as f32 - self.width(font) / 2.0,
+            Align::Right => x as f32 - self.width(font),
+        };
+        let position = Vector2::new(left, y as f32);
+
+        if let Some(color) = self.outline {
+            let thickness = (self.size / 20.0).max(1.0);
+            for (dx, dy) in [
+                (-1.0, -1.0),
+                (0.0, -1.0),
+                (1.0, -1.0),
+                (-1.0, 0.0),
+                (1.0, 0.0),
+                (-1.0, 1.0),
+                (0.0, 1.0),
+                (1.0, 1.0),
+            ] {
+                d.draw_text_ex(
+                    FontRef(font),
+                    self.text,
+                    position + Vector2::new(dx, dy) * thickness,
+                    self.size,
+                    self.spacing(),
+                    color,
+                );
+            }
+        }
+        d.draw_text_ex(
+            FontRef(font),
+            self.text,
+            position,
+            self.size,
+            self.spacing(),
+            self.color,
+        );
+    }
+}
+
+// Horizontally centred on the screen.
+pub fn draw_centered(
+    d: &mut impl RaylibDraw,
+    font: &ffi::Font,
+    s: &str,
+    y: i32,
+    size: i32,
+    color: Color,
+) {
+    Text::new(s, size, color)
+        .align(Align::Center)
+        .draw(d, font, (WINDOW_WIDTH / 2.0) as i32, y);
+}
+
+// Big outlined text in the middle of the screen, for game state messages.
+pub fn draw_banner(d: &mut impl RaylibDraw, font: &ffi::Font, s: &str) {
+    Text::new(s, BANNER_SIZE, Color::YELLOW)
+        .align(Align::Center)
+        .outline(Color::BLACK)
+        .draw(
+            d,
+            font,
+            (WINDOW_WIDTH / 2.0) as i32,
+            (WINDOW_HEIGHT / 2.0) as i32 - BANNER_SIZE / 2,
+        );
+}
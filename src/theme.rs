@@ -0,0 +1,198 @@
+use crate::config::Table;
+use crate::lang::tr;
+use crate::level::MAX_BRICK_HP;
+use crate::postfx::Effect;
+use raylib::prelude::*;
+use std::fs;
+use std::path::Path;
+
+const THEMES_DIR: &str = "themes";
+const BUILTIN: [&str; 2] = [
+    include_str!("../themes/neon.theme"),
+    include_str!("../themes/paper.theme"),
+];
+
+// Brick colours by hit points, from 1 up to MAX_BRICK_HP.
+pub const CLASSIC_BRICKS: [Color; MAX_BRICK_HP] = [
+    Color::new(0xFF, 0xFF, 0, 0xFF),
+    Color::new(0, 0xFF, 0, 0xFF),
+    Color::new(0, 0xFF, 0xFF, 0xFF),
+    Color::new(0, 0, 0xFF, 0xFF),
+    Color::new(0xFF, 0, 0xFF, 0xFF),
+];
+
+const CLASSIC_BRICK_SHADES: [Color; MAX_BRICK_HP] = [
+    Color::new(0x3F, 0x2F, 0, 0xFF),
+    Color::new(0, 0x3F, 0, 0xFF),
+    Color::new(0, 0x3F, 0x3F, 0xFF),
+    Color::new(0, 0, 0x3F, 0xFF),
+    Color::new(0x3F, 0, 0x3F, 0xFF),
+];
+
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub stars: Color,
+    pub ball: Color,
+    pub paddle: Color,
+    pub paddle_shade: Color,
+    pub sparks: Color,
+    pub text: Color,
+    pub hint: Color,
+    // File name of a font in `assets/fonts/`.
+    pub font: Option<String>,
+    // Post-processing applied while the theme is active.
+    pub effects: Vec<Effect>,
+    bricks: [Color; MAX_BRICK_HP],
+    brick_shades: [Color; MAX_BRICK_HP],
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            name: "Classic".into(),
+            background: Color::BLACK,
+            stars: Color::WHITE,
+            ball: Color::WHITE,
+            paddle: Color::RED,
+            paddle_shade: Color::new(80, 0, 0, 255),
+            sparks: Color::new(0xFF, 0xE0, 0x80, 0xFF),
+            text: Color::WHITE,
+            hint: Color::GRAY,
+            font: None,
+            effects: Vec::new(),
+            bricks: CLASSIC_BRICKS,
+            brick_shades: CLASSIC_BRICK_SHADES,
+        }
+    }
+
+    // Solid, saturated colours on black, used instead of the selected theme
+    // when high contrast mode is on.
+    pub fn high_contrast() -> Self {
+        let bricks = [
+            Color::YELLOW,
+            Color::new(0, 0xFF, 0, 0xFF),
+            Color::new(0, 0xFF, 0xFF, 0xFF),
+            Color::new(0x40, 0x80, 0xFF, 0xFF),
+            Color::new(0xFF, 0, 0xFF, 0xFF),
+        ];
+        Self {
+            name: tr!("settings.high_contrast").into(),
+            background: Color::BLACK,
+            stars: Color::BLACK,
+            ball: Color::WHITE,
+            paddle: Color::YELLOW,
+            paddle_shade: Color::YELLOW,
+            sparks: Color::WHITE,
+            text: Color::WHITE,
+            hint: Color::WHITE,
+            font: None,
+            effects: Vec::new(),
+            bricks,
+            brick_shades: bricks,
+        }
+    }
+
+    // A theme file is a `[theme]` section with a name and colours, plus a
+    // `[bricks]` section with one colour (and optional `_shade`) per hit point.
+    // Anything left out keeps its Classic value. Colours are "#RRGGBB" or "#RRGGBBAA".
+    // An optional `font` names a file in `assets/fonts/`, and `effects` lists
+    // post-processing shaders by name, e.g. "bloom, chromatic".
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let table = Table::parse(text);
+        let mut theme = Self::classic();
+        theme.name = table
+            .get_str("theme.name")
+            .ok_or("missing theme name")?
+            .to_string();
+
+        let color = |key: &str, default: Color| -> Result<Color, String> {
+            match table.get_str(key) {
+                Some(s) => parse_color(s).ok_or(format!("bad colour \"{}\" for {}", s, key)),
+                None => Ok(default),
+            }
+        };
+        theme.background = color("theme.background", theme.background)?;
+        theme.stars = color("theme.stars", theme.stars)?;
+        theme.ball = color("theme.ball", theme.ball)?;
+        theme.paddle = color("theme.paddle", theme.paddle)?;
+        theme.paddle_shade = color("theme.paddle_shade", theme.paddle_shade)?;
+        theme.sparks = color("theme.sparks", theme.sparks)?;
+        theme.text = color("theme.text", theme.text)?;
+        theme.hint = color("theme.hint", theme.hint)?;
+        theme.font = table.get_str("theme.font").map(str::to_string);
+        if let Some(list) = table.get_str("theme.effects") {
+            theme.effects = list
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    Effect::ALL
+                        .into_iter()
+                        .find(|e| e.key() == s)
+                        .ok_or(format!("unknown effect \"{}\"", s))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        for hp in 1..=MAX_BRICK_HP {
+            theme.bricks[hp - 1] = color(&format!("bricks.{}", hp), theme.bricks[hp - 1])?;
+            theme.brick_shades[hp - 1] =
+                color(&format!("bricks.{}_shade", hp), theme.brick_shades[hp - 1])?;
+        }
+        Ok(theme)
+    }
+
+    // Returns the top and bottom gradient colours of a brick with `hp` hit points left.
+    pub fn brick(&self, hp: usize) -> (Color, Color) {
+        let i = hp.clamp(1, MAX_BRICK_HP) - 1;
+        (self.bricks[i], self.brick_shades[i])
+    }
+}
+
+fn parse_color(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::new(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        if hex.len() == 8 { channel(6)? } else { 0xFF },
+    ))
+}
+
+// Classic first, then the other built-in themes, then any `*.theme` files in
+// the themes directory whose names are not taken yet.
+pub fn load_all() -> Vec<Theme> {
+    let mut themes = vec![Theme::classic()];
+    themes.extend(
+        BUILTIN
+            .iter()
+            .map(|text| Theme::parse(text).expect("built-in theme is valid")),
+    );
+
+    let Ok(entries) = fs::read_dir(THEMES_DIR) else {
+        return themes;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "theme"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        match load_file(&path) {
+            Ok(theme) if themes.iter().all(|t| t.name != theme.name) => themes.push(theme),
+            Ok(_) => (),
+            Err(e) => log::warn!("could not load theme {}: {}", path.display(), e),
+        }
+    }
+    themes
+}
+
+fn load_file(path: &Path) -> Result<Theme, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Theme::parse(&text)
+}
@@ -0,0 +1,42 @@
+use raylib::prelude::*;
+
+const TRAIL_CAPACITY: usize = 32;
+
+// The last few ball positions, newest at `head - 1`, kept in a fixed ring buffer.
+pub struct Trail {
+    points: [Vector2; TRAIL_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl Trail {
+    pub fn new() -> Self {
+        Self {
+            points: [Vector2::zero(); TRAIL_CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, point: Vector2) {
+        self.points[self.head] = point;
+        self.head = (self.head + 1) % TRAIL_CAPACITY;
+        self.len = (self.len + 1).min(TRAIL_CAPACITY);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    // `speed` is the ball speed relative to its base speed: faster balls get
+    // longer and more opaque trails so their path is easier to read.
+    pub fn render(&self, d: &mut impl RaylibDraw, speed: f32, radius: f32, color: Color) {
+        let length = ((speed * 10.0) as usize).min(self.len);
+        let opacity = (0.2 + 0.2 * speed).min(0.7);
+        for i in 1..=length {
+            let point = self.points[(self.head + TRAIL_CAPACITY - i) % TRAIL_CAPACITY];
+            let t = 1.0 - i as f32 / (length + 1) as f32;
+            d.draw_circle_v(point, radius * (0.4 + 0.6 * t), color.alpha(opacity * t));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::config::{Table, Value, Writer};
+use crate::settings::Difficulty;
+use crate::WINDOW_WIDTH;
+
+// The lowest a brick reaches, leaving the ball room above the racket.
+const BRICKS_BOTTOM: f32 = 520.0;
+
+// Gameplay numbers from the `[gameplay]` section of the config file. Speeds
+// are in pixels per second at the logical 1280x720 resolution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuning {
+    pub paddle_speed: f32,
+    pub ball_speed: f32,
+    // Added to the ball speed by every wall or paddle bounce, and by every
+    // brick hit.
+    pub bounce_speedup: f32,
+    pub brick_speedup: f32,
+    pub brick_height: f32,
+    pub brick_gap: f32,
+    pub bricks_top: f32,
+    pub lives_easy: usize,
+    pub lives_normal: usize,
+    pub lives_hard: usize,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            paddle_speed: 700.0,
+            ball_speed: 500.0,
+            bounce_speedup: 2.0,
+            brick_speedup: 4.0,
+            brick_height: 32.0,
+            brick_gap: 5.0,
+            bricks_top: 100.0,
+            lives_easy: 5,
+            lives_normal: 3,
+            lives_hard: 2,
+        }
+    }
+}
+
+// Each key with the line of documentation written above it.
+const DOCS: [(&str, &str); 10] = [
+    (
+        "paddle_speed",
+        "How fast the paddle moves with the keyboard.",
+    ),
+    (
+        "ball_speed",
+        "Serve speed on Normal; Easy and Hard scale it.",
+    ),
+    (
+        "bounce_speedup",
+        "Speed the ball gains from each wall or paddle bounce.",
+    ),
+    (
+        "brick_speedup",
+        "Speed the ball gains from each brick it hits.",
+    ),
+    (
+        "brick_height",
+        "Height of a brick; the width follows from the gap.",
+    ),
+    ("brick_gap", "Space between bricks and around the grid."),
+    (
+        "bricks_top",
+        "Distance from the top of the screen to the first row.",
+    ),
+    ("lives_easy", "Spare lives on Easy."),
+    ("lives_normal", "Spare lives on Normal."),
+    ("lives_hard", "Spare lives on Hard."),
+];
+
+impl Tuning {
+    pub fn from_table(table: &Table) -> Self {
+        let default = Self::default();
+        let float = |key: &str, default: f32| {
+            table
+                .get_float(&format!("gameplay.{}", key))
+                .map_or(default, |v| (v as f32).max(0.0))
+        };
+        let lives = |key: &str, default: usize| {
+            table
+                .get_int(&format!("gameplay.{}", key))
+                .map_or(default, |v| v.clamp(0, 99) as usize)
+        };
+        Self {
+            paddle_speed: float("paddle_speed", default.paddle_speed),
+            ball_speed: float("ball_speed", default.ball_speed).max(1.0),
+            bounce_speedup: float("bounce_speedup", default.bounce_speedup),
+            brick_speedup: float("brick_speedup", default.brick_speedup),
+            brick_height: float("brick_height", default.brick_height).max(1.0),
+            brick_gap: float("brick_gap", default.brick_gap),
+            bricks_top: float("bricks_top", default.bricks_top),
+            lives_easy: lives("lives_easy", default.lives_easy),
+            lives_normal: lives("lives_normal", default.lives_normal),
+            lives_hard: lives("lives_hard", default.lives_hard),
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer) {
+        let values = [
+            Value::Float(self.paddle_speed as f64),
+            Value::Float(self.ball_speed as f64),
+            Value::Float(self.bounce_speedup as f64),
+            Value::Float(self.brick_speedup as f64),
+            Value::Float(self.brick_height as f64),
+            Value::Float(self.brick_gap as f64),
+            Value::Float(self.bricks_top as f64),
+            Value::Int(self.lives_easy as i64),
+            Value::Int(self.lives_normal as i64),
+            Value::Int(self.lives_hard as i64),
+        ];
+        writer.section("gameplay");
+        for ((key, doc), value) in DOCS.iter().zip(values) {
+            writer.comment(doc).value(key, value);
+        }
+    }
+
+    // Bricks fill the width of the screen, `columns` to a row. Levels with a
+    // lot of rows get flatter bricks so they still end above BRICKS_BOTTOM.
+    pub fn brick_size(&self, columns: usize, rows: usize) -> (f32, f32) {
+        let width = (WINDOW_WIDTH - self.brick_gap) / columns as f32 - self.brick_gap;
+        let fit = (BRICKS_BOTTOM - self.bricks_top) / rows.max(1) as f32 - self.brick_gap;
+        (width, self.brick_height.min(fit))
+    }
+
+    pub fn lives(&self, difficulty: Difficulty) -> usize {
+        match difficulty {
+            Difficulty::Easy => self.lives_easy,
+            Difficulty::Normal => self.lives_normal,
+            Difficulty::Hard => self.lives_hard,
+        }
+    }
+}
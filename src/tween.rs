@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+// Progress from 0.0 to 1.0 over a fixed number of seconds, advanced by frame
+// time so it stops along with everything else when the game is paused.
+#[derive(Clone, Copy)]
+pub struct Tween {
+    elapsed: f32,
+    duration: f32,
+}
+
+impl Tween {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            elapsed: 0.0,
+            duration,
+        }
+    }
+
+    pub fn update(&mut self, duration: &Duration) {
+        self.elapsed = (self.elapsed + duration.as_secs_f32()).min(self.duration);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    pub fn linear(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.elapsed / self.duration
+        }
+    }
+
+    // Fast at first, settling gently at the end.
+    pub fn ease_out(&self) -> f32 {
+        1.0 - (1.0 - self.linear()).powi(3)
+    }
+}
@@ -0,0 +1,240 @@
+// Streamer mode: viewers vote in Twitch chat for something to happen to the
+// game. Every round the mutator with the most votes goes off, and a new round
+// starts. Chat is read anonymously over Twitch's IRC gateway on a background
+// thread, so no account or token is needed. Built without the `net` feature
+// streamer mode stays off.
+use crate::config::{Table, Value, Writer};
+use crate::lang::tr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mutator {
+    SpeedUp,
+    ShrinkPaddle,
+    PowerUp,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 3] = [Mutator::SpeedUp, Mutator::ShrinkPaddle, Mutator::PowerUp];
+
+    // What viewers type to vote for it.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Mutator::SpeedUp => "!speed",
+            Mutator::ShrinkPaddle => "!shrink",
+            Mutator::PowerUp => "!powerup",
+        }
+    }
+
+    // What goes up on screen when it goes off.
+    pub fn announcement(&self) -> &'static str {
+        match self {
+            Mutator::SpeedUp => tr!("game.chat_speed_up"),
+            Mutator::ShrinkPaddle => tr!("game.chat_shrink"),
+            Mutator::PowerUp => tr!("game.chat_power_up"),
+        }
+    }
+}
+
+// The `[twitch]` section of pong.toml.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwitchSettings {
+    // Empty means streamer mode is off.
+    pub channel: String,
+    pub vote_seconds: u32,
+}
+
+impl TwitchSettings {
+    pub fn from_table(table: &Table) -> Self {
+        Self {
+            channel: table
+                .get_str("twitch.channel")
+                .unwrap_or("")
+                .trim_start_matches('#')
+                .to_lowercase(),
+            vote_seconds: table
+                .get_int("twitch.vote_seconds")
+                .map_or(30, |s| s.clamp(5, 600) as u32),
+        }
+    }
+
+    pub fn write(&self, writer: &mut Writer) {
+        writer
+            .section("twitch")
+            .comment("a channel name turns on streamer mode; see the README")
+            .value("channel", Value::Str(self.channel.clone()))
+            .value("vote_seconds", Value::Int(self.vote_seconds as i64));
+    }
+}
+
+#[cfg(feature = "net")]
+mod imp {
+    use super::{Mutator, TwitchSettings};
+    use crate::rng::Rng;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    const SERVER: &str = "irc.chat.twitch.tv:6667";
+    const RECONNECT: Duration = Duration::from_secs(10);
+
+    struct Vote {
+        viewer: String,
+        mutator: Mutator,
+    }
+
+    // The votes of the running round. Each viewer counts once per round; voting
+    // again changes their vote.
+    pub struct Chat {
+        channel: String,
+        votes: Receiver<Vote>,
+        round: Duration,
+        left: Duration,
+        ballots: Vec<Vote>,
+    }
+
+    impl Chat {
+        pub fn connect(settings: &TwitchSettings) -> Option<Self> {
+            if settings.channel.is_empty() {
+                return None;
+            }
+            let (sender, votes) = mpsc::channel();
+            let channel = settings.channel.clone();
+            thread::spawn(move || loop {
+                if let Err(e) = listen(&channel, &sender) {
+                    log::warn!("lost Twitch chat: {}", e);
+                }
+                thread::sleep(RECONNECT);
+            });
+            let round = Duration::from_secs(settings.vote_seconds as u64);
+            Some(Self {
+                channel: settings.channel.clone(),
+                votes,
+                round,
+                left: round,
+                ballots: Vec::new(),
+            })
+        }
+
+        // Counts new votes and runs the round clock; returns the winner when a
+        // round with any votes ends.
+        pub fn update(&mut self, duration: &Duration) -> Option<Mutator> {
+            for vote in self.votes.try_iter() {
+                self.ballots.retain(|b| b.viewer != vote.viewer);
+                self.ballots.push(vote);
+            }
+            self.left = self.left.saturating_sub(*duration);
+            if !self.left.is_zero() {
+                return None;
+            }
+            self.left = self.round;
+            // `max_by_key` keeps the last of equals, so going backwards breaks
+            // ties in favour of the mutator listed first.
+            let winner = Mutator::ALL
+                .into_iter()
+                .rev()
+                .filter(|m| self.tally(*m) > 0)
+                .max_by_key(|m| self.tally(*m));
+            self.ballots.clear();
+            winner
+        }
+
+        pub fn tally(&self, mutator: Mutator) -> usize {
+            self.ballots.iter().filter(|b| b.mutator == mutator).count()
+        }
+
+        pub fn round_left(&self) -> Duration {
+            self.left
+        }
+
+        pub fn channel(&self) -> &str {
+            &self.channel
+        }
+    }
+
+    // Reads the channel's chat until the connection drops.
+    fn listen(channel: &str, votes: &Sender<Vote>) -> io::Result<()> {
+        let mut stream = TcpStream::connect(SERVER)?;
+        // Twitch lets anyone read chat as `justinfan` followed by digits.
+        let nick = format!("justinfan{}", Rng::from_time().next_u64() % 100_000);
+        write!(stream, "NICK {}\r\nJOIN #{}\r\n", nick, channel)?;
+        log::info!("reading Twitch chat in #{}", channel);
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let line = line.trim_end();
+            if let Some(token) = line.strip_prefix("PING ") {
+                write!(stream, "PONG {}\r\n", token)?;
+                continue;
+            }
+            let Some((viewer, text)) = parse_message(line) else {
+                continue;
+            };
+            let Some(mutator) = Mutator::ALL
+                .into_iter()
+                .find(|m| text.split_whitespace().next() == Some(m.command()))
+            else {
+                continue;
+            };
+            if votes
+                .send(Vote {
+                    viewer: viewer.to_string(),
+                    mutator,
+                })
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    // `:viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :text`
+    fn parse_message(line: &str) -> Option<(&str, &str)> {
+        let rest = line.strip_prefix(':')?;
+        let (prefix, rest) = rest.split_once(' ')?;
+        let rest = rest.strip_prefix("PRIVMSG ")?;
+        let (_channel, text) = rest.split_once(" :")?;
+        let viewer = prefix.split('!').next()?;
+        Some((viewer, text))
+    }
+}
+
+#[cfg(not(feature = "net"))]
+mod imp {
+    use super::{Mutator, TwitchSettings};
+    use std::time::Duration;
+
+    pub struct Chat;
+
+    impl Chat {
+        pub fn connect(settings: &TwitchSettings) -> Option<Self> {
+            if !settings.channel.is_empty() {
+                log::warn!("built without the net feature, streamer mode is off");
+            }
+            None
+        }
+
+        pub fn update(&mut self, _duration: &Duration) -> Option<Mutator> {
+            None
+        }
+
+        pub fn tally(&self, _mutator: Mutator) -> usize {
+            0
+        }
+
+        pub fn round_left(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        pub fn channel(&self) -> &str {
+            ""
+        }
+    }
+}
+
+pub use imp::Chat;
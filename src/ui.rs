@@ -0,0 +1,523 @@
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::screen::Canvas;
+use crate::text::{draw_centered, Align, Text};
+use crate::WINDOW_WIDTH;
+use raylib::prelude::*;
+use std::ops::Range;
+
+const ITEM_FONT_SIZE: i32 = 40;
+// Rows with a value at the side have the label in a smaller size to fit it.
+const WIDGET_FONT_SIZE: i32 = 32;
+const VALUE_FONT_SIZE: i32 = 26;
+const ITEM_SPACING: f32 = 60.0;
+const ITEM_WIDTH: f32 = 480.0;
+// The part of a row on the right that shows its value.
+const VALUE_WIDTH: f32 = 260.0;
+const SLIDER_HEIGHT: f32 = 10.0;
+const SWITCH_WIDTH: f32 = 56.0;
+const SWITCH_HEIGHT: f32 = 28.0;
+const HEADING_SIZE: i32 = 70;
+const HINT_SIZE: i32 = 20;
+
+// What a row of a list is, which decides how it is drawn and what pressing
+// it does.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Widget {
+    // Does something when pressed.
+    Button,
+    // On or off, flipped by pressing it or with left and right.
+    Toggle(bool),
+    // A percentage, drawn as a bar and stepped with left and right.
+    Slider(u8),
+    // One of a few values, shown by name and stepped with left and right.
+    Choice(String),
+}
+
+pub struct Item {
+    pub label: String,
+    pub widget: Widget,
+    pub enabled: bool,
+}
+
+impl Item {
+    pub fn button(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            widget: Widget::Button,
+            enabled: true,
+        }
+    }
+}
+
+// What happened to a list this frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    // A button was pressed.
+    Pressed(usize),
+    // The value of a toggle, slider or choice should step by -1 or +1.
+    Changed(usize, isize),
+}
+
+// A column of widgets with one of them focused. Up and down move the focus,
+// skipping disabled rows, and so does pointing at a row with the mouse.
+// Confirm or a click presses the focused row, and left and right step its
+// value.
+pub struct List {
+    items: Vec<Item>,
+    selected: usize,
+    top: f32,
+    width: f32,
+    spacing: f32,
+    visible: usize,
+    scroll: usize,
+}
+
+impl List {
+    pub fn new(items: Vec<Item>, top: f32) -> Self {
+        let selected = items.iter().position(|i| i.enabled).unwrap_or(0);
+        Self {
+            visible: items.len(),
+            items,
+            selected,
+            top,
+            width: ITEM_WIDTH,
+            spacing: ITEM_SPACING,
+            scroll: 0,
+        }
+    }
+
+    // Shows at most `rows` items at a time, scrolling to follow the selection.
+    pub fn scrolling(mut self, rows: usize) -> Self {
+        self.visible = rows.max(1);
+        self.keep_visible();
+        self
+    }
+
+    // Rows with values at the side need more room than buttons do.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    // Long menus can pack their rows closer together to fit on screen.
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.items.get(index).is_some_and(|i| i.enabled) {
+            self.selected = index;
+            self.keep_visible();
+        }
+    }
+
+    pub fn set(&mut self, index: usize, label: impl Into<String>, widget: Widget) {
+        self.items[index].label = label.into();
+        self.items[index].widget = widget;
+    }
+
+    // Labels the items in order, for menus whose labels only change with the
+    // language.
+    pub fn set_labels(&mut self, labels: &[&str]) {
+        for (item, label) in self.items.iter_mut().zip(labels) {
+            item.label = label.to_string();
+        }
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.items[index].enabled = enabled;
+        if !self.items[self.selected].enabled {
+            self.step(1);
+        }
+    }
+
+    fn visible_range(&self) -> Range<usize> {
+        self.scroll..(self.scroll + self.visible).min(self.items.len())
+    }
+
+    fn keep_visible(&mut self) {
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + self.visible {
+            self.scroll = self.selected + 1 - self.visible;
+        }
+    }
+
+    // Only meaningful for items inside `visible_range`.
+    fn item_rect(&self, index: usize) -> Rectangle {
+        Rectangle {
+            x: WINDOW_WIDTH / 2.0 - self.width / 2.0,
+            y: self.top + (index - self.scroll) as f32 * self.spacing,
+            width: self.width,
+            height: self.spacing,
+        }
+    }
+
+    fn value_rect(&self, index: usize) -> Rectangle {
+        let rect = self.item_rect(index);
+        Rectangle {
+            x: rect.x + rect.width - VALUE_WIDTH,
+            width: VALUE_WIDTH,
+            ..rect
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        let len = self.items.len() as isize;
+        let mut index = self.selected as isize;
+        for _ in 0..len {
+            index = (index + delta).rem_euclid(len);
+            if self.items[index as usize].enabled {
+                self.selected = index as usize;
+                self.keep_visible();
+                return;
+            }
+        }
+    }
+
+    // Pressing a row with a value steps it up, so a list can be used with
+    // Confirm alone.
+    fn press(&self, index: usize, delta: isize) -> Event {
+        match self.items[index].widget {
+            Widget::Button => Event::Pressed(index),
+            _ => Event::Changed(index, delta),
+        }
+    }
+
+    pub fn update(&mut self, input: &Input) -> Option<Event> {
+        if input.pressed(Action::Up) {
+            self.step(-1);
+        }
+        if input.pressed(Action::Down) {
+            self.step(1);
+        }
+
+        let hovered = self.visible_range().find(|i| {
+            self.items[*i].enabled && self.item_rect(*i).check_collision_point_rec(input.mouse)
+        });
+        if let Some(index) = hovered {
+            if input.mouse_moved {
+                self.selected = index;
+            }
+            if input.mouse_clicked {
+                self.selected = index;
+                // Clicking the left half of a slider or choice steps it down.
+                let value = self.value_rect(index);
+                let delta = match self.items[index].widget {
+                    Widget::Slider(_) | Widget::Choice(_)
+                        if input.mouse.x < value.x + value.width / 2.0 =>
+                    {
+                        -1
+                    }
+                    _ => 1,
+                };
+                return Some(self.press(index, delta));
+            }
+        }
+
+        let item = &self.items[self.selected];
+        if !item.enabled {
+            return None;
+        }
+        if input.pressed(Action::Confirm) {
+            return Some(self.press(self.selected, 1));
+        }
+        let delta = input.pressed(Action::Right) as isize - input.pressed(Action::Left) as isize;
+        if delta != 0 && item.widget != Widget::Button {
+            return Some(Event::Changed(self.selected, delta));
+        }
+        None
+    }
+
+    pub fn render(&self, d: &mut Canvas, font: &ffi::Font) {
+        for i in self.visible_range() {
+            let item = &self.items[i];
+            let color = if !item.enabled {
+                Color::DARKGRAY
+            } else if i == self.selected {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            let rect = self.item_rect(i);
+            if item.widget == Widget::Button {
+                let y = rect.y + (rect.height - ITEM_FONT_SIZE as f32) / 2.0;
+                draw_centered(d, font, &item.label, y as i32, ITEM_FONT_SIZE, color);
+                continue;
+            }
+            let y = rect.y + (rect.height - WIDGET_FONT_SIZE as f32) / 2.0;
+            Text::new(&item.label, WIDGET_FONT_SIZE, color).draw(d, font, rect.x as i32, y as i32);
+            draw_value(d, font, &item.widget, self.value_rect(i), color);
+        }
+
+        let bottom = self.top + self.visible as f32 * self.spacing;
+        draw_scroll_arrows(
+            d,
+            self.scroll > 0,
+            self.scroll + self.visible < self.items.len(),
+            self.top - 4.0,
+            bottom + 4.0,
+        );
+    }
+}
+
+fn draw_value(d: &mut Canvas, font: &ffi::Font, widget: &Widget, rect: Rectangle, color: Color) {
+    let middle = rect.y + rect.height / 2.0;
+    let text_y = (middle - VALUE_FONT_SIZE as f32 / 2.0) as i32;
+    let right = (rect.x + rect.width) as i32;
+    match widget {
+        Widget::Button => (),
+        Widget::Toggle(on) => {
+            let switch = Rectangle {
+                x: rect.x + rect.width - SWITCH_WIDTH,
+                y: middle - SWITCH_HEIGHT / 2.0,
+                width: SWITCH_WIDTH,
+                height: SWITCH_HEIGHT,
+            };
+            let radius = SWITCH_HEIGHT / 2.0 - 4.0;
+            let knob_x = if *on {
+                switch.x + switch.width - SWITCH_HEIGHT / 2.0
+            } else {
+                switch.x + SWITCH_HEIGHT / 2.0
+            };
+            if *on {
+                d.draw_rectangle_rounded(switch, 1.0, 8, color.alpha(0.35));
+            }
+            d.draw_rectangle_rounded_lines(switch, 1.0, 8, 2.0, color);
+            d.draw_circle_v(Vector2::new(knob_x, middle), radius, color);
+            let text = if *on {
+                tr!("settings.on")
+            } else {
+                tr!("settings.off")
+            };
+            Text::new(text, VALUE_FONT_SIZE, color)
+                .align(Align::Right)
+                .draw(d, font, switch.x as i32 - 12, text_y);
+        }
+        Widget::Slider(percent) => {
+            let text = format!("{}%", percent);
+            let bar = Rectangle {
+                x: rect.x,
+                y: middle - SLIDER_HEIGHT / 2.0,
+                width: rect.width - 80.0,
+                height: SLIDER_HEIGHT,
+            };
+            d.draw_rectangle_lines_ex(bar, 2.0, color);
+            d.draw_rectangle_rec(
+                Rectangle {
+                    width: bar.width * *percent as f32 / 100.0,
+                    ..bar
+                },
+                color,
+            );
+            Text::new(&text, VALUE_FONT_SIZE, color)
+                .align(Align::Right)
+                .draw(d, font, right, text_y);
+        }
+        Widget::Choice(value) => {
+            let centre = rect.x + rect.width / 2.0;
+            Text::new(value, VALUE_FONT_SIZE, color)
+                .align(Align::Center)
+                .draw(d, font, centre as i32, text_y);
+            // Arrows either side say it can be stepped both ways.
+            let (left, right) = (rect.x + 6.0, rect.x + rect.width - 6.0);
+            d.draw_triangle(
+                Vector2::new(left + 10.0, middle + 8.0),
+                Vector2::new(left + 10.0, middle - 8.0),
+                Vector2::new(left, middle),
+                color,
+            );
+            d.draw_triangle(
+                Vector2::new(right - 10.0, middle - 8.0),
+                Vector2::new(right - 10.0, middle + 8.0),
+                Vector2::new(right, middle),
+                color,
+            );
+        }
+    }
+}
+
+// Tiles laid out in rows of `columns`, centred on the screen, with one of
+// them focused. The arrow keys move the focus about the grid and so does
+// pointing with the mouse; once there are more rows than fit, the grid
+// scrolls to follow it.
+pub struct Grid {
+    count: usize,
+    columns: usize,
+    rows: usize,
+    tile: (f32, f32),
+    spacing: f32,
+    top: f32,
+    selected: usize,
+    // The first row on screen.
+    scroll: usize,
+}
+
+impl Grid {
+    pub fn new(count: usize, columns: usize, rows: usize, tile: (f32, f32), spacing: f32) -> Self {
+        Self {
+            count,
+            columns,
+            rows,
+            tile,
+            spacing,
+            top: 0.0,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn top(mut self, top: f32) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn visible(&self) -> Range<usize> {
+        self.scroll * self.columns..((self.scroll + self.rows) * self.columns).min(self.count)
+    }
+
+    // Only meaningful for tiles inside `visible`.
+    pub fn rect(&self, index: usize) -> Rectangle {
+        let (width, height) = self.tile;
+        let grid_width = self.columns as f32 * width + (self.columns - 1) as f32 * self.spacing;
+        Rectangle {
+            x: (WINDOW_WIDTH - grid_width) / 2.0
+                + (index % self.columns) as f32 * (width + self.spacing),
+            y: self.top + (index / self.columns - self.scroll) as f32 * (height + self.spacing),
+            width,
+            height,
+        }
+    }
+
+    fn step(&mut self, delta: isize) {
+        let index = self.selected as isize + delta;
+        if (0..self.count as isize).contains(&index) {
+            self.selected = index as usize;
+        }
+        let row = self.selected / self.columns;
+        self.scroll = self.scroll.clamp(row.saturating_sub(self.rows - 1), row);
+    }
+
+    // Returns the tile pressed this frame, if any.
+    pub fn update(&mut self, input: &Input) -> Option<usize> {
+        let columns = self.columns as isize;
+        for (action, delta) in [
+            (Action::Left, -1),
+            (Action::Right, 1),
+            (Action::Up, -columns),
+            (Action::Down, columns),
+        ] {
+            if input.pressed(action) {
+                self.step(delta);
+            }
+        }
+
+        let mut pressed = input.pressed(Action::Confirm);
+        if let Some(index) = self
+            .visible()
+            .find(|i| self.rect(*i).check_collision_point_rec(input.mouse))
+        {
+            if input.mouse_moved || input.mouse_clicked {
+                self.selected = index;
+            }
+            pressed |= input.mouse_clicked;
+        }
+        pressed.then_some(self.selected)
+    }
+
+    pub fn render_arrows(&self, d: &mut Canvas) {
+        let bottom = self.top + self.rows as f32 * (self.tile.1 + self.spacing);
+        draw_scroll_arrows(
+            d,
+            self.scroll > 0,
+            self.visible().end < self.count,
+            self.top - 20.0,
+            bottom - 10.0,
+        );
+    }
+}
+
+// Arrows hinting at rows scrolled out of view above `top` or below `bottom`.
+fn draw_scroll_arrows(d: &mut Canvas, above: bool, below: bool, top: f32, bottom: f32) {
+    let x = WINDOW_WIDTH / 2.0;
+    if above {
+        d.draw_triangle(
+            Vector2::new(x, top - 12.0),
+            Vector2::new(x - 12.0, top),
+            Vector2::new(x + 12.0, top),
+            Color::GRAY,
+        );
+    }
+    if below {
+        d.draw_triangle(
+            Vector2::new(x - 12.0, bottom),
+            Vector2::new(x, bottom + 12.0),
+            Vector2::new(x + 12.0, bottom),
+            Color::GRAY,
+        );
+    }
+}
+
+// One bar of a chart: what it is, how long it is, and the value written at
+// its end.
+pub struct Bar {
+    pub label: String,
+    pub value: f32,
+    pub text: String,
+}
+
+// Bars side by side in `rect`, one row each, with their labels down the left
+// and lengths scaled so the longest fills the rest of the width.
+pub fn bar_chart(d: &mut Canvas, font: &ffi::Font, rect: Rectangle, bars: &[Bar], color: Color) {
+    const LABEL_WIDTH: f32 = 200.0;
+    const TEXT_WIDTH: f32 = 110.0;
+    let Some(max) = bars.iter().map(|b| b.value).reduce(f32::max) else {
+        return;
+    };
+    let row = rect.height / bars.len() as f32;
+    let full = rect.width - LABEL_WIDTH - TEXT_WIDTH;
+    for (i, bar) in bars.iter().enumerate() {
+        let y = rect.y + i as f32 * row;
+        let text_y = (y + row / 2.0 - VALUE_FONT_SIZE as f32 / 2.0) as i32;
+        Text::new(&bar.label, VALUE_FONT_SIZE, Color::WHITE).draw(d, font, rect.x as i32, text_y);
+        let length = if max > 0.0 {
+            full * bar.value / max
+        } else {
+            0.0
+        };
+        let bar_rect = Rectangle {
+            x: rect.x + LABEL_WIDTH,
+            y: y + row * 0.2,
+            width: length,
+            height: row * 0.6,
+        };
+        d.draw_rectangle_rec(bar_rect, color);
+        Text::new(&bar.text, VALUE_FONT_SIZE, Color::LIGHTGRAY).draw(
+            d,
+            font,
+            (bar_rect.x + length + 10.0) as i32,
+            text_y,
+        );
+    }
+}
+
+// The big title at the top of a menu screen.
+pub fn heading(d: &mut Canvas, font: &ffi::Font, text: &str, y: i32) {
+    draw_centered(d, font, text, y, HEADING_SIZE, Color::YELLOW);
+}
+
+// A line of small print under a menu, saying what the keys do or what the
+// focused row means.
+pub fn hint(d: &mut Canvas, font: &ffi::Font, text: &str, y: i32) {
+    draw_centered(d, font, text, y, HINT_SIZE, Color::GRAY);
+}
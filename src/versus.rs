@@ -0,0 +1,78 @@
+use crate::game::Game;
+use crate::input::{Action, Input};
+use crate::lang::tr;
+use crate::net::{Phase, Status};
+use crate::presence::Activity;
+use crate::scene::{Context, Scene, Transition};
+use crate::screen::Canvas;
+use crate::text::draw_centered;
+use raylib::prelude::*;
+use std::time::Duration;
+
+// Waits until the other player is in their lobby too, then starts the race
+// on the level and seed the host picked.
+pub struct Lobby {
+    // Set when the host's level isn't one this player has.
+    missing_level: Option<usize>,
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        Self {
+            missing_level: None,
+        }
+    }
+}
+
+impl Scene for Lobby {
+    fn update(&mut self, ctx: &mut Context, input: &Input, _duration: &Duration) -> Transition {
+        if input.pressed(Action::Back) {
+            ctx.peer = None;
+            return Transition::Pop;
+        }
+        let Some(peer) = ctx.peer.as_mut() else {
+            return Transition::Pop;
+        };
+        let opponent = peer.opponent().copied();
+        let level = peer.host_level().or(opponent.map(|o| o.level)).unwrap_or(0);
+        let seed = peer.host_seed().or(opponent.map(|o| o.seed)).unwrap_or(0);
+        peer.update(&Status::waiting(level, seed));
+
+        let ready = opponent.is_some_and(|o| matches!(o.phase, Phase::Waiting | Phase::Playing));
+        if !ready {
+            return Transition::None;
+        }
+        if level >= ctx.levels.len() {
+            self.missing_level = Some(level);
+            return Transition::None;
+        }
+        Transition::fade(Transition::Replace(Box::new(Game::versus(
+            ctx, level, seed,
+        ))))
+    }
+
+    fn render(&self, ctx: &Context, d: &mut Canvas) {
+        let font = ctx.font();
+        d.clear_background(Color::BLACK);
+        draw_centered(d, font, tr!("versus.title"), 60, 70, Color::YELLOW);
+        let message = match (&ctx.peer, self.missing_level) {
+            (_, Some(level)) => tr!("versus.missing_level", level + 1, ctx.levels.len()),
+            (Some(peer), None) if peer.host_level().is_some() => {
+                tr!("versus.waiting", peer.describe())
+            }
+            (Some(peer), None) => tr!("versus.connecting", peer.describe()),
+            (None, None) => String::new(),
+        };
+        draw_centered(d, font, &message, 300, 30, Color::WHITE);
+        draw_centered(d, font, tr!("versus.rules"), 360, 24, Color::LIGHTGRAY);
+        draw_centered(d, font, tr!("versus.hint"), 650, 20, Color::GRAY);
+    }
+
+    fn activity(&self, _ctx: &Context) -> Option<Activity> {
+        Some(Activity {
+            details: tr!("presence.versus").into(),
+            state: String::new(),
+            elapsed: Duration::ZERO,
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Polls a directory tree on a background thread and reports files that were
+// added, changed or removed since the last look.
+pub struct Watcher {
+    changes: Receiver<PathBuf>,
+}
+
+impl Watcher {
+    pub fn spawn(dir: PathBuf) -> Self {
+        let (sender, changes) = mpsc::channel();
+        thread::spawn(move || watch(&dir, sender));
+        Self { changes }
+    }
+
+    pub fn changed(&self) -> Vec<PathBuf> {
+        self.changes.try_iter().collect()
+    }
+}
+
+fn watch(dir: &Path, sender: Sender<PathBuf>) {
+    let mut known = scan(dir);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = scan(dir);
+        let changed = current
+            .iter()
+            .filter(|(path, time)| known.get(*path) != Some(time))
+            .map(|(path, _)| path)
+            .chain(known.keys().filter(|path| !current.contains_key(*path)));
+        for path in changed {
+            // The game has quit.
+            if sender.send(path.clone()).is_err() {
+                return;
+            }
+        }
+        known = current;
+    }
+}
+
+fn scan(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let mut files = HashMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            files.extend(scan(&path));
+        } else if let Ok(modified) = metadata.modified() {
+            files.insert(path, modified);
+        }
+    }
+    files
+}
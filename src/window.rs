@@ -0,0 +1,111 @@
+use crate::error::Error;
+use raylib::prelude::*;
+use std::panic;
+use std::path::Path;
+
+const ICON_FILE: &str = "assets/icon.png";
+const ICON_SIZE: i32 = 64;
+pub const BASE_TITLE: &str = "Pong";
+
+// raylib doesn't expose taskbar flashing, but the GLFW it is built with does,
+// and raylib keeps its window's context current on the main thread.
+#[cfg(not(target_os = "emscripten"))]
+extern "C" {
+    fn glfwGetCurrentContext() -> *mut std::ffi::c_void;
+    fn glfwRequestWindowAttention(window: *mut std::ffi::c_void);
+}
+
+// Keeps the window title in sync with the game without resetting it every
+// frame.
+pub struct Window {
+    title: String,
+}
+
+// Opens the resizable game window. raylib-rs panics when there is no
+// display or the drivers can't give it an OpenGL context, so the panic is
+// caught and turned into an error the player can read.
+pub fn open(
+    width: i32,
+    height: i32,
+    log_level: TraceLogLevel,
+) -> Result<(RaylibHandle, RaylibThread), Error> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| ()));
+    let handles = panic::catch_unwind(|| {
+        raylib::init()
+            .size(width, height)
+            .title(BASE_TITLE)
+            .resizable()
+            .log_level(log_level)
+            .build()
+    });
+    panic::set_hook(hook);
+    handles.map_err(|_| Error::Window)
+}
+
+impl Window {
+    pub fn new(rl: &mut RaylibHandle) -> Self {
+        rl.set_window_icon(icon());
+        Self {
+            title: BASE_TITLE.to_string(),
+        }
+    }
+
+    pub fn set_title(&mut self, rl: &RaylibHandle, thread: &RaylibThread, title: String) {
+        if title != self.title {
+            rl.set_window_title(thread, &title);
+            self.title = title;
+        }
+    }
+
+    // Flashes the taskbar entry, but only when the player is looking elsewhere.
+    #[cfg(not(target_os = "emscripten"))]
+    pub fn request_attention(&self, rl: &RaylibHandle) {
+        if rl.is_window_focused() {
+            return;
+        }
+        unsafe {
+            let window = glfwGetCurrentContext();
+            if !window.is_null() {
+                glfwRequestWindowAttention(window);
+            }
+        }
+    }
+
+    // A browser tab has no taskbar entry to flash.
+    #[cfg(target_os = "emscripten")]
+    pub fn request_attention(&self, _rl: &RaylibHandle) {}
+}
+
+// `assets/icon.png` if there is one, otherwise a tiny paddle, ball and bricks.
+fn icon() -> Image {
+    if Path::new(ICON_FILE).exists() {
+        match Image::load_image(ICON_FILE) {
+            Ok(image) => return image,
+            Err(e) => log::warn!("could not load {}: {}", ICON_FILE, e),
+        }
+    }
+    let mut image = Image::gen_image_color(ICON_SIZE, ICON_SIZE, Color::BLACK);
+    let colors = [Color::RED, Color::ORANGE, Color::YELLOW];
+    for (row, color) in colors.iter().enumerate() {
+        for column in 0..3 {
+            image.draw_rectangle(4 + column * 20, 6 + row as i32 * 10, 16, 7, color);
+        }
+    }
+    image.draw_circle(38, 42, 5, Color::WHITE);
+    image.draw_rectangle(14, 54, 36, 6, Color::SKYBLUE);
+    image
+}
+
+// 12400 becomes "12,400".
+pub fn group_thousands(n: u32) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}